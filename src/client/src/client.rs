@@ -4,7 +4,7 @@ use reqwest::{Client, Error};
 use schema::block::SignedBlock;
 use schema::signed_transaction::SignedTransaction;
 use schema::state::State;
-use schema::transaction_pool::{TxnPool, TxnPoolKeyType, POOL};
+use schema::transaction_pool::{TxnPool, POOL};
 use std::collections::HashMap;
 use std::net::IpAddr;
 use utils::global_peer_data::{PeerData, GLOBALDATA};
@@ -432,13 +432,11 @@ impl ClientObj {
             if None == POOL.get(each) {
                 match self.fetch_transaction(&peer_url, each) {
                     Ok(is_txn) => match is_txn {
-                        Some(txn) => match txn.header.get(&String::from("timestamp")) {
-                            Some(string) => match string.parse::<TxnPoolKeyType>() {
-                                Ok(timestamp) => POOL.insert_op(&timestamp, &txn),
-                                Err(_) => return false,
-                            },
-                            None => return false,
-                        },
+                        Some(txn) => {
+                            if POOL.insert_op(&txn).is_err() {
+                                return false;
+                            }
+                        }
                         None => return false,
                     },
                     Err(_) => return false,
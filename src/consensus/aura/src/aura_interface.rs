@@ -6,16 +6,18 @@ extern crate utils;
 use super::aura_message_sender::AuraMessageSender;
 use super::aura_messages::{AuraMessageTypes, AuthorBlock, BlockAcceptance, RoundOwner};
 use super::config::initialize_config;
+use consensus_engine::{set_genesis_states, stored_height, BlockImporter, ConsensusEngine};
 use db_service::db_fork_ref::SchemaFork;
-use db_service::db_layer::{fork_db, patch_db, snapshot_db};
+use db_service::db_layer::DbInstance;
 use db_service::db_snapshot_ref::SchemaSnap;
 use exonum_merkledb::{ObjectHash, Snapshot};
 use futures::{channel::mpsc::*, executor::*, future, prelude::*, task::*};
+use governance::state::{genesis_validator_state, validators_at};
 
 use message_handler::messages::MessageTypes;
 use schema::block::SignedBlock;
 use schema::transaction_pool::{TxnPool, POOL};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -24,19 +26,26 @@ use utils::configreader::Configuration;
 use utils::keypair::KeypairType;
 use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
 
+#[derive(Clone)]
 pub struct Aura {
     // peer identity
     keypair: KeypairType,
     // peer public key
     pk: String,
-    // validator's details
-    validator_mapping: HashMap<String, u64>,
     // 10th part of step_time (in millis)
     leader_epoch: u64,
     // empty block acceptance
     force_sealing: bool,
     // last start time of consensus (in seconds)
     start_time: u64,
+    // storage handle of the node
+    db: DbInstance,
+    // data sender in P2P system
+    sender: Sender<Option<MessageTypes>>,
+    // consensus key-details shared with message handling
+    meta_data: Arc<Mutex<MetaData>>,
+    // waiting blocks shared with message handling
+    waiting_blocks_queue: Arc<Mutex<WaitingBLocksQueue>>,
 }
 
 /// WaitingBLocksQueue will store waiting block queue and
@@ -64,10 +73,6 @@ impl WaitingBLocksQueue {
 
 /// AURA consensus key-details
 pub struct MetaData {
-    // validator's pool size
-    validator_pool_size: u64,
-    // validator's public_key mapping with auther_ordering number
-    validator_mapping: HashMap<String, u64>,
     // peer keypair
     kp: KeypairType,
     // data sender in P2P system
@@ -82,6 +87,8 @@ pub struct MetaData {
     block_queue_size: usize,
     // peer public key
     public_key: String,
+    // storage handle of the node
+    db: DbInstance,
 }
 
 // AURA consensus custom headers for the signed block
@@ -91,40 +98,30 @@ pub struct CustomHeaders {
     round_number: u64,
 }
 
-impl Aura {
-    // init_state will create genesis block if predefined storage is empty
-    // or if storage is not empty it will start from previous state
-    // read genesis block details from config file (future work)
-    fn init_state(&mut self, _db_path: &String, _sender: &mut Sender<Option<MessageTypes>>) {
-        let fork = fork_db();
-        {
-            let mut schema = SchemaFork::new(&fork);
-            if schema.blockchain_length() == 0 {
-                let custom_headers: CustomHeaders = CustomHeaders {
-                    timestamp: self.start_time,
-                    round_number: 0,
-                };
-                let custom_headers: Vec<u8> = match serialize(&custom_headers) {
-                    Ok(value) => value,
-                    Err(_) => Vec::new(),
-                };
-                let genesis_signed_block =
-                    schema.initialize_db(custom_headers, self.start_time as u128);
-                info!(
-                    "genesis block created with hash {:?}",
-                    genesis_signed_block.get_hash()
-                );
-            } else {
-                info!(
-                    "started from previous state {} {}",
-                    schema.blockchain_length(),
-                    schema.state_trie_merkle_hash()
-                )
+impl MetaData {
+    // fn will resolve validators of block at given height in author order from stored chain
+    // state, changes voted through governance app are picked up at their activation height.
+    // It is empty if the set wasn't seeded at genesis
+    fn validators(&self, height: u64) -> Vec<String> {
+        let snapshot: Box<dyn Snapshot> = self.db.snapshot();
+        let schema = SchemaSnap::new(&snapshot);
+        validators_at(&schema, height).unwrap_or_default()
+    }
+
+    // fn will compute height of the block proposed on top of waiting blocks
+    fn next_height(&self, waiting_blocks_queue: &WaitingBLocksQueue) -> u64 {
+        match waiting_blocks_queue.queue.last() {
+            Some(last_waiting_block) => last_waiting_block.block.id + 1,
+            None => {
+                let snapshot: Box<dyn Snapshot> = self.db.snapshot();
+                let schema = SchemaSnap::new(&snapshot);
+                schema.get_blockchain_length()
             }
         }
-        patch_db(fork);
     }
+}
 
+impl Aura {
     // fn will compute what is the round number at present time
     fn calculate_round_number(meta_data: &MetaData) -> u64 {
         let current_epoch: u64 = SystemTime::now()
@@ -136,28 +133,30 @@ impl Aura {
         round_count
     }
 
-    // fn will compute what is the round leader at present time
-    fn primary_leader(meta_data: &MetaData) -> String {
+    // fn will compute what is the round leader at present time for block at given height,
+    // None if there is no validator
+    fn primary_leader(meta_data: &MetaData, height: u64) -> Option<String> {
         let round_count = Aura::calculate_round_number(meta_data);
-        let leader_id: u64 = round_count
-            - (round_count / meta_data.validator_pool_size) * meta_data.validator_pool_size;
-        for (pk, id) in meta_data.validator_mapping.iter() {
-            if id.clone() == leader_id {
-                return pk.clone();
-            }
+        let validators: Vec<String> = meta_data.validators(height);
+        if validators.is_empty() {
+            return None;
         }
-        panic!("Should match current identity with anyone validator identity");
+        let leader_id: u64 = round_count % validators.len() as u64;
+        Some(validators[leader_id as usize].clone())
     }
 
-    // fn will process incoming AuthorBlockEnum data
+    // fn will process incoming AuthorBlockEnum data, block state was checked by the engine
+    // on top of waiting blocks before
     fn handle_author_block_enum(
         author_block: AuthorBlock,
+        state_verified: bool,
         waiting_blocks_queue: &mut WaitingBLocksQueue,
         meta_data_obj: &mut MetaData,
     ) {
-        let current_leader: String = Aura::primary_leader(&meta_data_obj);
+        let height: u64 = meta_data_obj.next_height(waiting_blocks_queue);
+        let current_leader: Option<String> = Aura::primary_leader(&meta_data_obj, height);
         // only rightful auther should propose block
-        if current_leader != author_block.block.block.peer_id.clone() {
+        if current_leader.as_ref() != Some(&author_block.block.block.peer_id) {
             info!("malicious author proposing block!");
             return;
         }
@@ -252,7 +251,7 @@ impl Aura {
                 }
             }
             None => {
-                let snapshot: Box<dyn Snapshot> = snapshot_db();
+                let snapshot: Box<dyn Snapshot> = meta_data_obj.db.snapshot();
                 {
                     let schema = SchemaSnap::new(&snapshot);
                     // if schema.get_blockchain_length() != author_block.block.block.id {
@@ -342,9 +341,13 @@ impl Aura {
                 }
             }
         }
-        // we cannot validate block state
-        // let author_block: AuthorBlock = AuthorBlock::create(signed_block.clone());
-        // AuraMessageSender::send_author_block_msg(sender, author_block);
+        if !state_verified {
+            warn!(
+                "block state proposed by author {:?} couldn't verified!",
+                author_block.block.block.peer_id
+            );
+            return;
+        }
         let block_acceptance: BlockAcceptance =
             BlockAcceptance::create(&meta_data_obj.kp, author_block.block.get_hash());
         AuraMessageSender::send_block_acceptance_msg(&mut meta_data_obj.sender, block_acceptance);
@@ -371,10 +374,13 @@ impl Aura {
         waiting_blocks_queue: &mut WaitingBLocksQueue,
         meta_data_obj: &MetaData,
     ) {
-        // data coming from verifed validator
+        // data coming from verifed validator of the last waiting block
+        let height: u64 = meta_data_obj
+            .next_height(waiting_blocks_queue)
+            .saturating_sub(1);
         if !meta_data_obj
-            .validator_mapping
-            .contains_key(&block_acceptance.public_key)
+            .validators(height)
+            .contains(&block_acceptance.public_key)
         {
             warn!(
                 "Data coming from untrusted source {:?}",
@@ -421,8 +427,9 @@ impl Aura {
             info!("no waiting block to check aceeptance");
             return;
         }
-        let current_owner: String = Aura::primary_leader(&meta_data_obj);
-        if current_owner != round_owner.public_key {
+        let height: u64 = meta_data_obj.next_height(waiting_blocks_queue);
+        let current_owner: Option<String> = Aura::primary_leader(&meta_data_obj, height);
+        if current_owner.as_ref() != Some(&round_owner.public_key) {
             warn!(
                 "malicious round owner claim created by {:?}",
                 round_owner.public_key
@@ -436,7 +443,9 @@ impl Aura {
                     waiting_blocks_queue.last_block_acceptance
                 );
                 let got_votes = waiting_blocks_queue.last_block_acceptance.len() as u64;
-                let minimum_votes: u64 = (meta_data_obj.validator_pool_size * 2) / 3;
+                // votes are counted for the last waiting block
+                let minimum_votes: u64 =
+                    (meta_data_obj.validators(height - 1).len() as u64 * 2) / 3;
                 if minimum_votes <= got_votes {
                     waiting_blocks_queue.last_block_acceptance.clear();
                     waiting_blocks_queue.last_block_hash = String::from("temp_hash");
@@ -462,8 +471,12 @@ impl Aura {
     }
 
     // fn will update waiting blocks in sequence to local db
-    fn process_blocks(blocks_count: usize, waiting_blocks_queue: &mut WaitingBLocksQueue) {
-        let fork = fork_db();
+    fn process_blocks(
+        blocks_count: usize,
+        waiting_blocks_queue: &mut WaitingBLocksQueue,
+        db: &DbInstance,
+    ) {
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             for each in waiting_blocks_queue.queue.iter() {
@@ -473,7 +486,7 @@ impl Aura {
             }
         }
         let mut blocks_count = blocks_count;
-        let fork = fork_db();
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             while blocks_count > 0 {
@@ -518,7 +531,7 @@ impl Aura {
             }
         }
         if blocks_count == 0 {
-            patch_db(fork);
+            db.patch(fork);
             info!("Blocks are updated in the database");
         }
     }
@@ -532,7 +545,11 @@ impl Aura {
         if queue_length > meta_data_obj.block_queue_size + 1 {
             info!("queue length {:?}", queue_length);
             let blocks_to_be_confirmed: usize = queue_length / 3 * 2;
-            Aura::process_blocks(blocks_to_be_confirmed, waiting_blocks_queue);
+            Aura::process_blocks(
+                blocks_to_be_confirmed,
+                waiting_blocks_queue,
+                &meta_data_obj.db,
+            );
             info!(
                 "after processing queue length {:?}",
                 waiting_blocks_queue.queue.len()
@@ -541,11 +558,10 @@ impl Aura {
     }
 
     // fn will listen incoming data from other peers via P2P system
-    fn aura_msg_receiver(
-        waiting_blocks_queue: Arc<Mutex<WaitingBLocksQueue>>,
-        meta_data: Arc<Mutex<MetaData>>,
-        rx: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
-    ) {
+    fn aura_msg_receiver(engine: Aura, rx: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        let waiting_blocks_queue: Arc<Mutex<WaitingBLocksQueue>> =
+            engine.waiting_blocks_queue.clone();
+        let meta_data: Arc<Mutex<MetaData>> = engine.meta_data.clone();
         thread::spawn(move || {
             block_on(future::poll_fn(move |cx: &mut Context| {
                 loop {
@@ -560,11 +576,15 @@ impl Aura {
                                         AuraMessageTypes::AuthorBlockEnum(data) => {
                                             let author_block: AuthorBlock = data;
                                             info!("AuthorBlock data received");
+                                            // block state is checked before locking
+                                            let state_verified: bool =
+                                                engine.verify_block(&author_block.block);
                                             let mut waiting_blocks_queue_obj =
                                                 waiting_blocks_queue.lock().unwrap();
                                             let mut meta_data_obj = meta_data.lock().unwrap();
                                             Aura::handle_author_block_enum(
                                                 author_block,
+                                                state_verified,
                                                 &mut waiting_blocks_queue_obj,
                                                 &mut meta_data_obj,
                                             );
@@ -610,13 +630,13 @@ impl Aura {
     }
 
     // fn will create new block to propose after processing waiting blocks
-    fn propose_block(
+    fn compute_block(
         &self,
         waiting_blocks_queue: &mut WaitingBLocksQueue,
         meta_data: &MetaData,
     ) -> SignedBlock {
         loop {
-            let fork = fork_db();
+            let fork = self.db.fork();
             let mut schema = SchemaFork::new(&fork);
             let mut update_success_flag: bool = true;
             for each_block in waiting_blocks_queue.queue.iter() {
@@ -644,19 +664,19 @@ impl Aura {
                     return schema.create_block(&self.keypair, custom_headers);
                 } else {
                     let (_fork_instance, signed_block) =
-                        schema.forge_new_block(&self.keypair, custom_headers);
+                        schema.forge_new_block(&self.db, &self.keypair, custom_headers);
                     return signed_block;
                 }
             } else {
                 // init sync_state_flag with false
                 #[allow(unused_assignments)]
                 let mut sync_state_flag: bool = false;
-                let fork_to_sync = fork_db();
+                let fork_to_sync = self.db.fork();
                 {
                     let mut sync_schema = SchemaFork::new(&fork_to_sync);
                     sync_state_flag = sync_schema.sync_state();
                 }
-                patch_db(fork_to_sync);
+                self.db.patch(fork_to_sync);
                 // flush the waiting blocks queue
                 if sync_state_flag {
                     if waiting_blocks_queue.queue.len() > 0 {
@@ -677,15 +697,21 @@ impl Aura {
         meta_data: Arc<Mutex<MetaData>>,
         sender: &mut Sender<Option<MessageTypes>>,
     ) {
-        let mut wait_till_one_round: u64 = (self.validator_mapping.len() * 10) as u64;
+        let validator_count: usize = {
+            let waiting_blocks_queue_obj = waiting_blocks_queue.lock().unwrap();
+            let meta_data_obj = meta_data.lock().unwrap();
+            let height: u64 = meta_data_obj.next_height(&waiting_blocks_queue_obj);
+            meta_data_obj.validators(height).len()
+        };
+        let mut wait_till_one_round: u64 = (validator_count * 10) as u64;
         wait_till_one_round = wait_till_one_round * self.leader_epoch;
         thread::sleep(Duration::from_millis(wait_till_one_round));
-        let fork = fork_db();
+        let fork = self.db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             schema.sync_state();
         }
-        patch_db(fork);
+        self.db.patch(fork);
         #[allow(unused_assignments)]
         let mut leader_flag = false;
         loop {
@@ -697,9 +723,10 @@ impl Aura {
             {
                 let mut am_i_leader: bool = false;
                 {
+                    let waiting_blocks_queue_obj = waiting_blocks_queue.lock().unwrap();
                     let meta_data_obj = meta_data.lock().unwrap();
-                    let current_leader: String = Aura::primary_leader(&meta_data_obj);
-                    if current_leader == self.pk.clone() {
+                    let height: u64 = meta_data_obj.next_height(&waiting_blocks_queue_obj);
+                    if Aura::primary_leader(&meta_data_obj, height).as_ref() == Some(&self.pk) {
                         am_i_leader = true;
                     }
                 }
@@ -710,15 +737,16 @@ impl Aura {
                     AuraMessageSender::send_round_owner_msg(sender, round_owner.clone());
                     thread::sleep(Duration::from_millis(self.leader_epoch));
                     {
-                        let meta_data_obj = meta_data.lock().unwrap();
                         let mut waiting_blocks_queue_obj = waiting_blocks_queue.lock().unwrap();
+                        let meta_data_obj = meta_data.lock().unwrap();
                         Aura::handle_round_owner_enum(
                             round_owner,
                             &mut waiting_blocks_queue_obj,
                             &meta_data_obj,
                         );
-                        let signed_block: SignedBlock =
-                            self.propose_block(&mut waiting_blocks_queue_obj, &meta_data_obj);
+                    }
+                    // block is computed on top of waiting blocks, engine locks them meanwhile
+                    if let Some(signed_block) = self.propose_block() {
                         info!(
                             "new block created.. id {},hash {}",
                             signed_block.block.id,
@@ -726,6 +754,8 @@ impl Aura {
                         );
                         let author_block: AuthorBlock = AuthorBlock::create(signed_block.clone());
                         AuraMessageSender::send_author_block_msg(sender, author_block);
+                        let mut waiting_blocks_queue_obj = waiting_blocks_queue.lock().unwrap();
+                        let meta_data_obj = meta_data.lock().unwrap();
                         waiting_blocks_queue_obj.last_block_hash = signed_block.get_hash().to_hex();
                         waiting_blocks_queue_obj.queue.push(signed_block);
                         waiting_blocks_queue_obj.last_block_acceptance.clear();
@@ -745,34 +775,35 @@ impl Aura {
         }
     }
 
-    pub fn init_aura_consensus(
+    /// this function will create aura engine as per consensus config file, it is registered
+    /// as "aura" engine
+    pub fn create_engine(
         config: &Configuration,
+        db: DbInstance,
         consensus_file_path: &str,
-        sender: &mut Sender<Option<MessageTypes>>,
-        msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
-    ) {
+        sender: Sender<Option<MessageTypes>>,
+    ) -> Box<dyn ConsensusEngine> {
         initialize_config(consensus_file_path);
         let aura_config: &crate::config::Configuration = &crate::config::AURA_CONFIG;
-        let mut validator_mapping: HashMap<String, u64> = HashMap::new();
-        for i in 0..aura_config.validator_set.len() {
-            validator_mapping.insert(
-                aura_config.validator_set[i].clone(),
-                aura_config.validator_ids[i].clone(),
-            );
+        let mut genesis_validators: Vec<(u64, String)> = aura_config
+            .validator_ids
+            .iter()
+            .cloned()
+            .zip(aura_config.validator_set.iter().cloned())
+            .collect();
+        genesis_validators.sort();
+        // author order at genesis follows validator ids, later it is the order of chain state
+        match genesis_validator_state(
+            genesis_validators
+                .into_iter()
+                .map(|(_id, validator)| validator)
+                .collect(),
+        ) {
+            Ok(genesis_state) => set_genesis_states(&db, vec![genesis_state]),
+            Err(reason) => panic!("aura consensus: {}", reason),
         }
 
-        let mut aura_obj = Aura {
-            keypair: config.node.keypair.clone(),
-            pk: hex::encode(config.node.keypair.public().encode()),
-            validator_mapping: validator_mapping.clone(),
-            leader_epoch: 100 * aura_config.step_time,
-            force_sealing: aura_config.force_sealing,
-            start_time: aura_config.start_time,
-        };
-
         let consensus_meta_data = MetaData {
-            validator_pool_size: aura_config.validator_set.len() as u64,
-            validator_mapping,
             kp: config.node.keypair.clone(),
             public_key: hex::encode(config.node.keypair.public().encode()),
             sender: sender.clone(),
@@ -780,25 +811,105 @@ impl Aura {
             round_number: aura_config.round_number,
             step_time: aura_config.step_time,
             block_queue_size: aura_config.block_list_size,
+            db: db.clone(),
         };
-        let meta_data = Arc::new(Mutex::new(consensus_meta_data));
-        let waiting_blocks_queue: Arc<Mutex<WaitingBLocksQueue>> =
-            Arc::new(Mutex::new(WaitingBLocksQueue::new()));
-        Aura::aura_msg_receiver(
-            waiting_blocks_queue.clone(),
-            meta_data.clone(),
-            msg_receiver,
-        );
-        if config.node.genesis_block {
-            aura_obj.init_state(&config.db.dbpath, &mut sender.clone());
-        } else {
-            let fork = fork_db();
+        Box::new(Aura {
+            keypair: config.node.keypair.clone(),
+            pk: hex::encode(config.node.keypair.public().encode()),
+            leader_epoch: 100 * aura_config.step_time,
+            force_sealing: aura_config.force_sealing,
+            start_time: aura_config.start_time,
+            db,
+            sender,
+            meta_data: Arc::new(Mutex::new(consensus_meta_data)),
+            waiting_blocks_queue: Arc::new(Mutex::new(WaitingBLocksQueue::new())),
+        })
+    }
+}
+
+impl ConsensusEngine for Aura {
+    // init_state will create genesis block if predefined storage is empty
+    // or if storage is not empty it will start from previous state
+    // read genesis block details from config file (future work)
+    fn init_state(&mut self, genesis_block: bool) {
+        if !genesis_block {
+            let fork = self.db.fork();
             {
                 let mut schema = SchemaFork::new(&fork);
                 schema.sync_state();
             }
-            patch_db(fork);
+            self.db.patch(fork);
+            return;
         }
-        aura_obj.state_machine(waiting_blocks_queue.clone(), meta_data, sender);
+        let fork = self.db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if schema.blockchain_length() == 0 {
+                let custom_headers: CustomHeaders = CustomHeaders {
+                    timestamp: self.start_time,
+                    round_number: 0,
+                };
+                let custom_headers: Vec<u8> = match serialize(&custom_headers) {
+                    Ok(value) => value,
+                    Err(_) => Vec::new(),
+                };
+                let genesis_signed_block =
+                    schema.initialize_db(custom_headers, self.start_time as u128);
+                info!(
+                    "genesis block created with hash {:?}",
+                    genesis_signed_block.get_hash()
+                );
+            } else {
+                info!(
+                    "started from previous state {} {}",
+                    schema.blockchain_length(),
+                    schema.state_trie_merkle_hash()
+                )
+            }
+        }
+        self.db.patch(fork);
+    }
+
+    fn handle_messages(&mut self, msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        Aura::aura_msg_receiver(self.clone(), msg_receiver);
+    }
+
+    fn propose_block(&mut self) -> Option<SignedBlock> {
+        let mut waiting_blocks_queue_obj = self.waiting_blocks_queue.lock().unwrap();
+        let meta_data_obj = self.meta_data.lock().unwrap();
+        Some(self.compute_block(&mut waiting_blocks_queue_obj, &meta_data_obj))
+    }
+
+    // block is checked on top of waiting blocks, they aren't final yet
+    fn verify_block(&self, signed_block: &SignedBlock) -> bool {
+        let waiting_blocks_queue_obj = self.waiting_blocks_queue.lock().unwrap();
+        let fork = self.db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        for each_block in waiting_blocks_queue_obj.queue.iter() {
+            if !schema.update_block(each_block) {
+                return false;
+            }
+        }
+        schema.update_block(signed_block)
+    }
+
+    // only blocks which got majority acceptance are stored in db
+    fn finalized_height(&self) -> u64 {
+        stored_height(&self.db)
+    }
+
+    // blocks are stored once a majority accepted them through aura messages, gossiped
+    // blocks are dropped
+    fn block_importer(&self) -> Option<Arc<dyn BlockImporter>> {
+        None
+    }
+
+    fn run(&mut self) {
+        let mut sender = self.sender.clone();
+        self.state_machine(
+            self.waiting_blocks_queue.clone(),
+            self.meta_data.clone(),
+            &mut sender,
+        );
     }
 }
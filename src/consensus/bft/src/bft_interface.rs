@@ -0,0 +1,723 @@
+extern crate db_service;
+extern crate message_handler;
+extern crate schema;
+extern crate utils;
+
+use super::bft_message_sender::BftMessageSender;
+use super::bft_messages::{BftMessageTypes, Commit, CommitCertificate, Proposal, Vote};
+use super::bft_state::{Action, RoundState, Step};
+use super::config::initialize_config;
+use consensus_engine::{stored_height, BlockImporter, ConsensusEngine};
+use db_service::db_fork_ref::SchemaFork;
+use db_service::db_layer::DbInstance;
+use db_service::db_snapshot_ref::SchemaSnap;
+use exonum_merkledb::{Fork, ObjectHash, Snapshot};
+use futures::{channel::mpsc::*, executor::*, future, prelude::*, task::*};
+use message_handler::messages::MessageTypes;
+use schema::block::SignedBlock;
+use schema::transaction_pool::{TxnPool, POOL};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use utils::configreader::Configuration;
+use utils::keypair::KeypairType;
+use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
+
+// interval (in millis) at which timeouts & pending proposals are checked
+const TICK_TIME: u64 = 50;
+
+#[derive(Clone)]
+pub struct Bft {
+    // peer identity
+    keypair: KeypairType,
+    // validators of config, certificates are checked against them
+    validator_set: Vec<String>,
+    // empty block proposal
+    force_sealing: bool,
+    // genesis block time (in seconds)
+    start_time: u64,
+    // storage handle of the node
+    db: DbInstance,
+    // rounds of the ongoing height shared with message handling
+    round_state: Arc<Mutex<RoundState>>,
+    // engine details shared with message handling
+    meta_data: Arc<Mutex<MetaData>>,
+}
+
+/// timeout of a round step which has to fire at deadline
+pub struct ScheduledTimeout {
+    deadline: Instant,
+    step: Step,
+    height: u64,
+    round: u64,
+}
+
+/// proposal the node has to make as proposer of a round
+pub struct PendingProposal {
+    height: u64,
+    round: u64,
+    valid: Option<(u64, Proposal)>,
+}
+
+/// BFT engine details, they are locked after the round state
+pub struct MetaData {
+    // storage handle of the node
+    db: DbInstance,
+    // data sender in P2P system
+    sender: Sender<Option<MessageTypes>>,
+    // step timeouts of round 0 (in millis)
+    propose_timeout: u64,
+    prevote_timeout: u64,
+    precommit_timeout: u64,
+    // increase of step timeouts per round (in millis)
+    timeout_delta: u64,
+    // timeouts waiting to fire
+    scheduled_timeouts: Vec<ScheduledTimeout>,
+    // proposal to be built outside of the locks
+    pending_proposal: Option<PendingProposal>,
+    // a peer is at a later height, the chain has to be synced
+    lagging: bool,
+}
+
+// BFT consensus custom headers for the signed block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomHeaders {
+    timestamp: u64,
+    round: u64,
+}
+
+impl Bft {
+    fn timeout_duration(meta_data: &MetaData, step: Step, round: u64) -> Duration {
+        let timeout: u64 = match step {
+            Step::Propose => meta_data.propose_timeout,
+            Step::Prevote => meta_data.prevote_timeout,
+            Step::Precommit => meta_data.precommit_timeout,
+        };
+        Duration::from_millis(timeout + meta_data.timeout_delta * round)
+    }
+
+    // fn will carry out actions of the round state, commits start the next height
+    fn handle_actions(
+        actions: Vec<Action>,
+        round_state: &mut RoundState,
+        meta_data: &mut MetaData,
+    ) {
+        let mut actions: VecDeque<Action> = actions.into_iter().collect();
+        while let Some(action) = actions.pop_front() {
+            match action {
+                Action::Propose {
+                    height,
+                    round,
+                    valid,
+                } => {
+                    meta_data.pending_proposal = Some(PendingProposal {
+                        height,
+                        round,
+                        valid,
+                    });
+                }
+                Action::Broadcast(vote) => {
+                    BftMessageSender::send_vote_msg(&mut meta_data.sender, vote);
+                }
+                Action::ScheduleTimeout {
+                    step,
+                    height,
+                    round,
+                } => {
+                    let deadline: Instant =
+                        Instant::now() + Bft::timeout_duration(meta_data, step, round);
+                    meta_data.scheduled_timeouts.push(ScheduledTimeout {
+                        deadline,
+                        step,
+                        height,
+                        round,
+                    });
+                }
+                Action::Commit {
+                    proposal,
+                    certificate,
+                } => {
+                    let mut signed_block: SignedBlock = proposal.block;
+                    signed_block.auth_headers = match serialize(&certificate) {
+                        Ok(value) => value,
+                        Err(_) => {
+                            error!("commit certificate couldn't serialized");
+                            meta_data.lagging = true;
+                            continue;
+                        }
+                    };
+                    if Bft::store_block(
+                        &meta_data.db,
+                        round_state.validator_set(),
+                        &signed_block,
+                        &proposal.parent_auth_headers,
+                    ) {
+                        info!(
+                            "block with id {} & hash {} finalised in round {}",
+                            signed_block.block.id,
+                            signed_block.object_hash(),
+                            certificate.round
+                        );
+                        let commit: Commit =
+                            Commit::create(signed_block, proposal.parent_auth_headers);
+                        BftMessageSender::send_commit_msg(&mut meta_data.sender, commit);
+                        actions.extend(round_state.new_height(round_state.height() + 1));
+                    } else {
+                        meta_data.lagging = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // fn will put the certificate a block was built on in place of the one of the last
+    // stored block on given fork, the certificate has to finalise the last stored block
+    fn adopt_parent_auth_headers(
+        fork: &Fork,
+        validator_set: &[String],
+        parent_auth_headers: &Vec<u8>,
+    ) -> bool {
+        let root_block: SignedBlock = match SchemaSnap::new(fork).get_root_block() {
+            Some(block) => block,
+            None => return false,
+        };
+        if &root_block.auth_headers == parent_auth_headers {
+            return true;
+        }
+        let certificate: CommitCertificate = match deserialize(parent_auth_headers) {
+            Ok(value) => value,
+            Err(_) => {
+                warn!("parent commit certificate couldn't deserialized");
+                return false;
+            }
+        };
+        if !certificate.verify_block(&root_block, validator_set) {
+            warn!(
+                "parent commit certificate doesn't finalise block {}",
+                root_block.block.id
+            );
+            return false;
+        }
+        let mut schema = SchemaFork::new(fork);
+        schema.replace_root_auth_headers(parent_auth_headers.clone())
+    }
+
+    // fn will apply given block on top of the chain of fork, certified blocks must carry a
+    // valid certificate
+    fn apply_block(fork: &Fork, validator_set: &[String], signed_block: &SignedBlock) -> bool {
+        if !signed_block.auth_headers.is_empty() {
+            let certified: bool = match deserialize::<CommitCertificate>(&signed_block.auth_headers)
+            {
+                Ok(certificate) => certificate.verify_block(signed_block, validator_set),
+                Err(_) => false,
+            };
+            if !certified {
+                return false;
+            }
+        }
+        let mut schema = SchemaFork::new(fork);
+        schema.update_block(signed_block)
+    }
+
+    // fn will check proposed block on top of its certified parent without persisting it
+    fn verify_proposal(&self, proposal: &Proposal) -> bool {
+        let fork = self.db.fork();
+        Bft::adopt_parent_auth_headers(&fork, &self.validator_set, &proposal.parent_auth_headers)
+            && Bft::apply_block(&fork, &self.validator_set, &proposal.block)
+    }
+
+    // fn will store a finalised block, its certificate is in auth_headers
+    fn store_block(
+        db: &DbInstance,
+        validator_set: &[String],
+        signed_block: &SignedBlock,
+        parent_auth_headers: &Vec<u8>,
+    ) -> bool {
+        let fork = db.fork();
+        if !Bft::adopt_parent_auth_headers(&fork, validator_set, parent_auth_headers) {
+            return false;
+        }
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if !schema.update_block(signed_block) {
+                error!(
+                    "block with id {} & hash {} couldn't added in database",
+                    signed_block.block.id,
+                    signed_block.object_hash()
+                );
+                return false;
+            }
+        }
+        db.patch(fork);
+        POOL.sync_pool(&signed_block.block.txn_pool);
+        true
+    }
+
+    // fn will sync blocks from peers, they are stored only if all of them are certified
+    fn sync_chain(db: &DbInstance, validator_set: &[String]) -> bool {
+        let stored_length: u64 = stored_height(db) + 1;
+        let fork = db.fork();
+        {
+            let (length, sync_data) = {
+                let schema = SchemaFork::new(&fork);
+                let length: u64 = schema.blockchain_length();
+                // peers' copy of the last stored block comes along, synced blocks link to it
+                (length, schema.fetch_sync_state(length.saturating_sub(1)))
+            };
+            if length > 0 {
+                if let Some(peer_root_block) = sync_data.block_map.get(&(length - 1)) {
+                    // peers may have finalised it with other 2f+1 precommits
+                    if !Bft::adopt_parent_auth_headers(
+                        &fork,
+                        validator_set,
+                        &peer_root_block.auth_headers,
+                    ) {
+                        return false;
+                    }
+                }
+            }
+            let mut schema = SchemaFork::new(&fork);
+            if !schema.apply_sync_state(&sync_data) {
+                return false;
+            }
+        }
+        {
+            let schema = SchemaSnap::new(&fork);
+            // genesis block isn't certified
+            for height in stored_length.max(1)..schema.get_blockchain_length() {
+                let signed_block: SignedBlock = match schema.get_block(height) {
+                    Some(block) => block,
+                    None => return false,
+                };
+                let certified: bool =
+                    match deserialize::<CommitCertificate>(&signed_block.auth_headers) {
+                        Ok(certificate) => certificate.verify_block(&signed_block, validator_set),
+                        Err(_) => false,
+                    };
+                if !certified {
+                    error!(
+                        "block with id {} synced without valid commit certificate",
+                        height
+                    );
+                    return false;
+                }
+            }
+        }
+        db.patch(fork);
+        true
+    }
+
+    // fn will tell whether given auth headers carry 2f+1 precommits of a block at given
+    // height or later, only such a certificate makes the node sync its chain from peers
+    fn certifies_height(auth_headers: &Vec<u8>, height: u64, validator_set: &[String]) -> bool {
+        match deserialize::<CommitCertificate>(auth_headers) {
+            Ok(certificate) => certificate.height >= height && certificate.verify(validator_set),
+            Err(_) => false,
+        }
+    }
+
+    // fn will tell whether proposal is of the rightful proposer of a round at current height
+    fn proposal_acceptable(
+        proposal: &Proposal,
+        round_state: &RoundState,
+        meta_data: &mut MetaData,
+    ) -> bool {
+        if proposal.details.height > round_state.height() {
+            // parent of the proposed block is certified at a height the node hasn't stored
+            if Bft::certifies_height(
+                &proposal.parent_auth_headers,
+                round_state.height(),
+                round_state.validator_set(),
+            ) {
+                meta_data.lagging = true;
+            }
+            return false;
+        }
+        if proposal.details.height < round_state.height() {
+            return false;
+        }
+        if !round_state.validator_set().contains(&proposal.public_key)
+            || !round_state.in_round_window(proposal.details.round)
+            || round_state.proposer(proposal.details.round) != Some(&proposal.public_key)
+        {
+            warn!(
+                "proposal from {:?} isn't of the proposer of round {}",
+                proposal.public_key, proposal.details.round
+            );
+            return false;
+        }
+        true
+    }
+
+    // fn will process incoming Proposal data, its block is applied without holding the locks
+    // since it may fetch txns from peers
+    fn handle_proposal_enum(engine: &Bft, proposal: Proposal) {
+        if !proposal.verify() {
+            warn!("malicious proposal from {:?}", proposal.public_key);
+            return;
+        }
+        {
+            let round_state_obj = engine.round_state.lock().unwrap();
+            let mut meta_data_obj = engine.meta_data.lock().unwrap();
+            if !Bft::proposal_acceptable(&proposal, &round_state_obj, &mut meta_data_obj) {
+                return;
+            }
+        }
+        let valid: bool = engine.verify_proposal(&proposal);
+        if !valid {
+            warn!(
+                "invalid block proposed by {:?} in round {}",
+                proposal.public_key, proposal.details.round
+            );
+        }
+        // round state drops the proposal if its height got decided meanwhile
+        let mut round_state_obj = engine.round_state.lock().unwrap();
+        let mut meta_data_obj = engine.meta_data.lock().unwrap();
+        let actions: Vec<Action> = round_state_obj.on_proposal(proposal, valid);
+        Bft::handle_actions(actions, &mut round_state_obj, &mut meta_data_obj);
+    }
+
+    // fn will process incoming Vote data
+    fn handle_vote_enum(vote: Vote, round_state: &mut RoundState, meta_data: &mut MetaData) {
+        if !vote.verify() {
+            warn!("malicious vote from {:?}", vote.public_key);
+            return;
+        }
+        // a vote of a later height doesn't prove it is decided, commits & proposals carry
+        // the certificates the chain is synced on
+        let actions: Vec<Action> = round_state.on_vote(vote);
+        Bft::handle_actions(actions, round_state, meta_data);
+    }
+
+    // fn will process incoming Commit data, it lets the node catch up a missed decision
+    fn handle_commit_enum(commit: Commit, round_state: &mut RoundState, meta_data: &mut MetaData) {
+        let height: u64 = commit.block.block.id;
+        if height > round_state.height() {
+            if Bft::certifies_height(
+                &commit.block.auth_headers,
+                round_state.height(),
+                round_state.validator_set(),
+            ) {
+                meta_data.lagging = true;
+            }
+            return;
+        }
+        if height < round_state.height() {
+            return;
+        }
+        let certified: bool = match deserialize::<CommitCertificate>(&commit.block.auth_headers) {
+            Ok(certificate) => certificate.verify_block(&commit.block, round_state.validator_set()),
+            Err(_) => false,
+        };
+        if !certified {
+            warn!(
+                "block with id {} committed without valid certificate",
+                height
+            );
+            return;
+        }
+        if Bft::store_block(
+            &meta_data.db,
+            round_state.validator_set(),
+            &commit.block,
+            &commit.parent_auth_headers,
+        ) {
+            info!(
+                "block with id {} & hash {} finalised by peers",
+                height,
+                commit.block.object_hash()
+            );
+            let actions: Vec<Action> = round_state.new_height(height + 1);
+            Bft::handle_actions(actions, round_state, meta_data);
+        }
+    }
+
+    // fn will listen incoming data from other peers via P2P system
+    fn bft_msg_receiver(engine: Bft, rx: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        let round_state: Arc<Mutex<RoundState>> = engine.round_state.clone();
+        let meta_data: Arc<Mutex<MetaData>> = engine.meta_data.clone();
+        thread::spawn(move || {
+            block_on(future::poll_fn(move |cx: &mut Context| {
+                loop {
+                    match rx.lock().unwrap().poll_next_unpin(cx) {
+                        Poll::Ready(Some(msg)) => match msg {
+                            None => info!("Empty msg received !"),
+                            Some(msgtype) => {
+                                if let Ok(msgtype) =
+                                    deserialize::<BftMessageTypes>(msgtype.as_slice())
+                                {
+                                    match msgtype {
+                                        BftMessageTypes::ProposalEnum(data) => {
+                                            info!("Proposal data received");
+                                            Bft::handle_proposal_enum(&engine, data);
+                                        }
+                                        BftMessageTypes::VoteEnum(data) => {
+                                            info!("Vote data received");
+                                            let mut round_state_obj = round_state.lock().unwrap();
+                                            let mut meta_data_obj = meta_data.lock().unwrap();
+                                            Bft::handle_vote_enum(
+                                                data,
+                                                &mut round_state_obj,
+                                                &mut meta_data_obj,
+                                            );
+                                        }
+                                        BftMessageTypes::CommitEnum(data) => {
+                                            info!("Commit data received");
+                                            let mut round_state_obj = round_state.lock().unwrap();
+                                            let mut meta_data_obj = meta_data.lock().unwrap();
+                                            Bft::handle_commit_enum(
+                                                data,
+                                                &mut round_state_obj,
+                                                &mut meta_data_obj,
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        Poll::Ready(None) => {
+                            info!("channel closed !");
+                            return Poll::Ready(1);
+                        }
+                        Poll::Pending => break,
+                    }
+                }
+                Poll::Pending
+            }));
+        });
+    }
+
+    // fn will create new block on top of local chain
+    fn compute_block(&self, round: u64) -> SignedBlock {
+        let custom_headers: CustomHeaders = CustomHeaders {
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            round,
+        };
+        let custom_headers: Vec<u8> = match serialize(&custom_headers) {
+            Ok(value) => value,
+            Err(_) => Vec::new(),
+        };
+        let fork = self.db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        if self.force_sealing {
+            schema.create_block(&self.keypair, custom_headers)
+        } else {
+            let (_fork_instance, signed_block) =
+                schema.forge_new_block(&self.db, &self.keypair, custom_headers);
+            signed_block
+        }
+    }
+
+    // fn will propose the valid block of the height if any, otherwise a new block
+    fn make_proposal(&mut self, pending_proposal: PendingProposal) {
+        let (signed_block, parent_auth_headers, valid_round) = match pending_proposal.valid {
+            Some((valid_round, proposal)) => (
+                proposal.block,
+                proposal.parent_auth_headers,
+                Some(valid_round),
+            ),
+            None => {
+                let snapshot: Box<dyn Snapshot> = self.db.snapshot();
+                let parent_auth_headers: Vec<u8> = match SchemaSnap::new(&snapshot).get_root_block()
+                {
+                    Some(block) => block.auth_headers,
+                    None => return,
+                };
+                // block is built in the ongoing round, it is dropped below if the round moved
+                let signed_block: SignedBlock = match self.propose_block() {
+                    Some(block) => block,
+                    None => return,
+                };
+                (signed_block, parent_auth_headers, None)
+            }
+        };
+        if signed_block.block.id != pending_proposal.height {
+            warn!(
+                "block couldn't proposed for height {}, local chain has moved",
+                pending_proposal.height
+            );
+            return;
+        }
+        let proposal: Proposal = Proposal::create(
+            &self.keypair,
+            pending_proposal.round,
+            valid_round,
+            signed_block,
+            parent_auth_headers,
+        );
+        let mut round_state_obj = self.round_state.lock().unwrap();
+        let mut meta_data_obj = self.meta_data.lock().unwrap();
+        if round_state_obj.height() != pending_proposal.height
+            || round_state_obj.round() != pending_proposal.round
+        {
+            return;
+        }
+        info!(
+            "block with id {} & hash {} proposed in round {}",
+            proposal.block.block.id, proposal.details.block_hash, proposal.details.round
+        );
+        BftMessageSender::send_proposal_msg(&mut meta_data_obj.sender, proposal.clone());
+        let actions: Vec<Action> = round_state_obj.on_proposal(proposal, true);
+        Bft::handle_actions(actions, &mut round_state_obj, &mut meta_data_obj);
+    }
+
+    // fn will fire due timeouts, sync lagging chain and make proposals of the node
+    fn state_machine(&mut self) {
+        {
+            let mut round_state_obj = self.round_state.lock().unwrap();
+            let mut meta_data_obj = self.meta_data.lock().unwrap();
+            let actions: Vec<Action> = round_state_obj.new_height(self.finalized_height() + 1);
+            Bft::handle_actions(actions, &mut round_state_obj, &mut meta_data_obj);
+        }
+        loop {
+            let pending_proposal: Option<PendingProposal> = {
+                let mut round_state_obj = self.round_state.lock().unwrap();
+                let mut meta_data_obj = self.meta_data.lock().unwrap();
+                if meta_data_obj.lagging {
+                    meta_data_obj.lagging = false;
+                    Bft::sync_chain(&self.db, round_state_obj.validator_set());
+                    let height: u64 = self.finalized_height() + 1;
+                    if height > round_state_obj.height() {
+                        info!("synced up to height {}", height);
+                        meta_data_obj.scheduled_timeouts.clear();
+                        meta_data_obj.pending_proposal = None;
+                        let actions: Vec<Action> = round_state_obj.new_height(height);
+                        Bft::handle_actions(actions, &mut round_state_obj, &mut meta_data_obj);
+                    }
+                }
+                let now: Instant = Instant::now();
+                let (due, waiting): (Vec<ScheduledTimeout>, Vec<ScheduledTimeout>) = meta_data_obj
+                    .scheduled_timeouts
+                    .drain(..)
+                    .partition(|timeout| timeout.deadline <= now);
+                meta_data_obj.scheduled_timeouts = waiting;
+                for timeout in due {
+                    let actions: Vec<Action> =
+                        round_state_obj.on_timeout(timeout.step, timeout.height, timeout.round);
+                    Bft::handle_actions(actions, &mut round_state_obj, &mut meta_data_obj);
+                }
+                meta_data_obj.pending_proposal.take()
+            };
+            // block creation may take block_creation_time_limit, locks aren't held meanwhile
+            if let Some(pending_proposal) = pending_proposal {
+                self.make_proposal(pending_proposal);
+            }
+            thread::sleep(Duration::from_millis(TICK_TIME));
+        }
+    }
+
+    /// this function will create bft engine as per consensus config file, it is registered
+    /// as "bft" engine
+    pub fn create_engine(
+        config: &Configuration,
+        db: DbInstance,
+        consensus_file_path: &str,
+        sender: Sender<Option<MessageTypes>>,
+    ) -> Box<dyn ConsensusEngine> {
+        initialize_config(consensus_file_path);
+        let bft_config: &crate::config::Configuration = &crate::config::BFT_CONFIG;
+        if bft_config.validator_set.is_empty() {
+            panic!("bft consensus needs at least one validator");
+        }
+        let round_state: RoundState = RoundState::new(
+            config.node.keypair.clone(),
+            bft_config.validator_set.clone(),
+        );
+        if !round_state.is_validator() {
+            info!("node isn't a validator, it will follow finalised blocks");
+        }
+        let meta_data: MetaData = MetaData {
+            db: db.clone(),
+            sender,
+            propose_timeout: bft_config.propose_timeout,
+            prevote_timeout: bft_config.prevote_timeout,
+            precommit_timeout: bft_config.precommit_timeout,
+            timeout_delta: bft_config.timeout_delta,
+            scheduled_timeouts: Vec::new(),
+            pending_proposal: None,
+            lagging: false,
+        };
+        Box::new(Bft {
+            keypair: config.node.keypair.clone(),
+            validator_set: bft_config.validator_set.clone(),
+            force_sealing: bft_config.force_sealing,
+            start_time: bft_config.start_time,
+            db,
+            round_state: Arc::new(Mutex::new(round_state)),
+            meta_data: Arc::new(Mutex::new(meta_data)),
+        })
+    }
+}
+
+impl ConsensusEngine for Bft {
+    // init_state will create genesis block if predefined storage is empty
+    // or if storage is not empty it will sync certified blocks from peers
+    fn init_state(&mut self, genesis_block: bool) {
+        if !genesis_block {
+            let validator_set: Vec<String> =
+                self.round_state.lock().unwrap().validator_set().clone();
+            Bft::sync_chain(&self.db, &validator_set);
+            return;
+        }
+        let fork = self.db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if schema.blockchain_length() == 0 {
+                let custom_headers: CustomHeaders = CustomHeaders {
+                    timestamp: self.start_time,
+                    round: 0,
+                };
+                let custom_headers: Vec<u8> = match serialize(&custom_headers) {
+                    Ok(value) => value,
+                    Err(_) => Vec::new(),
+                };
+                let genesis_signed_block =
+                    schema.initialize_db(custom_headers, self.start_time as u128);
+                info!(
+                    "genesis block created with hash {:?}",
+                    genesis_signed_block.get_hash()
+                );
+            } else {
+                info!(
+                    "started from previous state {} {}",
+                    schema.blockchain_length(),
+                    schema.state_trie_merkle_hash()
+                )
+            }
+        }
+        self.db.patch(fork);
+    }
+
+    fn handle_messages(&mut self, msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        Bft::bft_msg_receiver(self.clone(), msg_receiver);
+    }
+
+    fn propose_block(&mut self) -> Option<SignedBlock> {
+        let round: u64 = self.round_state.lock().unwrap().round();
+        Some(self.compute_block(round))
+    }
+
+    // block must extend local chain, certified blocks must carry a valid certificate
+    fn verify_block(&self, signed_block: &SignedBlock) -> bool {
+        let fork = self.db.fork();
+        Bft::apply_block(&fork, &self.validator_set, signed_block)
+    }
+
+    // blocks are stored once 2f+1 validators precommitted them, they are final
+    fn finalized_height(&self) -> u64 {
+        stored_height(&self.db)
+    }
+
+    // blocks are stored with their commit certificate only, gossiped blocks are dropped
+    fn block_importer(&self) -> Option<Arc<dyn BlockImporter>> {
+        None
+    }
+
+    fn run(&mut self) {
+        self.state_machine();
+    }
+}
@@ -0,0 +1,44 @@
+use crate::bft_messages::{BftMessageTypes, Commit, Proposal, Vote};
+use futures::channel::mpsc::*;
+use message_handler::messages::MessageTypes;
+use utils::serializer::serialize;
+
+pub struct BftMessageSender {}
+
+impl BftMessageSender {
+    pub fn send_proposal_msg(sender: &mut Sender<Option<MessageTypes>>, msg: Proposal) {
+        if let Ok(serialize_msg) = serialize(&BftMessageTypes::ProposalEnum(msg)) {
+            let data = Some(MessageTypes::ConsensusMsg(serialize_msg));
+            let error: Result<(), TrySendError<Option<MessageTypes>>> = sender.try_send(data);
+            if error.is_err() {
+                error!("{:?}", error);
+            } else {
+                info!("msg send send_proposal_msg");
+            }
+        }
+    }
+
+    pub fn send_vote_msg(sender: &mut Sender<Option<MessageTypes>>, msg: Vote) {
+        if let Ok(serialize_msg) = serialize(&BftMessageTypes::VoteEnum(msg)) {
+            let data = Some(MessageTypes::ConsensusMsg(serialize_msg));
+            let error: Result<(), TrySendError<Option<MessageTypes>>> = sender.try_send(data);
+            if error.is_err() {
+                error!("{:?}", error);
+            } else {
+                info!("msg send send_vote_msg");
+            }
+        }
+    }
+
+    pub fn send_commit_msg(sender: &mut Sender<Option<MessageTypes>>, msg: Commit) {
+        if let Ok(serialize_msg) = serialize(&BftMessageTypes::CommitEnum(msg)) {
+            let data = Some(MessageTypes::ConsensusMsg(serialize_msg));
+            let error: Result<(), TrySendError<Option<MessageTypes>>> = sender.try_send(data);
+            if error.is_err() {
+                error!("{:?}", error);
+            } else {
+                info!("msg send send_commit_msg");
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use exonum_crypto::Hash;
+use exonum_merkledb::ObjectHash;
+use libp2p::floodsub::Topic;
+use message_handler::constants;
+use message_handler::message_traits::Message;
+use schema::block::SignedBlock;
+use std::collections::HashSet;
+use utils::keypair::{CryptoKeypair, Keypair, KeypairType, PublicKey, Verify};
+use utils::serializer::{serialize, Deserialize, Serialize};
+
+pub const BFT_MSG_TOPIC_STR: &'static [&'static str] = &["Proposal", "Vote", "Commit"];
+
+/// number of validators which have to agree, 2f+1 where f = (n - 1) / 3 may be faulty
+pub fn quorum(validator_count: usize) -> usize {
+    validator_count * 2 / 3 + 1
+}
+
+fn sign_payload<T: Serialize>(kp: &KeypairType, payload: &T) -> Vec<u8> {
+    let ser_payload: Vec<u8> = match serialize(payload) {
+        Result::Ok(value) => value,
+        Result::Err(_) => vec![0],
+    };
+    Keypair::sign(&kp, &ser_payload)
+}
+
+fn verify_payload<T: Serialize>(public_key: &String, payload: &T, signature: &[u8]) -> bool {
+    let ser_payload: Vec<u8> = match serialize(payload) {
+        Result::Ok(value) => value,
+        Result::Err(_) => return false,
+    };
+    PublicKey::verify_from_encoded_pk(public_key, &ser_payload, signature)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VoteType {
+    Prevote,
+    Precommit,
+}
+
+/// VoteDetails is what a validator signs, block_hash None is a vote for nil
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VoteDetails {
+    pub vote_type: VoteType,
+    pub height: u64,
+    pub round: u64,
+    pub block_hash: Option<Hash>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Vote {
+    pub details: VoteDetails,
+    pub signature: Vec<u8>,
+    pub public_key: String,
+}
+
+impl Vote {
+    pub fn verify(&self) -> bool {
+        verify_payload(&self.public_key, &self.details, &self.signature)
+    }
+
+    pub fn create(kp: &KeypairType, details: VoteDetails) -> Vote {
+        Vote {
+            signature: sign_payload(kp, &details),
+            details,
+            public_key: hex::encode(kp.public().encode()),
+        }
+    }
+}
+
+/// ProposalDetails is what the proposer signs, valid_round is the round a re-proposed block
+/// got 2f+1 prevotes in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProposalDetails {
+    pub height: u64,
+    pub round: u64,
+    pub valid_round: Option<u64>,
+    pub block_hash: Hash,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Proposal {
+    pub details: ProposalDetails,
+    pub block: SignedBlock,
+    // auth headers of the parent block the proposed block was built on
+    pub parent_auth_headers: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub public_key: String,
+}
+
+impl Proposal {
+    pub fn verify(&self) -> bool {
+        if self.block.block.id != self.details.height
+            || self.block.block.object_hash() != self.details.block_hash
+        {
+            return false;
+        }
+        verify_payload(&self.public_key, &self.details, &self.signature) && self.block.validate()
+    }
+
+    pub fn create(
+        kp: &KeypairType,
+        round: u64,
+        valid_round: Option<u64>,
+        block: SignedBlock,
+        parent_auth_headers: Vec<u8>,
+    ) -> Proposal {
+        let details: ProposalDetails = ProposalDetails {
+            height: block.block.id,
+            round,
+            valid_round,
+            block_hash: block.block.object_hash(),
+        };
+        Proposal {
+            signature: sign_payload(kp, &details),
+            details,
+            block,
+            parent_auth_headers,
+            public_key: hex::encode(kp.public().encode()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommitSignature {
+    pub public_key: String,
+    pub signature: Vec<u8>,
+}
+
+/// CommitCertificate carries 2f+1 precommits of a block, it is stored in auth_headers of the
+/// finalised block
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommitCertificate {
+    pub height: u64,
+    pub round: u64,
+    pub block_hash: Hash,
+    pub precommits: Vec<CommitSignature>,
+}
+
+impl CommitCertificate {
+    /// this function will check that distinct validators of given set signed precommits of
+    /// the block and that they are a quorum
+    pub fn verify(&self, validator_set: &[String]) -> bool {
+        let details: VoteDetails = VoteDetails {
+            vote_type: VoteType::Precommit,
+            height: self.height,
+            round: self.round,
+            block_hash: Some(self.block_hash),
+        };
+        let mut signers: HashSet<&String> = HashSet::new();
+        for precommit in self.precommits.iter() {
+            if !validator_set.contains(&precommit.public_key)
+                || !signers.insert(&precommit.public_key)
+                || !verify_payload(&precommit.public_key, &details, &precommit.signature)
+            {
+                return false;
+            }
+        }
+        signers.len() >= quorum(validator_set.len())
+    }
+
+    /// this function will check that the certificate finalises given block
+    pub fn verify_block(&self, signed_block: &SignedBlock, validator_set: &[String]) -> bool {
+        self.height == signed_block.block.id
+            && self.block_hash == signed_block.block.object_hash()
+            && self.verify(validator_set)
+    }
+}
+
+/// Commit announces a finalised block, it lets lagging validators catch up
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Commit {
+    // commit certificate is in auth_headers of the block
+    pub block: SignedBlock,
+    pub parent_auth_headers: Vec<u8>,
+}
+
+impl Commit {
+    pub fn create(block: SignedBlock, parent_auth_headers: Vec<u8>) -> Commit {
+        Commit {
+            block,
+            parent_auth_headers,
+        }
+    }
+}
+
+impl Message for Proposal {
+    const TOPIC: &'static str = BFT_MSG_TOPIC_STR[0];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am Proposal handler");
+    }
+}
+
+impl Message for Vote {
+    const TOPIC: &'static str = BFT_MSG_TOPIC_STR[1];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am Vote handler");
+    }
+}
+
+impl Message for Commit {
+    const TOPIC: &'static str = BFT_MSG_TOPIC_STR[2];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am Commit handler");
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum BftMessageTypes {
+    ProposalEnum(Proposal),
+    VoteEnum(Vote),
+    CommitEnum(Commit),
+}
+
+impl From<BftMessageTypes> for Topic {
+    fn from(msg: BftMessageTypes) -> Topic {
+        match msg {
+            BftMessageTypes::ProposalEnum(data) => Topic::new(data.topic()),
+            BftMessageTypes::VoteEnum(data) => Topic::new(data.topic()),
+            BftMessageTypes::CommitEnum(data) => Topic::new(data.topic()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod consensus_message_test {
+    use super::*;
+    use schema::block::{Block, BlockTraits};
+
+    fn signed_block(kp: &KeypairType, height: u64) -> SignedBlock {
+        let block: Block = Block::new_block(
+            height,
+            hex::encode(kp.public().encode()),
+            Hash::zero(),
+            vec![Hash::zero()],
+            [Hash::zero(), Hash::zero(), Hash::zero(), Hash::zero()],
+            Vec::new(),
+        );
+        let sign: Vec<u8> = block.sign(kp);
+        SignedBlock::create_block(block, sign, Vec::new())
+    }
+
+    fn certificate(kps: &[KeypairType], block: &SignedBlock, round: u64) -> CommitCertificate {
+        let details: VoteDetails = VoteDetails {
+            vote_type: VoteType::Precommit,
+            height: block.block.id,
+            round,
+            block_hash: Some(block.block.object_hash()),
+        };
+        CommitCertificate {
+            height: block.block.id,
+            round,
+            block_hash: block.block.object_hash(),
+            precommits: kps
+                .iter()
+                .map(|kp| {
+                    let vote: Vote = Vote::create(kp, details.clone());
+                    CommitSignature {
+                        public_key: vote.public_key,
+                        signature: vote.signature,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn check_bft_quorum() {
+        assert_eq!(quorum(1), 1);
+        assert_eq!(quorum(3), 3);
+        assert_eq!(quorum(4), 3);
+        assert_eq!(quorum(7), 5);
+        assert_eq!(quorum(10), 7);
+    }
+
+    #[test]
+    fn check_bft_messages_verification_process() {
+        let kp: KeypairType = Keypair::generate();
+        let block: SignedBlock = signed_block(&kp, 3);
+        let proposal: Proposal = Proposal::create(&kp, 1, None, block.clone(), Vec::new());
+        assert_eq!(proposal.verify(), true);
+        let mut forged: Proposal = proposal.clone();
+        forged.details.round = 2;
+        assert_eq!(forged.verify(), false);
+        let mut forged: Proposal = proposal.clone();
+        forged.block = signed_block(&kp, 3);
+        forged.block.block.txn_pool.clear();
+        assert_eq!(forged.verify(), false);
+
+        let vote: Vote = Vote::create(
+            &kp,
+            VoteDetails {
+                vote_type: VoteType::Prevote,
+                height: 3,
+                round: 1,
+                block_hash: None,
+            },
+        );
+        assert_eq!(vote.verify(), true);
+        let mut forged: Vote = vote.clone();
+        forged.details.vote_type = VoteType::Precommit;
+        assert_eq!(forged.verify(), false);
+    }
+
+    #[test]
+    fn check_bft_commit_certificate() {
+        let kps: Vec<KeypairType> = (0..4).map(|_| Keypair::generate()).collect();
+        let validator_set: Vec<String> = kps
+            .iter()
+            .map(|kp| hex::encode(kp.public().encode()))
+            .collect();
+        let block: SignedBlock = signed_block(&kps[0], 5);
+
+        let commit_certificate: CommitCertificate = certificate(&kps[..3], &block, 2);
+        assert_eq!(
+            commit_certificate.verify_block(&block, &validator_set),
+            true
+        );
+        // f+1 precommits aren't enough
+        let commit_certificate: CommitCertificate = certificate(&kps[..2], &block, 2);
+        assert_eq!(
+            commit_certificate.verify_block(&block, &validator_set),
+            false
+        );
+        // the same validator is counted once
+        let mut commit_certificate: CommitCertificate = certificate(&kps[..2], &block, 2);
+        let duplicate: CommitSignature = commit_certificate.precommits[0].clone();
+        commit_certificate.precommits.push(duplicate);
+        assert_eq!(
+            commit_certificate.verify_block(&block, &validator_set),
+            false
+        );
+        // precommits of another round don't count
+        let mut commit_certificate: CommitCertificate = certificate(&kps[..3], &block, 2);
+        commit_certificate.round = 3;
+        assert_eq!(
+            commit_certificate.verify_block(&block, &validator_set),
+            false
+        );
+        // signers must be validators
+        let outsider: KeypairType = Keypair::generate();
+        let commit_certificate: CommitCertificate =
+            certificate(&[kps[0].clone(), kps[1].clone(), outsider], &block, 2);
+        assert_eq!(
+            commit_certificate.verify_block(&block, &validator_set),
+            false
+        );
+        // certificate belongs to one block
+        let commit_certificate: CommitCertificate = certificate(&kps[..3], &block, 2);
+        let other_block: SignedBlock = signed_block(&kps[1], 5);
+        assert_eq!(
+            commit_certificate.verify_block(&other_block, &validator_set),
+            false
+        );
+    }
+}
@@ -0,0 +1,686 @@
+use crate::bft_messages::{
+    quorum, CommitCertificate, CommitSignature, Proposal, Vote, VoteDetails, VoteType,
+};
+use exonum_crypto::Hash;
+use std::collections::{HashMap, HashSet};
+use utils::keypair::KeypairType;
+
+/// rounds after the current one the node keeps proposals & votes of, messages of later
+/// rounds are dropped so that a peer can't grow them without bound
+pub const ROUND_WINDOW: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Propose,
+    Prevote,
+    Precommit,
+}
+
+/// Action is what the engine has to carry out on behalf of the round state
+#[derive(Debug)]
+pub enum Action {
+    /// node is proposer of the round, the valid block is re-proposed if there is one
+    Propose {
+        height: u64,
+        round: u64,
+        valid: Option<(u64, Proposal)>,
+    },
+    /// vote of the node has to be sent to peers, it is counted already
+    Broadcast(Vote),
+    /// timeout of the step has to fire after its duration
+    ScheduleTimeout { step: Step, height: u64, round: u64 },
+    /// block got 2f+1 precommits, it has to be stored with the certificate
+    Commit {
+        proposal: Proposal,
+        certificate: CommitCertificate,
+    },
+}
+
+/// RoundState runs propose, prevote & precommit steps of rounds at one height. It is fed
+/// with verified messages & fired timeouts and tells the engine what to do in return.
+/// A validator locks on the block it precommits and prevotes nil on other blocks until 2f+1
+/// prevotes of a later round justify them, so two blocks can't get 2f+1 precommits at the
+/// same height while at most f validators are byzantine.
+pub struct RoundState {
+    // peer identity
+    keypair: KeypairType,
+    // peer public key
+    public_key: String,
+    // public keys of validators, proposers take turns in this order
+    validator_set: Vec<String>,
+    height: u64,
+    round: u64,
+    step: Step,
+    // proposal the node precommitted with its round
+    locked: Option<(u64, Proposal)>,
+    // latest proposal which got 2f+1 prevotes with its round
+    valid: Option<(u64, Proposal)>,
+    // proposal of rightful proposer by round, with validity of its block
+    proposals: HashMap<u64, (Proposal, bool)>,
+    // first vote of every validator by round & vote type
+    votes: HashMap<(u64, VoteType), HashMap<String, Vote>>,
+    prevote_timeout_scheduled: bool,
+    precommit_timeout_scheduled: bool,
+    // 2f+1 prevotes for the proposal of current round are handled
+    valid_updated: bool,
+    // nothing is processed once the height is decided, or before it is started
+    decided: bool,
+}
+
+impl RoundState {
+    pub fn new(keypair: KeypairType, validator_set: Vec<String>) -> RoundState {
+        RoundState {
+            public_key: hex::encode(keypair.public().encode()),
+            keypair,
+            validator_set,
+            height: 0,
+            round: 0,
+            step: Step::Propose,
+            locked: None,
+            valid: None,
+            proposals: HashMap::new(),
+            votes: HashMap::new(),
+            prevote_timeout_scheduled: false,
+            precommit_timeout_scheduled: false,
+            valid_updated: false,
+            decided: true,
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    pub fn step(&self) -> Step {
+        self.step
+    }
+
+    pub fn is_validator(&self) -> bool {
+        self.validator_set.contains(&self.public_key)
+    }
+
+    pub fn validator_set(&self) -> &Vec<String> {
+        &self.validator_set
+    }
+
+    /// proposer of given round at current height, None if the turn can't be computed
+    pub fn proposer(&self, round: u64) -> Option<&String> {
+        let turn: u64 = self.height.checked_add(round)?;
+        let index = turn % self.validator_set.len() as u64;
+        Some(&self.validator_set[index as usize])
+    }
+
+    /// whether messages of given round are kept at current height
+    pub fn in_round_window(&self, round: u64) -> bool {
+        round <= self.round.saturating_add(ROUND_WINDOW)
+    }
+
+    /// this function will forget the decided height and start round 0 of given height
+    pub fn new_height(&mut self, height: u64) -> Vec<Action> {
+        self.height = height;
+        self.locked = None;
+        self.valid = None;
+        self.proposals.clear();
+        self.votes.clear();
+        self.decided = false;
+        self.start_round(0)
+    }
+
+    pub fn start_round(&mut self, round: u64) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        self.enter_round(round, &mut actions);
+        self.process(&mut actions);
+        actions
+    }
+
+    /// proposal has to be verified by the caller, valid tells whether its block can be
+    /// applied on top of local chain
+    pub fn on_proposal(&mut self, proposal: Proposal, valid: bool) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        let round: u64 = proposal.details.round;
+        if self.decided
+            || proposal.details.height != self.height
+            || !self.in_round_window(round)
+            || self.proposer(round) != Some(&proposal.public_key)
+            || self.proposals.contains_key(&round)
+        {
+            return actions;
+        }
+        if let Some(valid_round) = proposal.details.valid_round {
+            if valid_round >= round {
+                warn!(
+                    "proposal of round {} claims valid round {}",
+                    round, valid_round
+                );
+                return actions;
+            }
+        }
+        self.proposals.insert(round, (proposal, valid));
+        self.process(&mut actions);
+        actions
+    }
+
+    /// vote signature has to be verified by the caller
+    pub fn on_vote(&mut self, vote: Vote) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if self.decided
+            || vote.details.height != self.height
+            || !self.in_round_window(vote.details.round)
+            || !self.validator_set.contains(&vote.public_key)
+        {
+            return actions;
+        }
+        let votes = self
+            .votes
+            .entry((vote.details.round, vote.details.vote_type))
+            .or_insert_with(HashMap::new);
+        if let Some(known_vote) = votes.get(&vote.public_key) {
+            if known_vote.details != vote.details {
+                warn!(
+                    "validator {:?} voted twice in round {}",
+                    vote.public_key, vote.details.round
+                );
+            }
+            return actions;
+        }
+        votes.insert(vote.public_key.clone(), vote);
+        self.process(&mut actions);
+        actions
+    }
+
+    pub fn on_timeout(&mut self, step: Step, height: u64, round: u64) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if self.decided || height != self.height || round != self.round {
+            return actions;
+        }
+        match step {
+            Step::Propose if self.step == Step::Propose => {
+                self.cast(VoteType::Prevote, None, &mut actions);
+                self.step = Step::Prevote;
+            }
+            Step::Prevote if self.step == Step::Prevote => {
+                self.cast(VoteType::Precommit, None, &mut actions);
+                self.step = Step::Precommit;
+            }
+            Step::Precommit => self.enter_round(round + 1, &mut actions),
+            _ => return actions,
+        }
+        self.process(&mut actions);
+        actions
+    }
+
+    fn enter_round(&mut self, round: u64, actions: &mut Vec<Action>) {
+        self.round = round;
+        self.step = Step::Propose;
+        self.prevote_timeout_scheduled = false;
+        self.precommit_timeout_scheduled = false;
+        self.valid_updated = false;
+        if self.proposer(round) == Some(&self.public_key) {
+            actions.push(Action::Propose {
+                height: self.height,
+                round,
+                valid: self.valid.clone(),
+            });
+        }
+        // proposer waits as well, its proposal may fail
+        actions.push(Action::ScheduleTimeout {
+            step: Step::Propose,
+            height: self.height,
+            round,
+        });
+    }
+
+    // fn will apply rules until none of them makes progress
+    fn process(&mut self, actions: &mut Vec<Action>) {
+        loop {
+            if self.decided {
+                return;
+            }
+            if let Some((proposal, certificate)) = self.decision() {
+                self.decided = true;
+                actions.push(Action::Commit {
+                    proposal,
+                    certificate,
+                });
+                return;
+            }
+            if let Some(round) = self.skip_round() {
+                self.enter_round(round, actions);
+                continue;
+            }
+            let progress: bool = match self.step {
+                Step::Propose => self.prevote_proposal(actions),
+                Step::Prevote => self.lock_proposal(actions) || self.precommit_nil(actions),
+                Step::Precommit => self.lock_proposal(actions),
+            };
+            self.schedule_timeouts(actions);
+            if !progress {
+                return;
+            }
+        }
+    }
+
+    // proposal of any round which got 2f+1 precommits decides the height
+    fn decision(&self) -> Option<(Proposal, CommitCertificate)> {
+        for (round, (proposal, valid)) in self.proposals.iter() {
+            let block_hash: Hash = proposal.details.block_hash;
+            if !*valid
+                || self.count_for(*round, VoteType::Precommit, Some(block_hash)) < self.quorum()
+            {
+                continue;
+            }
+            let precommits = &self.votes[&(*round, VoteType::Precommit)];
+            let certificate: CommitCertificate = CommitCertificate {
+                height: self.height,
+                round: *round,
+                block_hash,
+                precommits: self
+                    .validator_set
+                    .iter()
+                    .filter_map(|public_key| precommits.get(public_key))
+                    .filter(|vote| vote.details.block_hash == Some(block_hash))
+                    .map(|vote| CommitSignature {
+                        public_key: vote.public_key.clone(),
+                        signature: vote.signature.clone(),
+                    })
+                    .collect(),
+            };
+            return Some((proposal.clone(), certificate));
+        }
+        None
+    }
+
+    // f+1 validators are in a later round, at least one of them is correct
+    fn skip_round(&self) -> Option<u64> {
+        let mut senders: HashMap<u64, HashSet<&String>> = HashMap::new();
+        for ((round, _), votes) in self.votes.iter() {
+            if *round > self.round {
+                senders.entry(*round).or_default().extend(votes.keys());
+            }
+        }
+        for (round, (proposal, _)) in self.proposals.iter() {
+            if *round > self.round {
+                senders
+                    .entry(*round)
+                    .or_default()
+                    .insert(&proposal.public_key);
+            }
+        }
+        let minimum_senders: usize = self.validator_set.len() - self.quorum() + 1;
+        senders
+            .into_iter()
+            .filter(|(_, senders)| senders.len() >= minimum_senders)
+            .map(|(round, _)| round)
+            .max()
+    }
+
+    // fn will prevote the proposal of current round unless the node is locked on another block
+    fn prevote_proposal(&mut self, actions: &mut Vec<Action>) -> bool {
+        let (proposal, valid): (Proposal, bool) = match self.proposals.get(&self.round) {
+            Some((proposal, valid)) => (proposal.clone(), *valid),
+            None => return false,
+        };
+        let block_hash: Hash = proposal.details.block_hash;
+        let acceptable: bool = match proposal.details.valid_round {
+            None => match &self.locked {
+                None => true,
+                Some((_, locked)) => locked.details.block_hash == block_hash,
+            },
+            Some(valid_round) => {
+                // re-proposal has to wait for the prevotes which justify it
+                if self.count_for(valid_round, VoteType::Prevote, Some(block_hash)) < self.quorum()
+                {
+                    return false;
+                }
+                match &self.locked {
+                    None => true,
+                    Some((locked_round, locked)) => {
+                        *locked_round <= valid_round || locked.details.block_hash == block_hash
+                    }
+                }
+            }
+        };
+        let vote: Option<Hash> = if valid && acceptable {
+            Some(block_hash)
+        } else {
+            None
+        };
+        self.cast(VoteType::Prevote, vote, actions);
+        self.step = Step::Prevote;
+        true
+    }
+
+    // fn will lock on the proposal of current round once it got 2f+1 prevotes
+    fn lock_proposal(&mut self, actions: &mut Vec<Action>) -> bool {
+        if self.valid_updated {
+            return false;
+        }
+        let proposal: Proposal = match self.proposals.get(&self.round) {
+            Some((proposal, true)) => proposal.clone(),
+            _ => return false,
+        };
+        let block_hash: Hash = proposal.details.block_hash;
+        if self.count_for(self.round, VoteType::Prevote, Some(block_hash)) < self.quorum() {
+            return false;
+        }
+        self.valid_updated = true;
+        if self.step == Step::Prevote {
+            self.locked = Some((self.round, proposal.clone()));
+            self.cast(VoteType::Precommit, Some(block_hash), actions);
+            self.step = Step::Precommit;
+        }
+        self.valid = Some((self.round, proposal));
+        true
+    }
+
+    fn precommit_nil(&mut self, actions: &mut Vec<Action>) -> bool {
+        if self.count_for(self.round, VoteType::Prevote, None) < self.quorum() {
+            return false;
+        }
+        self.cast(VoteType::Precommit, None, actions);
+        self.step = Step::Precommit;
+        true
+    }
+
+    // 2f+1 votes of a kind but no 2f+1 for one value, missing votes are waited for
+    fn schedule_timeouts(&mut self, actions: &mut Vec<Action>) {
+        if self.step == Step::Prevote
+            && !self.prevote_timeout_scheduled
+            && self.count_any(self.round, VoteType::Prevote) >= self.quorum()
+        {
+            self.prevote_timeout_scheduled = true;
+            actions.push(Action::ScheduleTimeout {
+                step: Step::Prevote,
+                height: self.height,
+                round: self.round,
+            });
+        }
+        if !self.precommit_timeout_scheduled
+            && self.count_any(self.round, VoteType::Precommit) >= self.quorum()
+        {
+            self.precommit_timeout_scheduled = true;
+            actions.push(Action::ScheduleTimeout {
+                step: Step::Precommit,
+                height: self.height,
+                round: self.round,
+            });
+        }
+    }
+
+    // fn will sign the vote, count it and hand it over for broadcast
+    fn cast(&mut self, vote_type: VoteType, block_hash: Option<Hash>, actions: &mut Vec<Action>) {
+        if !self.is_validator() {
+            return;
+        }
+        let vote: Vote = Vote::create(
+            &self.keypair,
+            VoteDetails {
+                vote_type,
+                height: self.height,
+                round: self.round,
+                block_hash,
+            },
+        );
+        self.votes
+            .entry((self.round, vote_type))
+            .or_insert_with(HashMap::new)
+            .insert(self.public_key.clone(), vote.clone());
+        actions.push(Action::Broadcast(vote));
+    }
+
+    fn quorum(&self) -> usize {
+        quorum(self.validator_set.len())
+    }
+
+    fn count_any(&self, round: u64, vote_type: VoteType) -> usize {
+        match self.votes.get(&(round, vote_type)) {
+            Some(votes) => votes.len(),
+            None => 0,
+        }
+    }
+
+    fn count_for(&self, round: u64, vote_type: VoteType, block_hash: Option<Hash>) -> usize {
+        match self.votes.get(&(round, vote_type)) {
+            Some(votes) => votes
+                .values()
+                .filter(|vote| vote.details.block_hash == block_hash)
+                .count(),
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_round_state {
+    use super::*;
+    use exonum_merkledb::ObjectHash;
+    use schema::block::{Block, BlockTraits, SignedBlock};
+    use utils::keypair::{CryptoKeypair, Keypair};
+
+    struct Validators {
+        kps: Vec<KeypairType>,
+        validator_set: Vec<String>,
+    }
+
+    impl Validators {
+        fn new(count: usize) -> Validators {
+            let kps: Vec<KeypairType> = (0..count).map(|_| Keypair::generate()).collect();
+            let validator_set: Vec<String> = kps
+                .iter()
+                .map(|kp| hex::encode(kp.public().encode()))
+                .collect();
+            Validators { kps, validator_set }
+        }
+
+        fn kp(&self, public_key: &String) -> &KeypairType {
+            let index = self
+                .validator_set
+                .iter()
+                .position(|each| each == public_key)
+                .unwrap();
+            &self.kps[index]
+        }
+
+        fn proposal(&self, state: &RoundState, round: u64, nonce: u64) -> Proposal {
+            let kp: &KeypairType = self.kp(state.proposer(round).unwrap());
+            let block: Block = Block::new_block(
+                state.height(),
+                hex::encode(kp.public().encode()),
+                Hash::zero(),
+                Vec::new(),
+                [Hash::zero(), Hash::zero(), Hash::zero(), Hash::zero()],
+                nonce.to_be_bytes().to_vec(),
+            );
+            let sign: Vec<u8> = block.sign(kp);
+            let block: SignedBlock = SignedBlock::create_block(block, sign, Vec::new());
+            Proposal::create(kp, round, None, block, Vec::new())
+        }
+
+        fn vote(
+            &self,
+            index: usize,
+            vote_type: VoteType,
+            state: &RoundState,
+            round: u64,
+            block_hash: Option<Hash>,
+        ) -> Vote {
+            Vote::create(
+                &self.kps[index],
+                VoteDetails {
+                    vote_type,
+                    height: state.height(),
+                    round,
+                    block_hash,
+                },
+            )
+        }
+    }
+
+    fn broadcast_votes(actions: &[Action]) -> Vec<VoteDetails> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Broadcast(vote) => Some(vote.details.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_state_commit() {
+        let validators = Validators::new(4);
+        let mut state =
+            RoundState::new(validators.kps[0].clone(), validators.validator_set.clone());
+        let actions: Vec<Action> = state.new_height(1);
+        assert_eq!(state.proposer(0), Some(&validators.validator_set[1]));
+        assert!(actions.iter().all(|action| match action {
+            Action::ScheduleTimeout { .. } => true,
+            _ => false,
+        }));
+
+        let proposal: Proposal = validators.proposal(&state, 0, 0);
+        let block_hash: Option<Hash> = Some(proposal.block.block.object_hash());
+        let actions: Vec<Action> = state.on_proposal(proposal, true);
+        assert_eq!(broadcast_votes(&actions)[0].block_hash, block_hash);
+        assert_eq!(state.step(), Step::Prevote);
+
+        for index in 1..3 {
+            let vote = validators.vote(index, VoteType::Prevote, &state, 0, block_hash);
+            let actions: Vec<Action> = state.on_vote(vote);
+            if index == 2 {
+                let votes: Vec<VoteDetails> = broadcast_votes(&actions);
+                assert_eq!(votes[0].vote_type, VoteType::Precommit);
+                assert_eq!(votes[0].block_hash, block_hash);
+            }
+        }
+        assert_eq!(state.step(), Step::Precommit);
+
+        let vote = validators.vote(1, VoteType::Precommit, &state, 0, block_hash);
+        assert!(state.on_vote(vote).is_empty());
+        let vote = validators.vote(3, VoteType::Precommit, &state, 0, block_hash);
+        let actions: Vec<Action> = state.on_vote(vote);
+        match actions.last() {
+            Some(Action::Commit {
+                proposal,
+                certificate,
+            }) => {
+                assert_eq!(Some(proposal.block.block.object_hash()), block_hash);
+                assert_eq!(certificate.precommits.len(), 3);
+                assert!(certificate.verify_block(&proposal.block, &validators.validator_set));
+            }
+            _ => panic!("block isn't committed"),
+        }
+        // late votes of the decided height are ignored
+        let vote = validators.vote(2, VoteType::Precommit, &state, 0, block_hash);
+        assert!(state.on_vote(vote).is_empty());
+    }
+
+    #[test]
+    fn test_round_state_locking() {
+        let validators = Validators::new(4);
+        let mut state =
+            RoundState::new(validators.kps[0].clone(), validators.validator_set.clone());
+        state.new_height(1);
+
+        // round 0, node locks on block A but precommits of others don't arrive
+        let proposal_a: Proposal = validators.proposal(&state, 0, 0);
+        let hash_a: Option<Hash> = Some(proposal_a.details.block_hash);
+        state.on_proposal(proposal_a.clone(), true);
+        for index in 1..3 {
+            state.on_vote(validators.vote(index, VoteType::Prevote, &state, 0, hash_a));
+        }
+        for index in 1..3 {
+            state.on_vote(validators.vote(index, VoteType::Precommit, &state, 0, None));
+        }
+        let actions: Vec<Action> = state.on_timeout(Step::Precommit, 1, 0);
+        assert_eq!(state.round(), 1);
+        assert_eq!(state.step(), Step::Propose);
+        assert!(actions.iter().any(|action| match action {
+            Action::ScheduleTimeout {
+                step: Step::Propose,
+                round: 1,
+                ..
+            } => true,
+            _ => false,
+        }));
+
+        // round 1, another block B is prevoted nil as the node is locked on A
+        let proposal_b: Proposal = validators.proposal(&state, 1, 1);
+        let actions: Vec<Action> = state.on_proposal(proposal_b, true);
+        assert_eq!(broadcast_votes(&actions)[0].block_hash, None);
+        for index in 1..3 {
+            state.on_vote(validators.vote(index, VoteType::Prevote, &state, 1, None));
+        }
+        assert_eq!(state.step(), Step::Precommit);
+        state.on_timeout(Step::Precommit, 1, 1);
+
+        // round 2, block A re-proposed with its valid round is prevoted again
+        let kp: &KeypairType = validators.kp(state.proposer(2).unwrap());
+        let reproposal: Proposal =
+            Proposal::create(kp, 2, Some(0), proposal_a.block.clone(), Vec::new());
+        let actions: Vec<Action> = state.on_proposal(reproposal, true);
+        assert_eq!(broadcast_votes(&actions)[0].block_hash, hash_a);
+    }
+
+    #[test]
+    fn test_round_state_timeouts() {
+        let validators = Validators::new(4);
+        let mut state =
+            RoundState::new(validators.kps[0].clone(), validators.validator_set.clone());
+        state.new_height(2);
+
+        // proposal didn't arrive, node prevotes & precommits nil
+        let actions: Vec<Action> = state.on_timeout(Step::Propose, 2, 0);
+        assert_eq!(broadcast_votes(&actions)[0].block_hash, None);
+        // stale timeouts are ignored
+        assert!(state.on_timeout(Step::Propose, 2, 0).is_empty());
+        assert!(state.on_timeout(Step::Prevote, 1, 0).is_empty());
+        for index in 1..3 {
+            state.on_vote(validators.vote(index, VoteType::Prevote, &state, 0, None));
+        }
+        assert_eq!(state.step(), Step::Precommit);
+
+        // f+1 validators moved to round 5 already
+        state.on_vote(validators.vote(1, VoteType::Prevote, &state, 5, None));
+        assert_eq!(state.round(), 0);
+        let actions: Vec<Action> =
+            state.on_vote(validators.vote(2, VoteType::Precommit, &state, 5, None));
+        assert_eq!(state.round(), 5);
+        assert!(actions.iter().any(|action| match action {
+            Action::ScheduleTimeout {
+                step: Step::Propose,
+                round: 5,
+                ..
+            } => true,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn test_round_state_bounds() {
+        let validators = Validators::new(4);
+        let mut state =
+            RoundState::new(validators.kps[0].clone(), validators.validator_set.clone());
+        state.new_height(2);
+
+        // f+1 validators beyond the round window don't move the node
+        let far_round: u64 = ROUND_WINDOW + 1;
+        state.on_vote(validators.vote(1, VoteType::Prevote, &state, far_round, None));
+        let proposal: Proposal = validators.proposal(&state, far_round, 0);
+        state.on_proposal(proposal, true);
+        state.on_vote(validators.vote(2, VoteType::Prevote, &state, far_round, None));
+        assert_eq!(state.round(), 0);
+        state.on_vote(validators.vote(1, VoteType::Prevote, &state, ROUND_WINDOW, None));
+        state.on_vote(validators.vote(2, VoteType::Prevote, &state, ROUND_WINDOW, None));
+        assert_eq!(state.round(), ROUND_WINDOW);
+
+        // proposer turn overflowing u64 has no proposer
+        state.new_height(u64::MAX);
+        assert!(state.proposer(0).is_some());
+        assert_eq!(state.proposer(1), None);
+    }
+}
@@ -0,0 +1,11 @@
+#[macro_use]
+extern crate lazy_static;
+
+#[macro_use]
+extern crate log;
+
+pub mod bft_interface;
+pub mod bft_message_sender;
+pub mod bft_messages;
+pub mod bft_state;
+pub mod config;
@@ -0,0 +1,298 @@
+extern crate db_service;
+extern crate message_handler;
+extern crate schema;
+extern crate utils;
+
+use super::config::initialize_config;
+use consensus_engine::{store_block, stored_height, sync_behind, BlockImporter, ConsensusEngine};
+use db_service::db_fork_ref::SchemaFork;
+use db_service::db_layer::DbInstance;
+use exonum_crypto::Hash;
+use exonum_merkledb::ObjectHash;
+use futures::{channel::mpsc::*, executor::*, future, prelude::*, task::*};
+use message_handler::message_sender::MessageSender;
+use message_handler::messages::{ConsensusRequest, MessageTypes, MSG_DISPATCHER};
+use schema::block::SignedBlock;
+use schema::transaction_pool::{TxnPool, POOL};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use utils::configreader::Configuration;
+use utils::keypair::KeypairType;
+use utils::serializer::{serialize, Deserialize, Serialize};
+
+// interval (in millis) at which txn pool & seal requests are checked
+const TICK_TIME: u64 = 50;
+
+pub struct Dev {
+    // peer identity
+    keypair: KeypairType,
+    // seal a block when new txns land in pool
+    seal_on_transaction: bool,
+    // time (in millis) after which a block is sealed without txns, 0 disables it
+    block_interval: u64,
+    // genesis block time (in seconds)
+    start_time: u64,
+    // storage handle of the node
+    db: DbInstance,
+    // data sender in P2P system, full nodes may follow sealed blocks
+    sender: Sender<Option<MessageTypes>>,
+    // public key of the sealing node, the node seals only if it is its own
+    sealer: String,
+    // engine details shared with message handling
+    meta_data: Arc<Mutex<MetaData>>,
+    // txns the last proposed block couldn't execute, evicted once the block is stored
+    rejected_txns: Vec<Hash>,
+}
+
+/// Dev engine details shared with message handling
+pub struct MetaData {
+    // a block has to be sealed even without txns
+    seal_requested: bool,
+}
+
+/// DevImporter stores blocks the sealing node sealed, other nodes follow them this way
+pub struct DevImporter {
+    // storage handle of the node
+    db: DbInstance,
+    // public key of the sealing node
+    sealer: String,
+}
+
+impl BlockImporter for DevImporter {
+    fn import_block(&self, signed_block: &SignedBlock) -> bool {
+        if signed_block.block.peer_id != self.sealer || !signed_block.validate() {
+            warn!(
+                "block with id {} isn't signed by its sealer",
+                signed_block.block.id
+            );
+            return false;
+        }
+        sync_behind(&self.db, signed_block);
+        store_block(&self.db, signed_block)
+    }
+}
+
+// Dev consensus custom headers for the signed block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomHeaders {
+    timestamp: u64,
+}
+
+impl Dev {
+    // fn will listen requests of the node, dev consensus has no peers
+    fn dev_msg_receiver(
+        meta_data: Arc<Mutex<MetaData>>,
+        rx: Arc<Mutex<Receiver<Option<ConsensusRequest>>>>,
+    ) {
+        thread::spawn(move || {
+            block_on(future::poll_fn(move |cx: &mut Context| {
+                loop {
+                    match rx.lock().unwrap().poll_next_unpin(cx) {
+                        Poll::Ready(Some(msg)) => match msg {
+                            None => info!("Empty msg received !"),
+                            Some(request) => match request {
+                                ConsensusRequest::SealBlock => {
+                                    info!("SealBlock request received");
+                                    meta_data.lock().unwrap().seal_requested = true;
+                                }
+                            },
+                        },
+                        Poll::Ready(None) => {
+                            info!("channel closed !");
+                            return Poll::Ready(1);
+                        }
+                        Poll::Pending => break,
+                    }
+                }
+                Poll::Pending
+            }));
+        });
+    }
+
+    fn is_sealer(&self) -> bool {
+        self.sealer == hex::encode(self.keypair.public().encode())
+    }
+
+    fn custom_headers(timestamp: u64) -> Vec<u8> {
+        match serialize(&CustomHeaders { timestamp }) {
+            Ok(value) => value,
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    // fn will seal a block of txns in pool on top of local chain right away, a block
+    // without txns is sealed only if allowed. Pool is left untouched when the block is
+    // discarded. It returns count of sealed txns
+    fn seal_block(&mut self, allow_empty: bool) -> Option<usize> {
+        let signed_block: SignedBlock = self.propose_block()?;
+        let txn_count: usize = signed_block.block.txn_pool.len();
+        if txn_count == 0 && !allow_empty {
+            return None;
+        }
+        if !store_block(&self.db, &signed_block) {
+            return None;
+        }
+        POOL.evict_txns(&self.rejected_txns);
+        info!(
+            "block with id {} & hash {} sealed with {} txns",
+            signed_block.block.id,
+            signed_block.object_hash(),
+            txn_count
+        );
+        MessageSender::send_block_msg(&mut self.sender, signed_block);
+        Some(txn_count)
+    }
+
+    // fn will seal blocks when txns land in pool, on request or when block interval passes
+    fn state_machine(&mut self) {
+        // pool length after the last sealing, txns landing in pool change it
+        let mut sealed_pool_length: usize = 0;
+        let mut last_seal: Instant = Instant::now();
+        loop {
+            let seal_requested: bool = {
+                let mut meta_data_obj = self.meta_data.lock().unwrap();
+                let seal_requested: bool = meta_data_obj.seal_requested;
+                meta_data_obj.seal_requested = false;
+                seal_requested
+            };
+            let pool_length: usize = POOL.length_hash_pool();
+            let interval_passed: bool = self.block_interval > 0
+                && last_seal.elapsed() >= Duration::from_millis(self.block_interval);
+            let txns_landed: bool =
+                self.seal_on_transaction && pool_length > 0 && pool_length != sealed_pool_length;
+            if seal_requested || interval_passed || txns_landed {
+                sealed_pool_length = match self.seal_block(seal_requested || interval_passed) {
+                    Some(txn_count) => {
+                        last_seal = Instant::now();
+                        // txns beyond block_transaction_limit are sealed in the next block
+                        if txn_count > 0 {
+                            0
+                        } else {
+                            POOL.length_hash_pool()
+                        }
+                    }
+                    // txns left in pool can't be executed yet, e.g. nonce gaps
+                    None => POOL.length_hash_pool(),
+                };
+            }
+            thread::sleep(Duration::from_millis(TICK_TIME));
+        }
+    }
+
+    /// this function will create dev engine as per consensus config file, it is registered
+    /// as "dev" engine
+    pub fn create_engine(
+        config: &Configuration,
+        db: DbInstance,
+        consensus_file_path: &str,
+        sender: Sender<Option<MessageTypes>>,
+    ) -> Box<dyn ConsensusEngine> {
+        initialize_config(consensus_file_path);
+        let dev_config: &crate::config::Configuration = &crate::config::DEV_CONFIG;
+        let public_key: String = hex::encode(config.node.keypair.public().encode());
+        let sealer: String = if dev_config.sealer.is_empty() {
+            public_key
+        } else {
+            dev_config.sealer.clone()
+        };
+        Box::new(Dev {
+            keypair: config.node.keypair.clone(),
+            seal_on_transaction: dev_config.seal_on_transaction,
+            block_interval: dev_config.block_interval,
+            start_time: dev_config.start_time,
+            db,
+            sender,
+            sealer,
+            meta_data: Arc::new(Mutex::new(MetaData {
+                seal_requested: false,
+            })),
+            rejected_txns: Vec::new(),
+        })
+    }
+}
+
+impl ConsensusEngine for Dev {
+    // dev consensus has no peers to sync from, genesis block is created whenever storage
+    // is empty
+    fn init_state(&mut self, _genesis_block: bool) {
+        let fork = self.db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if schema.blockchain_length() == 0 {
+                let genesis_signed_block = schema.initialize_db(
+                    Dev::custom_headers(self.start_time),
+                    self.start_time as u128,
+                );
+                info!(
+                    "genesis block created with hash {:?}",
+                    genesis_signed_block.get_hash()
+                );
+            } else {
+                info!(
+                    "started from previous state {} {}",
+                    schema.blockchain_length(),
+                    schema.state_trie_merkle_hash()
+                )
+            }
+        }
+        self.db.patch(fork);
+    }
+
+    // dev consensus has no peers, it listens requests of the node on their own channel
+    fn handle_messages(&mut self, _msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        Dev::dev_msg_receiver(
+            self.meta_data.clone(),
+            MSG_DISPATCHER.consensus_request_receiver.clone(),
+        );
+    }
+
+    fn propose_block(&mut self) -> Option<SignedBlock> {
+        let fork = self.db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        let (signed_block, rejected_txns) =
+            schema.prepare_block(&self.keypair, Dev::custom_headers(Dev::now()));
+        self.rejected_txns = rejected_txns;
+        Some(signed_block)
+    }
+
+    fn verify_block(&self, signed_block: &SignedBlock) -> bool {
+        if !signed_block.validate() {
+            return false;
+        }
+        let fork = self.db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        schema.update_block(signed_block)
+    }
+
+    // the single node seals blocks on its own, they are never reverted
+    fn finalized_height(&self) -> u64 {
+        stored_height(&self.db)
+    }
+
+    // sealing node stores only blocks it sealed
+    fn block_importer(&self) -> Option<Arc<dyn BlockImporter>> {
+        if self.is_sealer() {
+            return None;
+        }
+        Some(Arc::new(DevImporter {
+            db: self.db.clone(),
+            sealer: self.sealer.clone(),
+        }))
+    }
+
+    fn run(&mut self) {
+        if !self.is_sealer() {
+            info!("node follows blocks sealed by {}", self.sealer);
+            return;
+        }
+        self.state_machine();
+    }
+}
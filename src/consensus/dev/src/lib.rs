@@ -0,0 +1,8 @@
+#[macro_use]
+extern crate lazy_static;
+
+#[macro_use]
+extern crate log;
+
+pub mod config;
+pub mod dev_interface;
@@ -0,0 +1,243 @@
+extern crate db_service;
+extern crate message_handler;
+extern crate schema;
+extern crate utils;
+#[macro_use]
+extern crate log;
+
+use db_service::db_fork_ref::SchemaFork;
+use db_service::db_layer::DbInstance;
+use db_service::db_snapshot_ref::SchemaSnap;
+use exonum_merkledb::{ObjectHash, Snapshot};
+use futures::channel::mpsc::*;
+use message_handler::messages::MessageTypes;
+use schema::block::SignedBlock;
+use schema::state::State;
+use schema::transaction_pool::{TxnPool, POOL};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use utils::configreader::Configuration;
+
+/// ConsensusEngine is implemented by every consensus the node can run. The node drives an
+/// engine through start_engine, engines are selected by `consensus_name` of node config.
+pub trait ConsensusEngine {
+    /// create genesis block if storage is empty & the node is configured to, otherwise
+    /// resume from the stored chain
+    fn init_state(&mut self, genesis_block: bool);
+    /// spawn handling of consensus messages received from peers
+    fn handle_messages(&mut self, msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>);
+    /// compute a new block on top of local chain, None if it can't be proposed now
+    fn propose_block(&mut self) -> Option<SignedBlock>;
+    /// this function will check whether a block received from a peer can be accepted on top
+    /// of local chain, nothing is persisted
+    fn verify_block(&self, signed_block: &SignedBlock) -> bool;
+    /// height of the last block which can't be reverted anymore
+    fn finalized_height(&self) -> u64;
+    /// importer of blocks peers gossip on node topic, None if the engine doesn't gossip
+    /// blocks and such blocks have to be dropped
+    fn block_importer(&self) -> Option<Arc<dyn BlockImporter>>;
+    /// drive rounds of the engine, it returns only when consensus stops
+    fn run(&mut self);
+}
+
+/// BlockImporter stores blocks gossiped on node topic, a block is verified the way its engine
+/// verifies blocks before it is stored
+pub trait BlockImporter: Send + Sync {
+    /// this function will verify given block on top of stored chain and persist it, a block
+    /// ahead of stored chain makes it sync the chain from peers first
+    fn import_block(&self, signed_block: &SignedBlock) -> bool;
+}
+
+/// SharedImporter hands importer of the running engine over to node message processing, it
+/// stays None until the engine is started
+pub type SharedImporter = Arc<RwLock<Option<Arc<dyn BlockImporter>>>>;
+
+/// EngineFactory creates an engine from node config, storage, consensus config file path
+/// and the sender of messages to peers
+pub type EngineFactory = fn(
+    config: &Configuration,
+    db: DbInstance,
+    consensus_file_path: &str,
+    sender: Sender<Option<MessageTypes>>,
+) -> Box<dyn ConsensusEngine>;
+
+/// EngineRegistry maps consensus names to engine factories
+pub struct EngineRegistry {
+    factories: HashMap<String, EngineFactory>,
+}
+
+impl EngineRegistry {
+    pub fn new() -> EngineRegistry {
+        EngineRegistry {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// this function will register an engine under given name, a name can be taken once
+    pub fn register(&mut self, name: &str, factory: EngineFactory) -> Result<(), String> {
+        if self.factories.contains_key(name) {
+            return Err(format!("consensus engine {} is already registered", name));
+        }
+        self.factories.insert(String::from(name), factory);
+        Ok(())
+    }
+
+    /// registered engine names in sorted order
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.factories.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// this function will create the engine registered under given name
+    pub fn create(
+        &self,
+        name: &str,
+        config: &Configuration,
+        db: DbInstance,
+        consensus_file_path: &str,
+        sender: Sender<Option<MessageTypes>>,
+    ) -> Result<Box<dyn ConsensusEngine>, String> {
+        match self.factories.get(name) {
+            Some(factory) => Ok(factory(config, db, consensus_file_path, sender)),
+            None => Err(format!(
+                "consensus engine {} is unknown, registered engines are {:?}",
+                name,
+                self.names()
+            )),
+        }
+    }
+}
+
+/// this function will start handling peer messages of the engine, hand over its block
+/// importer, initialise its state and run its rounds
+pub fn start_engine(
+    engine: &mut dyn ConsensusEngine,
+    genesis_block: bool,
+    msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
+    importer: &SharedImporter,
+) {
+    engine.handle_messages(msg_receiver);
+    *importer.write().unwrap() = engine.block_importer();
+    engine.init_state(genesis_block);
+    info!(
+        "consensus engine resumes from finalized height {}",
+        engine.finalized_height()
+    );
+    engine.run();
+}
+
+/// height of the last block stored in given db, engines which store only final blocks
+/// report it as finalized height
+pub fn stored_height(db: &DbInstance) -> u64 {
+    let snapshot: Box<dyn Snapshot> = db.snapshot();
+    let schema = SchemaSnap::new(&snapshot);
+    schema.get_blockchain_length().saturating_sub(1)
+}
+
+/// this function will apply given block on top of stored chain and persist it, txns of the
+/// block leave the txn pool. Engines storing blocks right after proposing them use it
+pub fn store_block(db: &DbInstance, signed_block: &SignedBlock) -> bool {
+    let fork = db.fork();
+    {
+        let mut schema = SchemaFork::new(&fork);
+        if !schema.update_block(signed_block) {
+            error!(
+                "block with id {} & hash {} couldn't added in database",
+                signed_block.block.id,
+                signed_block.object_hash()
+            );
+            return false;
+        }
+    }
+    db.patch(fork);
+    POOL.sync_pool(&signed_block.block.txn_pool);
+    true
+}
+
+/// this function will keep given states in node db, genesis block created or replayed by
+/// the node starts with them
+pub fn set_genesis_states(db: &DbInstance, states: Vec<(String, State)>) {
+    let fork = db.fork();
+    {
+        let mut schema = SchemaFork::new(&fork);
+        schema.set_genesis_states(states);
+    }
+    db.patch(fork);
+}
+
+/// this function will sync stored chain from peers if given block is ahead of it, importers
+/// catch up this way before storing a gossiped block
+pub fn sync_behind(db: &DbInstance, signed_block: &SignedBlock) {
+    if signed_block.block.id <= stored_height(db) + 1 {
+        return;
+    }
+    let fork = db.fork();
+    {
+        let mut schema = SchemaFork::new(&fork);
+        schema.sync_state();
+    }
+    db.patch(fork);
+}
+
+#[cfg(test)]
+mod test_engine_registry {
+    use super::*;
+
+    struct IdleEngine {
+        db: DbInstance,
+    }
+
+    impl ConsensusEngine for IdleEngine {
+        fn init_state(&mut self, _genesis_block: bool) {}
+        fn handle_messages(&mut self, _msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {}
+        fn propose_block(&mut self) -> Option<SignedBlock> {
+            None
+        }
+        fn verify_block(&self, _signed_block: &SignedBlock) -> bool {
+            false
+        }
+        fn finalized_height(&self) -> u64 {
+            stored_height(&self.db)
+        }
+        fn block_importer(&self) -> Option<Arc<dyn BlockImporter>> {
+            None
+        }
+        fn run(&mut self) {}
+    }
+
+    fn idle_engine(
+        _config: &Configuration,
+        db: DbInstance,
+        _consensus_file_path: &str,
+        _sender: Sender<Option<MessageTypes>>,
+    ) -> Box<dyn ConsensusEngine> {
+        Box::new(IdleEngine { db })
+    }
+
+    #[test]
+    fn test_engine_registry() {
+        let mut registry = EngineRegistry::new();
+        assert_eq!(registry.register("idle", idle_engine), Ok(()));
+        assert!(registry.register("idle", idle_engine).is_err());
+        assert_eq!(registry.register("another_idle", idle_engine), Ok(()));
+        assert_eq!(registry.names(), vec!["another_idle", "idle"]);
+
+        utils::configreader::initialize_config("../../../config.toml");
+        let config: &Configuration = &utils::configreader::GLOBAL_CONFIG;
+        let (sender, _receiver) = channel::<Option<MessageTypes>>(1);
+        let db: DbInstance = DbInstance::temporary();
+        let mut engine: Box<dyn ConsensusEngine> = registry
+            .create("idle", config, db, "consensus.toml", sender.clone())
+            .unwrap();
+        assert!(engine.propose_block().is_none());
+        assert_eq!(engine.finalized_height(), 0);
+        assert!(engine.block_importer().is_none());
+        let error: String =
+            match registry.create("raft", config, DbInstance::temporary(), "", sender) {
+                Ok(_) => panic!("unregistered engine created"),
+                Err(error) => error,
+            };
+        assert!(error.starts_with("consensus engine raft is unknown"));
+    }
+}
@@ -8,11 +8,15 @@ use super::poa_message_sender::ConsensusMessageSender;
 use super::poa_messages::{
     ConsensusMessageTypes, ElectionPing, ElectionPong, LeaderElection, SignedLeaderElection,
 };
+use consensus_engine::{
+    set_genesis_states, store_block, stored_height, sync_behind, BlockImporter, ConsensusEngine,
+};
 use db_service::db_fork_ref::SchemaFork;
-use db_service::db_layer::{fork_db, patch_db, snapshot_db};
+use db_service::db_layer::DbInstance;
 use db_service::db_snapshot_ref::SchemaSnap;
 use exonum_merkledb::{Fork, ObjectHash};
 use futures::{channel::mpsc::*, executor::*, future, prelude::*, task::*};
+use governance::state::{genesis_validator_state, validators_at};
 use message_handler::message_sender::MessageSender;
 use message_handler::messages::MessageTypes;
 use schema::block::SignedBlock;
@@ -31,8 +35,11 @@ pub struct Consensus {
     keypair: KeypairType,
     pk: String,
     round_number: u64,
-    public_keys: Vec<String>,
     force_sealing: bool,
+    db: DbInstance,
+    sender: Sender<Option<MessageTypes>>,
+    leader_map: Arc<Mutex<LeaderMap>>,
+    meta_data: Arc<Mutex<MetaData>>,
 }
 
 pub struct LeaderMap {
@@ -41,19 +48,62 @@ pub struct LeaderMap {
 
 pub struct MetaData {
     active_node: Vec<String>,
-    public_keys: Vec<String>,
     kp: KeypairType,
     sender: Sender<Option<MessageTypes>>,
+    db: DbInstance,
+}
+
+impl MetaData {
+    // fn will resolve validators of block at given height from stored chain state, changes
+    // voted through governance app are picked up at their activation height
+    fn validators(&self, height: u64) -> Vec<String> {
+        let snapshot = self.db.snapshot();
+        let schema = SchemaSnap::new(&snapshot);
+        validators_at(&schema, height).unwrap_or_default()
+    }
+
+    // fn will tell whether block is signed by a validator of its height
+    fn signed_by_validator(&self, signed_block: &SignedBlock) -> bool {
+        self.validators(signed_block.block.id)
+            .contains(&signed_block.block.peer_id)
+            && signed_block.validate()
+    }
+}
+
+/// PoaImporter stores blocks gossiped by leaders, followers take blocks this way
+pub struct PoaImporter {
+    db: DbInstance,
+    meta_data: Arc<Mutex<MetaData>>,
+}
+
+impl BlockImporter for PoaImporter {
+    /// blocks below the gossiped one are synced first, the signer is checked against the
+    /// validator set of the synced chain
+    fn import_block(&self, signed_block: &SignedBlock) -> bool {
+        sync_behind(&self.db, signed_block);
+        if !self
+            .meta_data
+            .lock()
+            .unwrap()
+            .signed_by_validator(signed_block)
+        {
+            warn!(
+                "block with id {} isn't signed by a validator",
+                signed_block.block.id
+            );
+            return false;
+        }
+        store_block(&self.db, signed_block)
+    }
 }
 
 impl Consensus {
-    fn init_state(
+    fn init_genesis_state(
         &mut self,
-        _db_path: &String,
         leader_map: Arc<Mutex<LeaderMap>>,
         sender: &mut Sender<Option<MessageTypes>>,
     ) {
-        let fork = fork_db();
+        let fork = self.db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             if schema.blockchain_length() == 0 {
@@ -77,7 +127,7 @@ impl Consensus {
                 )
             }
         }
-        patch_db(fork);
+        self.db.patch(fork);
         let leader_payload: LeaderElection = LeaderElection {
             block_height: self.round_number + 1,
             old_leader: self.pk.clone(),
@@ -154,30 +204,27 @@ impl Consensus {
         }
     }
 
-    fn propose_block(&self, fork: Fork) -> (Fork, SignedBlock) {
-        let signed_block: SignedBlock;
-        {
-            let mut schema = SchemaFork::new(&fork);
-            // let signed_block = schema.create_block(&self.keypair);
-            let custom_headers: Vec<u8> = Vec::new();
-            if !self.force_sealing {
-                let (fork_instance, signed_block) =
-                    schema.forge_new_block(&self.keypair, custom_headers);
-                info!(
-                    "new block created.. id {},hash {}",
-                    signed_block.block.id,
-                    signed_block.object_hash()
-                );
-                return (fork_instance, signed_block);
-            }
-            signed_block = schema.create_block(&self.keypair, custom_headers);
-            info!(
-                "new block created.. id {},hash {}",
-                signed_block.block.id,
-                signed_block.object_hash()
-            );
-        }
-        return (fork, signed_block);
+    // fn will compute new block on top of local chain, returned fork has the block applied
+    fn compute_block(&self) -> (Fork, SignedBlock) {
+        let custom_headers: Vec<u8> = Vec::new();
+        let (fork, signed_block): (Fork, SignedBlock) = if self.force_sealing {
+            let fork = self.db.fork();
+            let signed_block: SignedBlock = {
+                let mut schema = SchemaFork::new(&fork);
+                schema.create_block(&self.keypair, custom_headers)
+            };
+            (fork, signed_block)
+        } else {
+            let fork = self.db.fork();
+            let schema = SchemaFork::new(&fork);
+            schema.forge_new_block(&self.db, &self.keypair, custom_headers)
+        };
+        info!(
+            "new block created.. id {},hash {}",
+            signed_block.block.id,
+            signed_block.object_hash()
+        );
+        (fork, signed_block)
     }
 
     fn validator(
@@ -188,14 +235,20 @@ impl Consensus {
         // no polling machenism of txn_pool and create block need to implement or modified here
         // if one want to change the create_block and txn priority then change/ implment that part in
         // schema operations and p2p module
-        let mut fork = fork_db();
+        let (fork, signed_block): (Fork, SignedBlock) = self.compute_block();
+        // block is kept as computed, its txns aren't executed again
+        if signed_block.block.id != stored_height(&self.db) + 1 {
+            warn!(
+                "block with id {} dropped, local chain has moved",
+                signed_block.block.id
+            );
+            return false;
+        }
+        self.db.patch(fork);
+        POOL.sync_pool(&signed_block.block.txn_pool);
+        self.round_number = signed_block.block.id;
+        MessageSender::send_block_msg(sender, signed_block);
         {
-            let (fork_instance, signed_block) = self.propose_block(fork);
-            fork = fork_instance;
-            POOL.sync_pool(&signed_block.block.txn_pool);
-            self.round_number = signed_block.block.id;
-            MessageSender::send_block_msg(sender, signed_block);
-
             let mut meta_data_locked = meta_data.lock().unwrap();
             meta_data_locked.active_node.clear();
             let msg: ElectionPing =
@@ -204,7 +257,6 @@ impl Consensus {
             info!("pinging for block number {}", self.round_number + 1);
             thread::sleep(Duration::from_micros(1000));
         }
-        patch_db(fork);
         let signed_new_leader: SignedLeaderElection = self.select_leader(meta_data);
         self.round_number = self.round_number + 1;
         let flag: bool = signed_new_leader.leader_payload.new_leader.clone()
@@ -266,7 +318,7 @@ impl Consensus {
                                                 let mut meta_data_locked =
                                                     meta_data.lock().unwrap();
                                                 if meta_data_locked
-                                                    .public_keys
+                                                    .validators(election_ping.payload.height)
                                                     .contains(&election_ping.payload.public_key)
                                                 {
                                                     if election_ping.verify() {
@@ -292,8 +344,10 @@ impl Consensus {
                                                     }
                                                 } else {
                                                     debug!(
-                                                        "public_keys {:?} key {:?}",
-                                                        meta_data_locked.public_keys,
+                                                        "validators {:?} key {:?}",
+                                                        meta_data_locked.validators(
+                                                            election_ping.payload.height
+                                                        ),
                                                         election_ping.payload.public_key
                                                     );
                                                     warn!("Election Ping data from malicious node");
@@ -307,9 +361,12 @@ impl Consensus {
                                                     meta_data_locked.kp.public().encode(),
                                                 ) == election_pong.payload.current_leader
                                                 {
-                                                    if meta_data_locked.public_keys.contains(
-                                                        &election_pong.payload.may_be_leader,
-                                                    ) {
+                                                    if meta_data_locked
+                                                        .validators(election_pong.payload.height)
+                                                        .contains(
+                                                            &election_pong.payload.may_be_leader,
+                                                        )
+                                                    {
                                                         if election_pong.verify() {
                                                             meta_data_locked.active_node.push(
                                                                 election_pong
@@ -368,7 +425,7 @@ impl Consensus {
             else you are the leader or other will be leader
             */
             {
-                let snapshot = snapshot_db();
+                let snapshot = self.db.snapshot();
                 let db_snapshot = SchemaSnap::new(&snapshot);
                 let current_block_chain_length = db_snapshot.get_blockchain_length();
                 let mut leader_map_locked = leader_map.lock().unwrap();
@@ -380,12 +437,12 @@ impl Consensus {
                     } else if key > current_block_chain_length {
                         // future leader
                         // by pass this for now and wait
-                        let fork = fork_db();
+                        let fork = self.db.fork();
                         {
                             let mut schema = SchemaFork::new(&fork);
                             schema.sync_state();
                         }
-                        patch_db(fork);
+                        self.db.patch(fork);
                     } else {
                         // current leader
                         let value = _value.clone();
@@ -409,52 +466,107 @@ impl Consensus {
         }
     }
 
-    pub fn init_poa_consensus(
+    /// this function will create poa engine as per consensus config file, it is registered
+    /// as "poa" engine
+    pub fn create_engine(
         config: &Configuration,
+        db: DbInstance,
         consensus_file_path: &str,
-        sender: &mut Sender<Option<MessageTypes>>,
-        msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
-    ) {
+        sender: Sender<Option<MessageTypes>>,
+    ) -> Box<dyn ConsensusEngine> {
         initialize_config(consensus_file_path);
         let consensus_configuration: &crate::poa_config::Configuration =
             &crate::poa_config::POA_CONFIG;
-        let mut consensus_obj = Consensus {
-            keypair: config.node.keypair.clone(),
-            pk: String::from(""),
-            round_number: 0,
-            public_keys: consensus_configuration.public_keys.clone(),
-            force_sealing: consensus_configuration.force_sealing,
-        };
-        consensus_obj.pk = hex::encode(consensus_obj.keypair.public().encode());
+        // genesis block starts with validators of consensus config as active set
+        match genesis_validator_state(consensus_configuration.public_keys.clone()) {
+            Ok(genesis_state) => set_genesis_states(&db, vec![genesis_state]),
+            Err(reason) => panic!("poa consensus: {}", reason),
+        }
         let leader_map_obj = LeaderMap {
             map: BTreeMap::new(),
         };
-        let leader_map = Arc::new(Mutex::new(leader_map_obj));
-
         let consensus_meta_data = MetaData {
             active_node: vec![],
-            public_keys: consensus_obj.public_keys.clone(),
             kp: config.node.keypair.clone(),
             sender: sender.clone(),
+            db: db.clone(),
         };
-        let meta_data = Arc::new(Mutex::new(consensus_meta_data));
-        Consensus::consensus_msg_receiver(leader_map.clone(), meta_data.clone(), msg_receiver);
+        Box::new(Consensus {
+            keypair: config.node.keypair.clone(),
+            pk: hex::encode(config.node.keypair.public().encode()),
+            round_number: 0,
+            force_sealing: consensus_configuration.force_sealing,
+            db,
+            sender,
+            leader_map: Arc::new(Mutex::new(leader_map_obj)),
+            meta_data: Arc::new(Mutex::new(consensus_meta_data)),
+        })
+    }
+}
+
+impl ConsensusEngine for Consensus {
+    fn init_state(&mut self, genesis_block: bool) {
+        // give peers time to connect before first leader election
         thread::sleep(Duration::from_millis(5000));
-        if config.node.genesis_block {
+        if genesis_block {
             {
-                let clone_leader_map = leader_map.clone();
-                let mut leader_map_locked = clone_leader_map.lock().unwrap();
-                leader_map_locked.map.insert(0, consensus_obj.pk.clone());
+                let mut leader_map_locked = self.leader_map.lock().unwrap();
+                leader_map_locked.map.insert(0, self.pk.clone());
             }
-            consensus_obj.init_state(&config.db.dbpath, leader_map.clone(), &mut sender.clone());
+            let mut sender = self.sender.clone();
+            self.init_genesis_state(self.leader_map.clone(), &mut sender);
         } else {
-            let fork = fork_db();
+            let fork = self.db.fork();
             {
                 let mut schema = SchemaFork::new(&fork);
                 schema.sync_state();
             }
-            patch_db(fork);
+            self.db.patch(fork);
         }
-        consensus_obj.state_machine(leader_map.clone(), meta_data.clone(), &mut sender.clone());
+    }
+
+    fn handle_messages(&mut self, msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        Consensus::consensus_msg_receiver(
+            self.leader_map.clone(),
+            self.meta_data.clone(),
+            msg_receiver,
+        );
+    }
+
+    fn propose_block(&mut self) -> Option<SignedBlock> {
+        let (_fork, signed_block) = self.compute_block();
+        Some(signed_block)
+    }
+
+    // block has to be signed by a validator of its height & extend local chain
+    fn verify_block(&self, signed_block: &SignedBlock) -> bool {
+        if !self
+            .meta_data
+            .lock()
+            .unwrap()
+            .signed_by_validator(signed_block)
+        {
+            return false;
+        }
+        let fork = self.db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        schema.update_block(signed_block)
+    }
+
+    // blocks are stored once the leader computed them, there are no forks to revert
+    fn finalized_height(&self) -> u64 {
+        stored_height(&self.db)
+    }
+
+    fn block_importer(&self) -> Option<Arc<dyn BlockImporter>> {
+        Some(Arc::new(PoaImporter {
+            db: self.db.clone(),
+            meta_data: self.meta_data.clone(),
+        }))
+    }
+
+    fn run(&mut self) {
+        let mut sender = self.sender.clone();
+        self.state_machine(self.leader_map.clone(), self.meta_data.clone(), &mut sender);
     }
 }
@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate lazy_static;
+
+#[macro_use]
+extern crate log;
+
+pub mod config;
+pub mod raft_interface;
+pub mod raft_message_sender;
+pub mod raft_messages;
+pub mod raft_state;
+pub mod raft_storage;
@@ -0,0 +1,513 @@
+extern crate db_service;
+extern crate message_handler;
+extern crate schema;
+extern crate utils;
+
+use super::config::initialize_config;
+use super::raft_message_sender::RaftMessageSender;
+use super::raft_messages::{LogEntry, RaftMessageTypes};
+use super::raft_state::{Action, RaftState, Role};
+use super::raft_storage::{HardState, RaftStorage};
+use consensus_engine::{stored_height, BlockImporter, ConsensusEngine};
+use db_service::db_fork_ref::SchemaFork;
+use db_service::db_layer::DbInstance;
+use db_service::db_snapshot_ref::SchemaSnap;
+use exonum_merkledb::{ObjectHash, Snapshot};
+use futures::{channel::mpsc::*, executor::*, future, prelude::*, task::*};
+use message_handler::messages::MessageTypes;
+use schema::block::SignedBlock;
+use schema::transaction_pool::{TxnPool, POOL};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use utils::configreader::Configuration;
+use utils::keypair::KeypairType;
+use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
+
+// interval (in millis) at which timers & block creation are checked
+const TICK_TIME: u64 = 50;
+
+#[derive(Clone)]
+pub struct Raft {
+    // peer identity
+    keypair: KeypairType,
+    // peers of config, log entries are signed by them
+    peers: Vec<String>,
+    // empty block creation
+    force_sealing: bool,
+    // genesis block time (in seconds)
+    start_time: u64,
+    // storage handle of the node
+    db: DbInstance,
+    // term, vote & log of the peer shared with message handling
+    raft_state: Arc<Mutex<RaftState>>,
+    // engine details shared with message handling
+    meta_data: Arc<Mutex<MetaData>>,
+}
+
+/// Raft engine details, they are locked after the raft state
+pub struct MetaData {
+    // storage handle of the node
+    db: DbInstance,
+    // data sender in P2P system
+    sender: Sender<Option<MessageTypes>>,
+    // hard state file of the peer
+    storage: RaftStorage,
+    // range of randomized election timeout (in millis)
+    election_timeout_min: u64,
+    election_timeout_max: u64,
+    // interval of leader's heartbeats (in millis)
+    heartbeat_interval: u64,
+    // an election starts if no leader or candidate was heard until deadline
+    election_deadline: Instant,
+    heartbeat_deadline: Instant,
+    // blocks committed by peers are missing, the chain has to be synced
+    lagging: bool,
+}
+
+// Raft consensus custom headers for the signed block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomHeaders {
+    timestamp: u64,
+    term: u64,
+}
+
+impl Raft {
+    // fn will pick next election deadline, timeouts are randomized so that peers rarely
+    // split their votes
+    fn reset_election_timer(meta_data: &mut MetaData) {
+        let nanos: u64 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos() as u64;
+        let range: u64 = meta_data
+            .election_timeout_max
+            .saturating_sub(meta_data.election_timeout_min)
+            + 1;
+        let timeout: u64 = meta_data.election_timeout_min + nanos % range;
+        meta_data.election_deadline = Instant::now() + Duration::from_millis(timeout);
+    }
+
+    // fn will return height & term of the last stored block
+    fn stored_root(db: &DbInstance) -> (u64, u64) {
+        let snapshot: Box<dyn Snapshot> = db.snapshot();
+        let schema = SchemaSnap::new(&snapshot);
+        match schema.get_root_block() {
+            Some(root_block) => {
+                let term: u64 = match deserialize::<CustomHeaders>(&root_block.block.custom_headers)
+                {
+                    Ok(custom_headers) => custom_headers.term,
+                    Err(_) => 0,
+                };
+                (root_block.block.id, term)
+            }
+            None => (0, 0),
+        }
+    }
+
+    // fn will carry out actions of the raft state, hard state is saved before any reply
+    fn handle_actions(actions: Vec<Action>, meta_data: &mut MetaData) {
+        for action in actions {
+            match action {
+                Action::Persist(hard_state) => {
+                    if let Err(error) = meta_data.storage.save(&hard_state) {
+                        // a vote or entry which may be forgotten can't be acknowledged
+                        error!("{}", error);
+                        panic!("{}", error);
+                    }
+                }
+                Action::Send(msg) => {
+                    RaftMessageSender::send_raft_msg(&mut meta_data.sender, msg);
+                }
+                Action::ResetElectionTimer => {
+                    Raft::reset_election_timer(meta_data);
+                }
+                Action::Commit(entries) => {
+                    for entry in entries.iter() {
+                        if !Raft::store_block(&meta_data.db, &entry.block) {
+                            meta_data.lagging = true;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // fn will store a committed block
+    fn store_block(db: &DbInstance, signed_block: &SignedBlock) -> bool {
+        if signed_block.block.id <= stored_height(db) {
+            return true;
+        }
+        let fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if !schema.update_block(signed_block) {
+                error!(
+                    "block with id {} & hash {} couldn't added in database",
+                    signed_block.block.id,
+                    signed_block.object_hash()
+                );
+                return false;
+            }
+        }
+        db.patch(fork);
+        POOL.sync_pool(&signed_block.block.txn_pool);
+        info!(
+            "block with id {} & hash {} committed",
+            signed_block.block.id,
+            signed_block.object_hash()
+        );
+        true
+    }
+
+    // fn will sync committed blocks from peers
+    fn sync_chain(db: &DbInstance) -> bool {
+        let fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if !schema.sync_state() {
+                return false;
+            }
+        }
+        db.patch(fork);
+        true
+    }
+
+    // fn will process incoming raft data
+    fn handle_raft_msg(
+        engine: &Raft,
+        msg: RaftMessageTypes,
+        raft_state: &mut RaftState,
+        meta_data: &mut MetaData,
+    ) {
+        let actions: Vec<Action> = match msg {
+            RaftMessageTypes::RequestVoteEnum(data) => {
+                if !data.verify() {
+                    warn!("malicious request vote from {:?}", data.public_key);
+                    return;
+                }
+                raft_state.on_request_vote(data)
+            }
+            RaftMessageTypes::VoteResponseEnum(data) => {
+                if !data.verify() {
+                    warn!("malicious vote response from {:?}", data.public_key);
+                    return;
+                }
+                raft_state.on_vote_response(data)
+            }
+            RaftMessageTypes::AppendEntriesEnum(data) => {
+                if !data.verify()
+                    || !data
+                        .payload
+                        .entries
+                        .iter()
+                        .all(|entry| engine.verify_block(&entry.block))
+                {
+                    warn!("malicious append entries from {:?}", data.public_key);
+                    return;
+                }
+                // blocks the leader has committed are synced from peers
+                if data.payload.prev_log_index > raft_state.last_log_index()
+                    && data.payload.leader_commit > raft_state.commit_index()
+                {
+                    meta_data.lagging = true;
+                }
+                raft_state.on_append_entries(data)
+            }
+            RaftMessageTypes::AppendResponseEnum(data) => {
+                if !data.verify() {
+                    warn!("malicious append response from {:?}", data.public_key);
+                    return;
+                }
+                raft_state.on_append_response(data)
+            }
+        };
+        Raft::handle_actions(actions, meta_data);
+    }
+
+    // fn will listen incoming data from other peers via P2P system
+    fn raft_msg_receiver(engine: Raft, rx: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        let raft_state: Arc<Mutex<RaftState>> = engine.raft_state.clone();
+        let meta_data: Arc<Mutex<MetaData>> = engine.meta_data.clone();
+        thread::spawn(move || {
+            block_on(future::poll_fn(move |cx: &mut Context| {
+                loop {
+                    match rx.lock().unwrap().poll_next_unpin(cx) {
+                        Poll::Ready(Some(msg)) => match msg {
+                            None => info!("Empty msg received !"),
+                            Some(msgtype) => {
+                                if let Ok(msgtype) =
+                                    deserialize::<RaftMessageTypes>(msgtype.as_slice())
+                                {
+                                    let mut raft_state_obj = raft_state.lock().unwrap();
+                                    let mut meta_data_obj = meta_data.lock().unwrap();
+                                    Raft::handle_raft_msg(
+                                        &engine,
+                                        msgtype,
+                                        &mut raft_state_obj,
+                                        &mut meta_data_obj,
+                                    );
+                                }
+                            }
+                        },
+                        Poll::Ready(None) => {
+                            info!("channel closed !");
+                            return Poll::Ready(1);
+                        }
+                        Poll::Pending => break,
+                    }
+                }
+                Poll::Pending
+            }));
+        });
+    }
+
+    // fn will create new block of given term on top of the log, entries after the last
+    // stored block are applied first
+    fn compute_block(&self, term: u64, entries: &Vec<LogEntry>) -> Option<SignedBlock> {
+        let custom_headers: CustomHeaders = CustomHeaders {
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            term,
+        };
+        let custom_headers: Vec<u8> = match serialize(&custom_headers) {
+            Ok(value) => value,
+            Err(_) => Vec::new(),
+        };
+        let fork = self.db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        if entries.is_empty() {
+            if self.force_sealing {
+                return Some(schema.create_block(&self.keypair, custom_headers));
+            }
+            let (_fork_instance, signed_block) =
+                schema.forge_new_block(&self.db, &self.keypair, custom_headers);
+            return Some(signed_block);
+        }
+        // txns of uncommitted entries are in the txn trie of the fork, they aren't executed twice
+        for entry in entries.iter() {
+            if !schema.update_block(&entry.block) {
+                warn!(
+                    "log entry {} couldn't applied on top of local chain",
+                    entry.index()
+                );
+                return None;
+            }
+        }
+        Some(schema.create_block(&self.keypair, custom_headers))
+    }
+
+    // fn will append a new block to the log of the leader
+    fn append_block(&mut self, term: u64) {
+        // block is built in the ongoing term, it is dropped below if the term moved
+        let signed_block: SignedBlock = match self.propose_block() {
+            Some(block) => block,
+            None => return,
+        };
+        let mut raft_state_obj = self.raft_state.lock().unwrap();
+        let mut meta_data_obj = self.meta_data.lock().unwrap();
+        if raft_state_obj.role() != Role::Leader || raft_state_obj.current_term() != term {
+            return;
+        }
+        info!(
+            "block with id {} & hash {} appended in term {}",
+            signed_block.block.id,
+            signed_block.object_hash(),
+            term
+        );
+        let actions: Vec<Action> = raft_state_obj.append_block(signed_block);
+        Raft::handle_actions(actions, &mut meta_data_obj);
+    }
+
+    // fn will fire due timers, sync lagging chain and append blocks as leader
+    fn state_machine(&mut self) {
+        {
+            let mut raft_state_obj = self.raft_state.lock().unwrap();
+            let mut meta_data_obj = self.meta_data.lock().unwrap();
+            let (commit_index, commit_term) = Raft::stored_root(&self.db);
+            let actions: Vec<Action> = raft_state_obj.on_synced(commit_index, commit_term);
+            Raft::handle_actions(actions, &mut meta_data_obj);
+            Raft::reset_election_timer(&mut meta_data_obj);
+        }
+        loop {
+            let pending_term: Option<u64> = {
+                let mut raft_state_obj = self.raft_state.lock().unwrap();
+                let mut meta_data_obj = self.meta_data.lock().unwrap();
+                if meta_data_obj.lagging {
+                    meta_data_obj.lagging = false;
+                    Raft::sync_chain(&self.db);
+                    let (commit_index, commit_term) = Raft::stored_root(&self.db);
+                    if commit_index > raft_state_obj.commit_index() {
+                        info!("synced up to height {}", commit_index);
+                    }
+                    let actions: Vec<Action> = raft_state_obj.on_synced(commit_index, commit_term);
+                    Raft::handle_actions(actions, &mut meta_data_obj);
+                }
+                let now: Instant = Instant::now();
+                if raft_state_obj.role() != Role::Leader && now >= meta_data_obj.election_deadline {
+                    if raft_state_obj.is_member() {
+                        let actions: Vec<Action> = raft_state_obj.on_election_timeout();
+                        Raft::handle_actions(actions, &mut meta_data_obj);
+                    } else {
+                        // non-member follows the chain committed by peers
+                        meta_data_obj.lagging = true;
+                        Raft::reset_election_timer(&mut meta_data_obj);
+                    }
+                }
+                if raft_state_obj.role() == Role::Leader && now >= meta_data_obj.heartbeat_deadline
+                {
+                    let actions: Vec<Action> = raft_state_obj.on_heartbeat();
+                    Raft::handle_actions(actions, &mut meta_data_obj);
+                    meta_data_obj.heartbeat_deadline =
+                        now + Duration::from_millis(meta_data_obj.heartbeat_interval);
+                }
+                if raft_state_obj.needs_block() {
+                    Some(raft_state_obj.current_term())
+                } else {
+                    None
+                }
+            };
+            // block creation may take block_creation_time_limit, locks aren't held meanwhile
+            if let Some(term) = pending_term {
+                self.append_block(term);
+            }
+            thread::sleep(Duration::from_millis(TICK_TIME));
+        }
+    }
+
+    /// this function will create raft engine as per consensus config file, it is registered
+    /// as "raft" engine
+    pub fn create_engine(
+        config: &Configuration,
+        db: DbInstance,
+        consensus_file_path: &str,
+        sender: Sender<Option<MessageTypes>>,
+    ) -> Box<dyn ConsensusEngine> {
+        initialize_config(consensus_file_path);
+        let raft_config: &crate::config::Configuration = &crate::config::RAFT_CONFIG;
+        if raft_config.peers.is_empty() {
+            panic!("raft consensus needs at least one peer");
+        }
+        let storage: RaftStorage = RaftStorage::new(&raft_config.state_file);
+        let hard_state: HardState = match storage.load() {
+            Ok(hard_state) => hard_state,
+            Err(error) => {
+                error!("{}", error);
+                panic!("{}", error);
+            }
+        };
+        let (commit_index, commit_term) = Raft::stored_root(&db);
+        let raft_state: RaftState = RaftState::new(
+            config.node.keypair.clone(),
+            raft_config.peers.clone(),
+            hard_state,
+            commit_index,
+            commit_term,
+        );
+        if !raft_state.is_member() {
+            info!("node isn't a raft peer, it will follow committed blocks");
+        }
+        info!(
+            "raft state restored at term {} with {} uncommitted entries",
+            raft_state.current_term(),
+            raft_state.entries().len()
+        );
+        let meta_data: MetaData = MetaData {
+            db: db.clone(),
+            sender,
+            storage,
+            election_timeout_min: raft_config.election_timeout_min,
+            election_timeout_max: raft_config.election_timeout_max,
+            heartbeat_interval: raft_config.heartbeat_interval,
+            election_deadline: Instant::now(),
+            heartbeat_deadline: Instant::now(),
+            lagging: false,
+        };
+        Box::new(Raft {
+            keypair: config.node.keypair.clone(),
+            peers: raft_config.peers.clone(),
+            force_sealing: raft_config.force_sealing,
+            start_time: raft_config.start_time,
+            db,
+            raft_state: Arc::new(Mutex::new(raft_state)),
+            meta_data: Arc::new(Mutex::new(meta_data)),
+        })
+    }
+}
+
+impl ConsensusEngine for Raft {
+    // init_state will create genesis block if predefined storage is empty
+    // or if storage is not empty it will sync committed blocks from peers
+    fn init_state(&mut self, genesis_block: bool) {
+        if !genesis_block {
+            Raft::sync_chain(&self.db);
+            return;
+        }
+        let fork = self.db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            if schema.blockchain_length() == 0 {
+                let custom_headers: CustomHeaders = CustomHeaders {
+                    timestamp: self.start_time,
+                    term: 0,
+                };
+                let custom_headers: Vec<u8> = match serialize(&custom_headers) {
+                    Ok(value) => value,
+                    Err(_) => Vec::new(),
+                };
+                let genesis_signed_block =
+                    schema.initialize_db(custom_headers, self.start_time as u128);
+                info!(
+                    "genesis block created with hash {:?}",
+                    genesis_signed_block.get_hash()
+                );
+            } else {
+                info!(
+                    "started from previous state {} {}",
+                    schema.blockchain_length(),
+                    schema.state_trie_merkle_hash()
+                )
+            }
+        }
+        self.db.patch(fork);
+    }
+
+    fn handle_messages(&mut self, msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>) {
+        Raft::raft_msg_receiver(self.clone(), msg_receiver);
+    }
+
+    fn propose_block(&mut self) -> Option<SignedBlock> {
+        let (term, entries) = {
+            let raft_state_obj = self.raft_state.lock().unwrap();
+            (
+                raft_state_obj.current_term(),
+                raft_state_obj.entries().clone(),
+            )
+        };
+        self.compute_block(term, &entries)
+    }
+
+    // log entries must be blocks signed by peers, an entry may extend uncommitted ones so
+    // its state is checked once it commits. It doesn't lock raft state, message handling
+    // calls it while holding it
+    fn verify_block(&self, signed_block: &SignedBlock) -> bool {
+        self.peers.contains(&signed_block.block.peer_id) && signed_block.validate()
+    }
+
+    // blocks are stored once a majority of peers appended them, they are final
+    fn finalized_height(&self) -> u64 {
+        stored_height(&self.db)
+    }
+
+    // blocks are appended through raft messages only, gossiped blocks are dropped
+    fn block_importer(&self) -> Option<Arc<dyn BlockImporter>> {
+        None
+    }
+
+    fn run(&mut self) {
+        self.state_machine();
+    }
+}
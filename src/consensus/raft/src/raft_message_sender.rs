@@ -0,0 +1,20 @@
+use crate::raft_messages::RaftMessageTypes;
+use futures::channel::mpsc::*;
+use message_handler::messages::MessageTypes;
+use utils::serializer::serialize;
+
+pub struct RaftMessageSender {}
+
+impl RaftMessageSender {
+    pub fn send_raft_msg(sender: &mut Sender<Option<MessageTypes>>, msg: RaftMessageTypes) {
+        if let Ok(serialize_msg) = serialize(&msg) {
+            let data = Some(MessageTypes::ConsensusMsg(serialize_msg));
+            let error: Result<(), TrySendError<Option<MessageTypes>>> = sender.try_send(data);
+            if error.is_err() {
+                error!("{:?}", error);
+            } else {
+                info!("msg send send_raft_msg");
+            }
+        }
+    }
+}
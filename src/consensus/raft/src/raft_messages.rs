@@ -0,0 +1,174 @@
+use libp2p::floodsub::Topic;
+use message_handler::constants;
+use message_handler::message_traits::Message;
+use schema::block::SignedBlock;
+use utils::keypair::{CryptoKeypair, Keypair, KeypairType, PublicKey, Verify};
+use utils::serializer::{serialize, Deserialize, Serialize};
+
+pub const RAFT_MSG_TOPIC_STR: &'static [&'static str] = &[
+    "RequestVote",
+    "VoteResponse",
+    "AppendEntries",
+    "AppendResponse",
+];
+
+/// LogEntry is a block with the term of the leader which appended it, its log index is the
+/// block height
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LogEntry {
+    pub term: u64,
+    pub block: SignedBlock,
+}
+
+impl LogEntry {
+    pub fn index(&self) -> u64 {
+        self.block.block.id
+    }
+}
+
+/// candidate asks for votes of peers, the candidate is the signer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RequestVote {
+    pub term: u64,
+    pub last_log_index: u64,
+    pub last_log_term: u64,
+}
+
+/// answer to RequestVote, the voter is the signer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VoteResponse {
+    pub term: u64,
+    pub candidate: String,
+    pub granted: bool,
+}
+
+/// leader replicates its log to a follower, empty entries are a heartbeat. The leader is the
+/// signer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AppendEntries {
+    pub term: u64,
+    pub follower: String,
+    pub prev_log_index: u64,
+    pub prev_log_term: u64,
+    pub entries: Vec<LogEntry>,
+    pub leader_commit: u64,
+}
+
+/// answer to AppendEntries, match_index is the last index known to match the leader's log
+/// on success, a hint where the logs may match otherwise. The follower is the signer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AppendResponse {
+    pub term: u64,
+    pub leader: String,
+    pub success: bool,
+    pub match_index: u64,
+}
+
+/// Signed wraps every raft message, peers are identified by the key which signed it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Signed<T> {
+    pub payload: T,
+    pub signature: Vec<u8>,
+    pub public_key: String,
+}
+
+impl<T: Serialize> Signed<T> {
+    pub fn verify(&self) -> bool {
+        let ser_payload: Vec<u8> = match serialize(&self.payload) {
+            Result::Ok(value) => value,
+            Result::Err(_) => return false,
+        };
+        PublicKey::verify_from_encoded_pk(&self.public_key, &ser_payload, &self.signature.as_ref())
+    }
+
+    pub fn create(kp: &KeypairType, payload: T) -> Signed<T> {
+        let ser_payload: Vec<u8> = match serialize(&payload) {
+            Result::Ok(value) => value,
+            Result::Err(_) => vec![0],
+        };
+        Signed {
+            signature: Keypair::sign(&kp, &ser_payload),
+            payload,
+            public_key: hex::encode(kp.public().encode()),
+        }
+    }
+}
+
+impl Message for Signed<RequestVote> {
+    const TOPIC: &'static str = RAFT_MSG_TOPIC_STR[0];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am RequestVote handler");
+    }
+}
+
+impl Message for Signed<VoteResponse> {
+    const TOPIC: &'static str = RAFT_MSG_TOPIC_STR[1];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am VoteResponse handler");
+    }
+}
+
+impl Message for Signed<AppendEntries> {
+    const TOPIC: &'static str = RAFT_MSG_TOPIC_STR[2];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am AppendEntries handler");
+    }
+}
+
+impl Message for Signed<AppendResponse> {
+    const TOPIC: &'static str = RAFT_MSG_TOPIC_STR[3];
+    const MODULE_TOPIC: &'static str = constants::CONSENSUS;
+    fn handler(&self) {
+        info!("i am AppendResponse handler");
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum RaftMessageTypes {
+    RequestVoteEnum(Signed<RequestVote>),
+    VoteResponseEnum(Signed<VoteResponse>),
+    AppendEntriesEnum(Signed<AppendEntries>),
+    AppendResponseEnum(Signed<AppendResponse>),
+}
+
+impl From<RaftMessageTypes> for Topic {
+    fn from(msg: RaftMessageTypes) -> Topic {
+        match msg {
+            RaftMessageTypes::RequestVoteEnum(data) => Topic::new(data.topic()),
+            RaftMessageTypes::VoteResponseEnum(data) => Topic::new(data.topic()),
+            RaftMessageTypes::AppendEntriesEnum(data) => Topic::new(data.topic()),
+            RaftMessageTypes::AppendResponseEnum(data) => Topic::new(data.topic()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod consensus_message_test {
+
+    #[test]
+    pub fn check_raft_messages_verification_process() {
+        use super::*;
+
+        let kp: KeypairType = Keypair::generate();
+        let request_vote: Signed<RequestVote> = Signed::create(
+            &kp,
+            RequestVote {
+                term: 4,
+                last_log_index: 10,
+                last_log_term: 3,
+            },
+        );
+        assert_eq!(request_vote.verify(), true);
+        assert_eq!(request_vote.public_key, hex::encode(kp.public().encode()));
+
+        let mut forged: Signed<RequestVote> = request_vote.clone();
+        forged.payload.term = 5;
+        assert_eq!(forged.verify(), false);
+        let mut forged: Signed<RequestVote> = request_vote.clone();
+        forged.public_key = hex::encode(Keypair::generate().public().encode());
+        assert_eq!(forged.verify(), false);
+    }
+}
@@ -0,0 +1,709 @@
+use crate::raft_messages::{
+    AppendEntries, AppendResponse, LogEntry, RaftMessageTypes, RequestVote, Signed, VoteResponse,
+};
+use crate::raft_storage::HardState;
+use schema::block::SignedBlock;
+use std::collections::{HashMap, HashSet};
+use utils::keypair::KeypairType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// Action is what the engine has to carry out on behalf of the raft state, in given order
+#[derive(Debug)]
+pub enum Action {
+    /// hard state changed, it has to be saved before anything else is sent
+    Persist(HardState),
+    /// message has to be sent to peers
+    Send(RaftMessageTypes),
+    /// a leader or candidate is alive, election has to wait a new timeout
+    ResetElectionTimer,
+    /// entries are committed, their blocks have to be stored in given order
+    Commit(Vec<LogEntry>),
+}
+
+/// RaftState replicates blocks from the leader of a term to its followers. Blocks up to the
+/// commit index are stored in db & can't change, the log holds the ones after it. An entry is
+/// committed once a majority stored it, and only peers holding all committed entries can win
+/// an election, so a committed block survives crash of any minority.
+pub struct RaftState {
+    // peer identity
+    keypair: KeypairType,
+    // peer public key
+    public_key: String,
+    // public keys of voting members, the peer included
+    peers: Vec<String>,
+    current_term: u64,
+    voted_for: Option<String>,
+    // entries after the commit index
+    entries: Vec<LogEntry>,
+    role: Role,
+    leader: Option<String>,
+    // height of the last block stored in db and term it was appended in
+    commit_index: u64,
+    commit_term: u64,
+    // peers which voted for the candidate in current term
+    votes: HashSet<String>,
+    // leader's view of each follower, next index to send and last index known to match
+    next_index: HashMap<String, u64>,
+    match_index: HashMap<String, u64>,
+}
+
+impl RaftState {
+    /// state restarts as follower from saved hard state on top of the blocks stored in db
+    pub fn new(
+        keypair: KeypairType,
+        peers: Vec<String>,
+        hard_state: HardState,
+        commit_index: u64,
+        commit_term: u64,
+    ) -> RaftState {
+        RaftState {
+            public_key: hex::encode(keypair.public().encode()),
+            keypair,
+            peers,
+            current_term: hard_state.current_term,
+            voted_for: hard_state.voted_for,
+            entries: hard_state
+                .entries
+                .into_iter()
+                .filter(|entry| entry.index() > commit_index)
+                .collect(),
+            role: Role::Follower,
+            leader: None,
+            commit_index,
+            commit_term,
+            votes: HashSet::new(),
+            next_index: HashMap::new(),
+            match_index: HashMap::new(),
+        }
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn current_term(&self) -> u64 {
+        self.current_term
+    }
+
+    pub fn leader(&self) -> Option<&String> {
+        self.leader.as_ref()
+    }
+
+    pub fn commit_index(&self) -> u64 {
+        self.commit_index
+    }
+
+    pub fn entries(&self) -> &Vec<LogEntry> {
+        &self.entries
+    }
+
+    pub fn peers(&self) -> &Vec<String> {
+        &self.peers
+    }
+
+    pub fn is_member(&self) -> bool {
+        self.peers.contains(&self.public_key)
+    }
+
+    pub fn hard_state(&self) -> HardState {
+        HardState {
+            current_term: self.current_term,
+            voted_for: self.voted_for.clone(),
+            entries: self.entries.clone(),
+        }
+    }
+
+    pub fn last_log_index(&self) -> u64 {
+        match self.entries.last() {
+            Some(entry) => entry.index(),
+            None => self.commit_index,
+        }
+    }
+
+    pub fn last_log_term(&self) -> u64 {
+        match self.entries.last() {
+            Some(entry) => entry.term,
+            None => self.commit_term,
+        }
+    }
+
+    /// leader needs a block of its own term, entries of earlier terms commit only with it
+    pub fn needs_block(&self) -> bool {
+        self.role == Role::Leader
+            && !self
+                .entries
+                .iter()
+                .any(|entry| entry.term == self.current_term)
+    }
+
+    /// blocks of stored chain were synced from peers
+    pub fn on_synced(&mut self, commit_index: u64, commit_term: u64) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if commit_index <= self.commit_index {
+            return actions;
+        }
+        self.commit_index = commit_index;
+        self.commit_term = commit_term;
+        self.entries.retain(|entry| entry.index() > commit_index);
+        actions.push(Action::Persist(self.hard_state()));
+        actions
+    }
+
+    /// this function will start an election for the next term
+    pub fn on_election_timeout(&mut self) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if self.role == Role::Leader || !self.is_member() {
+            return actions;
+        }
+        self.current_term += 1;
+        self.role = Role::Candidate;
+        self.leader = None;
+        self.voted_for = Some(self.public_key.clone());
+        self.votes.clear();
+        self.votes.insert(self.public_key.clone());
+        info!(
+            "election started for term {} with log at {}",
+            self.current_term,
+            self.last_log_index()
+        );
+        actions.push(Action::Persist(self.hard_state()));
+        actions.push(Action::ResetElectionTimer);
+        let request_vote = RequestVote {
+            term: self.current_term,
+            last_log_index: self.last_log_index(),
+            last_log_term: self.last_log_term(),
+        };
+        actions.push(Action::Send(RaftMessageTypes::RequestVoteEnum(
+            Signed::create(&self.keypair, request_vote),
+        )));
+        // a single peer is majority by itself
+        self.count_votes(&mut actions);
+        actions
+    }
+
+    /// leader's heartbeat, followers also get the entries they miss
+    pub fn on_heartbeat(&mut self) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if self.role == Role::Leader {
+            self.replicate(&mut actions);
+        }
+        actions
+    }
+
+    /// this function will append the block to the log of the leader, it has to be built on
+    /// top of the last log entry
+    pub fn append_block(&mut self, block: SignedBlock) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if self.role != Role::Leader || block.block.id != self.last_log_index() + 1 {
+            return actions;
+        }
+        self.entries.push(LogEntry {
+            term: self.current_term,
+            block,
+        });
+        actions.push(Action::Persist(self.hard_state()));
+        self.replicate(&mut actions);
+        self.advance_commit(&mut actions);
+        actions
+    }
+
+    /// message signature has to be verified by the caller
+    pub fn on_request_vote(&mut self, message: Signed<RequestVote>) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        let candidate: String = message.public_key;
+        let request: RequestVote = message.payload;
+        if !self.peers.contains(&candidate) || !self.is_member() {
+            return actions;
+        }
+        if request.term > self.current_term {
+            self.step_down(request.term, &mut actions);
+        }
+        // candidate's log must hold everything this peer has
+        let up_to_date: bool = request.last_log_term > self.last_log_term()
+            || (request.last_log_term == self.last_log_term()
+                && request.last_log_index >= self.last_log_index());
+        let granted: bool = request.term == self.current_term
+            && up_to_date
+            && match &self.voted_for {
+                None => true,
+                Some(voted_for) => voted_for == &candidate,
+            };
+        if granted {
+            if self.voted_for.is_none() {
+                self.voted_for = Some(candidate.clone());
+                actions.push(Action::Persist(self.hard_state()));
+            }
+            actions.push(Action::ResetElectionTimer);
+        }
+        let response = VoteResponse {
+            term: self.current_term,
+            candidate,
+            granted,
+        };
+        actions.push(Action::Send(RaftMessageTypes::VoteResponseEnum(
+            Signed::create(&self.keypair, response),
+        )));
+        actions
+    }
+
+    pub fn on_vote_response(&mut self, message: Signed<VoteResponse>) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        let voter: String = message.public_key;
+        let response: VoteResponse = message.payload;
+        if !self.peers.contains(&voter) {
+            return actions;
+        }
+        if response.term > self.current_term {
+            self.step_down(response.term, &mut actions);
+            return actions;
+        }
+        if self.role != Role::Candidate
+            || response.term != self.current_term
+            || response.candidate != self.public_key
+            || !response.granted
+        {
+            return actions;
+        }
+        self.votes.insert(voter);
+        self.count_votes(&mut actions);
+        actions
+    }
+
+    pub fn on_append_entries(&mut self, message: Signed<AppendEntries>) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        let leader: String = message.public_key;
+        let request: AppendEntries = message.payload;
+        if request.follower != self.public_key || !self.peers.contains(&leader) {
+            return actions;
+        }
+        if request.term < self.current_term {
+            self.respond(&leader, false, self.last_log_index(), &mut actions);
+            return actions;
+        }
+        if request.term > self.current_term || self.role != Role::Follower {
+            self.step_down(request.term, &mut actions);
+        }
+        self.leader = Some(leader.clone());
+        actions.push(Action::ResetElectionTimer);
+
+        // logs have to match at prev_log_index, committed blocks match anyway
+        if request.prev_log_index > self.last_log_index() {
+            self.respond(&leader, false, self.last_log_index(), &mut actions);
+            return actions;
+        }
+        if request.prev_log_index > self.commit_index
+            && self.term_at(request.prev_log_index) != Some(request.prev_log_term)
+        {
+            self.respond(&leader, false, request.prev_log_index - 1, &mut actions);
+            return actions;
+        }
+        let mut log_changed: bool = false;
+        let mut last_new_index: u64 = request.prev_log_index;
+        for entry in request.entries {
+            let index: u64 = entry.index();
+            last_new_index = index;
+            if index <= self.commit_index {
+                continue;
+            }
+            let position: usize = (index - self.commit_index - 1) as usize;
+            match self.entries.get(position) {
+                Some(existing) if existing.term == entry.term => continue,
+                Some(_) => {
+                    // conflicting entries of an earlier leader are dropped
+                    self.entries.truncate(position);
+                    self.entries.push(entry);
+                }
+                None => self.entries.push(entry),
+            }
+            log_changed = true;
+        }
+        if log_changed {
+            actions.push(Action::Persist(self.hard_state()));
+        }
+        let commit_to: u64 = request.leader_commit.min(last_new_index);
+        self.commit(commit_to, &mut actions);
+        let match_index: u64 = last_new_index.max(self.commit_index);
+        self.respond(&leader, true, match_index, &mut actions);
+        actions
+    }
+
+    pub fn on_append_response(&mut self, message: Signed<AppendResponse>) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        let follower: String = message.public_key;
+        let response: AppendResponse = message.payload;
+        if response.leader != self.public_key || !self.peers.contains(&follower) {
+            return actions;
+        }
+        if response.term > self.current_term {
+            self.step_down(response.term, &mut actions);
+            return actions;
+        }
+        if self.role != Role::Leader || response.term != self.current_term {
+            return actions;
+        }
+        if response.success {
+            let match_index: u64 = self.match_index.get(&follower).cloned().unwrap_or(0);
+            if response.match_index > match_index {
+                self.match_index
+                    .insert(follower.clone(), response.match_index);
+            }
+            self.next_index
+                .insert(follower, response.match_index.max(match_index) + 1);
+            self.advance_commit(&mut actions);
+        } else {
+            // retry right away from where the follower's log may match
+            let next_index: u64 = self.next_index.get(&follower).cloned().unwrap_or(1);
+            let next_index: u64 = next_index.saturating_sub(1).min(response.match_index + 1);
+            self.next_index.insert(follower.clone(), next_index.max(1));
+            self.send_entries(&follower, &mut actions);
+        }
+        actions
+    }
+
+    fn majority(&self) -> usize {
+        self.peers.len() / 2 + 1
+    }
+
+    fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.commit_index {
+            return Some(self.commit_term);
+        }
+        if index < self.commit_index {
+            return None;
+        }
+        self.entries
+            .get((index - self.commit_index - 1) as usize)
+            .map(|entry| entry.term)
+    }
+
+    // fn will turn the peer into follower of given term, vote is reset for a new term
+    fn step_down(&mut self, term: u64, actions: &mut Vec<Action>) {
+        if term > self.current_term {
+            self.current_term = term;
+            self.voted_for = None;
+            self.leader = None;
+            actions.push(Action::Persist(self.hard_state()));
+        }
+        if self.role != Role::Follower {
+            info!("stepped down to follower in term {}", self.current_term);
+            self.role = Role::Follower;
+        }
+    }
+
+    fn count_votes(&mut self, actions: &mut Vec<Action>) {
+        if self.role != Role::Candidate || self.votes.len() < self.majority() {
+            return;
+        }
+        info!(
+            "elected as leader of term {} by {:?}",
+            self.current_term, self.votes
+        );
+        self.role = Role::Leader;
+        self.leader = Some(self.public_key.clone());
+        self.next_index.clear();
+        self.match_index.clear();
+        for peer in self.peers.iter() {
+            if peer != &self.public_key {
+                self.next_index
+                    .insert(peer.clone(), self.last_log_index() + 1);
+            }
+        }
+        self.replicate(actions);
+    }
+
+    fn replicate(&mut self, actions: &mut Vec<Action>) {
+        let followers: Vec<String> = self.next_index.keys().cloned().collect();
+        for follower in followers.iter() {
+            self.send_entries(follower, actions);
+        }
+    }
+
+    // fn will send the follower entries from its next index, blocks stored in db are synced
+    // by followers from peers
+    fn send_entries(&self, follower: &String, actions: &mut Vec<Action>) {
+        let next_index: u64 = self
+            .next_index
+            .get(follower)
+            .cloned()
+            .unwrap_or(self.last_log_index() + 1)
+            .max(self.commit_index + 1);
+        let prev_log_index: u64 = next_index - 1;
+        let request = AppendEntries {
+            term: self.current_term,
+            follower: follower.clone(),
+            prev_log_index,
+            prev_log_term: self.term_at(prev_log_index).unwrap_or(0),
+            entries: self
+                .entries
+                .iter()
+                .filter(|entry| entry.index() >= next_index)
+                .cloned()
+                .collect(),
+            leader_commit: self.commit_index,
+        };
+        actions.push(Action::Send(RaftMessageTypes::AppendEntriesEnum(
+            Signed::create(&self.keypair, request),
+        )));
+    }
+
+    fn respond(&self, leader: &String, success: bool, match_index: u64, actions: &mut Vec<Action>) {
+        let response = AppendResponse {
+            term: self.current_term,
+            leader: leader.clone(),
+            success,
+            match_index,
+        };
+        actions.push(Action::Send(RaftMessageTypes::AppendResponseEnum(
+            Signed::create(&self.keypair, response),
+        )));
+    }
+
+    // leader commits the latest entry of its term stored by a majority, with all before it
+    fn advance_commit(&mut self, actions: &mut Vec<Action>) {
+        let mut commit_to: u64 = self.commit_index;
+        for entry in self.entries.iter().rev() {
+            if entry.term != self.current_term {
+                break;
+            }
+            let replicas: usize = 1 + self
+                .match_index
+                .values()
+                .filter(|match_index| **match_index >= entry.index())
+                .count();
+            if replicas >= self.majority() {
+                commit_to = entry.index();
+                break;
+            }
+        }
+        self.commit(commit_to, actions);
+    }
+
+    fn commit(&mut self, commit_to: u64, actions: &mut Vec<Action>) {
+        if commit_to <= self.commit_index {
+            return;
+        }
+        let count: usize = (commit_to - self.commit_index) as usize;
+        let committed: Vec<LogEntry> = self.entries.drain(..count).collect();
+        if let Some(entry) = committed.last() {
+            self.commit_index = entry.index();
+            self.commit_term = entry.term;
+        }
+        actions.push(Action::Commit(committed));
+        actions.push(Action::Persist(self.hard_state()));
+    }
+}
+
+#[cfg(test)]
+mod test_raft_state {
+    use super::*;
+    use exonum_crypto::Hash;
+    use schema::block::{Block, BlockTraits};
+    use utils::keypair::{CryptoKeypair, Keypair};
+
+    fn peers(count: usize) -> (Vec<KeypairType>, Vec<String>) {
+        let kps: Vec<KeypairType> = (0..count).map(|_| Keypair::generate()).collect();
+        let peers: Vec<String> = kps
+            .iter()
+            .map(|kp| hex::encode(kp.public().encode()))
+            .collect();
+        (kps, peers)
+    }
+
+    fn block(kp: &KeypairType, height: u64) -> SignedBlock {
+        let block: Block = Block::new_block(
+            height,
+            hex::encode(kp.public().encode()),
+            Hash::zero(),
+            Vec::new(),
+            [Hash::zero(), Hash::zero(), Hash::zero(), Hash::zero()],
+            Vec::new(),
+        );
+        let sign: Vec<u8> = block.sign(kp);
+        SignedBlock::create_block(block, sign, Vec::new())
+    }
+
+    fn sent(actions: Vec<Action>) -> Vec<RaftMessageTypes> {
+        actions
+            .into_iter()
+            .filter_map(|action| match action {
+                Action::Send(message) => Some(message),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn committed(actions: &[Action]) -> Vec<u64> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::Commit(entries) => Some(entries.iter().map(|entry| entry.index())),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    // fn will deliver messages to the state, its answers are returned
+    fn deliver(state: &mut RaftState, messages: Vec<RaftMessageTypes>) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        for message in messages {
+            actions.extend(match message {
+                RaftMessageTypes::RequestVoteEnum(data) => state.on_request_vote(data),
+                RaftMessageTypes::VoteResponseEnum(data) => state.on_vote_response(data),
+                RaftMessageTypes::AppendEntriesEnum(data) => state.on_append_entries(data),
+                RaftMessageTypes::AppendResponseEnum(data) => state.on_append_response(data),
+            });
+        }
+        actions
+    }
+
+    #[test]
+    fn test_raft_election_and_replication() {
+        let (kps, peers) = peers(3);
+        let mut states: Vec<RaftState> = kps
+            .iter()
+            .map(|kp| RaftState::new(kp.clone(), peers.clone(), HardState::default(), 0, 0))
+            .collect();
+
+        let request_vote = sent(states[0].on_election_timeout());
+        assert_eq!(states[0].role(), Role::Candidate);
+        assert_eq!(states[0].current_term(), 1);
+        let vote = sent(deliver(&mut states[1], request_vote.clone()));
+        // peer 1 voted in term 1, it won't vote for another candidate
+        let other_request = sent(states[2].on_election_timeout());
+        assert_eq!(states[2].current_term(), 1);
+        let refusal = sent(deliver(&mut states[1], other_request));
+        match &refusal[0] {
+            RaftMessageTypes::VoteResponseEnum(data) => assert_eq!(data.payload.granted, false),
+            _ => panic!("vote response expected"),
+        }
+        let heartbeats = sent(deliver(&mut states[0], vote));
+        assert_eq!(states[0].role(), Role::Leader);
+        assert_eq!(heartbeats.len(), 2);
+        assert!(states[0].needs_block());
+
+        // leader's block is committed once one follower stored it
+        let actions: Vec<Action> = states[0].append_block(block(&kps[0], 1));
+        assert!(committed(&actions).is_empty());
+        assert!(!states[0].needs_block());
+        let append_entries = sent(actions);
+        let actions: Vec<Action> = deliver(&mut states[1], append_entries.clone());
+        assert!(committed(&actions).is_empty());
+        assert_eq!(states[1].leader(), Some(&peers[0]));
+        assert_eq!(states[1].entries().len(), 1);
+        let actions: Vec<Action> = deliver(&mut states[0], sent(actions));
+        assert_eq!(committed(&actions), vec![1]);
+        assert_eq!(states[0].commit_index(), 1);
+        assert!(states[0].needs_block());
+
+        // follower commits with the next heartbeat
+        let heartbeats = sent(states[0].on_heartbeat());
+        let actions: Vec<Action> = deliver(&mut states[1], heartbeats.clone());
+        assert_eq!(committed(&actions), vec![1]);
+        assert_eq!(states[1].commit_index(), 1);
+        // candidate of term 1 steps down, the committed block it misses is synced from peers
+        let actions: Vec<Action> = deliver(&mut states[2], heartbeats);
+        assert_eq!(states[2].role(), Role::Follower);
+        assert_eq!(states[2].leader(), Some(&peers[0]));
+        assert!(committed(&actions).is_empty());
+    }
+
+    #[test]
+    fn test_raft_log_conflict_and_vote_restriction() {
+        let (kps, peers) = peers(3);
+        // peer 0 was leader of term 2 and appended block 5 no one else stored
+        let stale_entry = LogEntry {
+            term: 2,
+            block: block(&kps[0], 5),
+        };
+        let hard_state = HardState {
+            current_term: 2,
+            voted_for: Some(peers[0].clone()),
+            entries: vec![stale_entry],
+        };
+        let mut old_leader = RaftState::new(kps[0].clone(), peers.clone(), hard_state, 4, 1);
+        assert_eq!(old_leader.last_log_index(), 5);
+        let mut leader = RaftState::new(kps[1].clone(), peers.clone(), HardState::default(), 4, 1);
+        let mut voter = RaftState::new(kps[2].clone(), peers.clone(), HardState::default(), 4, 1);
+
+        // peer 1 campaigns until term 4, a term later than the old leader
+        leader.on_election_timeout();
+        leader.on_election_timeout();
+        leader.on_election_timeout();
+        let request_vote = sent(leader.on_election_timeout());
+        assert_eq!(leader.current_term(), 4);
+        // old leader refuses, peer 1 misses the block of term 2 it has
+        let refusal = sent(deliver(&mut old_leader, request_vote.clone()));
+        match &refusal[0] {
+            RaftMessageTypes::VoteResponseEnum(data) => {
+                assert_eq!(data.payload.term, 4);
+                assert_eq!(data.payload.granted, false)
+            }
+            _ => panic!("vote response expected"),
+        }
+        let vote = sent(deliver(&mut voter, request_vote));
+        deliver(&mut leader, vote);
+        assert_eq!(leader.role(), Role::Leader);
+
+        // block 5 of the new leader replaces the stale one
+        let append_entries = sent(leader.append_block(block(&kps[1], 5)));
+        let actions: Vec<Action> = deliver(&mut old_leader, append_entries);
+        assert_eq!(old_leader.role(), Role::Follower);
+        assert_eq!(old_leader.entries().len(), 1);
+        assert_eq!(old_leader.entries()[0].term, 4);
+        assert_eq!(old_leader.entries()[0].block.block.peer_id, peers[1]);
+        assert!(actions.iter().any(|action| match action {
+            Action::Persist(hard_state) => hard_state.entries.len() == 1,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn test_raft_follower_catch_up() {
+        let (kps, peers) = peers(3);
+        let mut leader = RaftState::new(kps[0].clone(), peers.clone(), HardState::default(), 0, 0);
+        let mut follower =
+            RaftState::new(kps[1].clone(), peers.clone(), HardState::default(), 0, 0);
+        let mut voter = RaftState::new(kps[2].clone(), peers.clone(), HardState::default(), 0, 0);
+        let vote = sent(deliver(&mut voter, sent(leader.on_election_timeout())));
+        deliver(&mut leader, vote);
+
+        // follower misses two blocks, voter stores them
+        for height in 1..3 {
+            let append_entries = sent(leader.append_block(block(&kps[0], height)));
+            let response = sent(deliver(&mut voter, append_entries));
+            deliver(&mut leader, response);
+        }
+        assert_eq!(leader.commit_index(), 2);
+        leader.append_block(block(&kps[0], 3));
+
+        // follower's log is behind, leader retries from the hinted index
+        let heartbeats = sent(leader.on_heartbeat());
+        let response = sent(deliver(&mut follower, heartbeats));
+        match &response[..] {
+            [_, RaftMessageTypes::AppendResponseEnum(data)]
+            | [RaftMessageTypes::AppendResponseEnum(data)] => {
+                assert_eq!(data.payload.success, false);
+                assert_eq!(data.payload.match_index, 0);
+            }
+            _ => panic!("append response expected"),
+        }
+        // committed blocks aren't in the leader's log, the follower syncs them from peers
+        let retry = sent(deliver(&mut leader, response));
+        let response = sent(deliver(&mut follower, retry));
+        assert!(follower.entries().is_empty());
+        follower.on_synced(2, 1);
+        deliver(&mut leader, response);
+        let heartbeats = sent(leader.on_heartbeat());
+        let response = sent(deliver(&mut follower, heartbeats));
+        assert_eq!(follower.entries().len(), 1);
+        let actions: Vec<Action> = deliver(&mut leader, response);
+        assert_eq!(committed(&actions), vec![3]);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::raft_messages::LogEntry;
+use std::fs;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
+
+/// HardState is what a peer must not forget across restarts: a vote is given once per term
+/// and acknowledged entries stay in the log until they are committed into the db
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct HardState {
+    pub current_term: u64,
+    pub voted_for: Option<String>,
+    // entries after the last block stored in db
+    pub entries: Vec<LogEntry>,
+}
+
+/// RaftStorage keeps the hard state of the peer in a local file
+pub struct RaftStorage {
+    path: PathBuf,
+}
+
+impl RaftStorage {
+    pub fn new(path: &str) -> RaftStorage {
+        RaftStorage {
+            path: PathBuf::from(path),
+        }
+    }
+
+    /// hard state saved last, a fresh peer starts from term 0 without vote
+    pub fn load(&self) -> Result<HardState, String> {
+        if !self.path.exists() {
+            return Ok(HardState::default());
+        }
+        let data: Vec<u8> = fs::read(&self.path).map_err(|error| {
+            format!(
+                "raft state {} couldn't read: {}",
+                self.path.display(),
+                error
+            )
+        })?;
+        deserialize(&data).map_err(|_| format!("raft state {} is corrupted", self.path.display()))
+    }
+
+    /// this function will replace saved hard state, a crash leaves either the old or the new
+    /// state behind
+    pub fn save(&self, hard_state: &HardState) -> Result<(), String> {
+        let data: Vec<u8> = serialize(hard_state)?;
+        let mut temp_path: PathBuf = self.path.clone();
+        temp_path.set_extension("tmp");
+        let write_error = |error: std::io::Error| {
+            format!(
+                "raft state {} couldn't write: {}",
+                temp_path.display(),
+                error
+            )
+        };
+        {
+            let mut file = fs::File::create(&temp_path).map_err(write_error)?;
+            file.write_all(&data).map_err(write_error)?;
+            file.sync_all().map_err(write_error)?;
+        }
+        fs::rename(&temp_path, &self.path).map_err(|error| {
+            format!(
+                "raft state {} couldn't replace: {}",
+                self.path.display(),
+                error
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_raft_storage {
+    use super::*;
+    use exonum_crypto::Hash;
+    use schema::block::{Block, BlockTraits, SignedBlock};
+
+    #[test]
+    fn test_raft_storage() {
+        let path: PathBuf =
+            std::env::temp_dir().join(format!("raft_state_{}.cbor", std::process::id()));
+        let storage = RaftStorage::new(path.to_str().unwrap());
+        assert_eq!(storage.load(), Ok(HardState::default()));
+
+        let block: Block = Block::new_block(
+            1,
+            String::from("leader"),
+            Hash::zero(),
+            Vec::new(),
+            [Hash::zero(), Hash::zero(), Hash::zero(), Hash::zero()],
+            Vec::new(),
+        );
+        let hard_state = HardState {
+            current_term: 3,
+            voted_for: Some(String::from("candidate")),
+            entries: vec![LogEntry {
+                term: 2,
+                block: SignedBlock::create_block(block, Vec::new(), Vec::new()),
+            }],
+        };
+        assert_eq!(storage.save(&hard_state), Ok(()));
+        assert_eq!(storage.load(), Ok(hard_state.clone()));
+        // state survives a restart, i.e. a fresh handle of the same file
+        assert_eq!(
+            RaftStorage::new(path.to_str().unwrap()).load(),
+            Ok(hard_state)
+        );
+
+        fs::write(&path, b"garbage").unwrap();
+        assert!(storage.load().is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
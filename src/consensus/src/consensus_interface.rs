@@ -1,40 +1,118 @@
 extern crate aura;
+extern crate bft;
+extern crate consensus_engine;
+extern crate db_service;
+extern crate dev;
 extern crate message_handler;
+extern crate raft;
 extern crate utils;
 
 use aura::aura_interface;
+use bft::bft_interface;
+use consensus_engine::{start_engine, ConsensusEngine, EngineFactory, EngineRegistry};
+// node message processing hands gossiped blocks over to the importer of the engine
+pub use consensus_engine::{BlockImporter, SharedImporter};
+use db_service::db_layer::DbInstance;
+use dev::dev_interface;
 use futures::channel::mpsc::*;
 use message_handler::messages::MessageTypes;
 use poa::poa_interface;
+use raft::raft_interface;
 use std::sync::{Arc, Mutex};
 use utils::configreader::Configuration;
 
+lazy_static! {
+    /// engines selectable through consensus_name of node config, built-in engines are
+    /// registered up front
+    static ref ENGINES: Mutex<EngineRegistry> = Mutex::new(Consensus::builtin_engines());
+}
+
 pub struct Consensus {}
 
 impl Consensus {
-    pub fn init_consensus(
+    fn builtin_engines() -> EngineRegistry {
+        let mut registry = EngineRegistry::new();
+        let builtin: [(&str, EngineFactory); 5] = [
+            ("aura", aura_interface::Aura::create_engine),
+            ("bft", bft_interface::Bft::create_engine),
+            ("dev", dev_interface::Dev::create_engine),
+            ("poa", poa_interface::Consensus::create_engine),
+            ("raft", raft_interface::Raft::create_engine),
+        ];
+        for (name, factory) in builtin.iter() {
+            registry
+                .register(name, *factory)
+                .expect("built-in consensus engine registered twice");
+        }
+        registry
+    }
+
+    /// this function will make an engine selectable by given name, it has to be called before
+    /// init_consensus e.g. by a node binary experimenting with its own engine
+    pub fn register_engine(name: &str, factory: EngineFactory) -> Result<(), String> {
+        ENGINES.lock().unwrap().register(name, factory)
+    }
+
+    /// registered engine names in sorted order
+    pub fn engine_names() -> Vec<String> {
+        ENGINES.lock().unwrap().names()
+    }
+
+    // fn will create the engine selected by node config, Err carries the reason it couldn't
+    fn create_engine(
         config: &Configuration,
+        db: DbInstance,
         consensus_file_path: &str,
         sender: &mut Sender<Option<MessageTypes>>,
-        msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
-    ) {
-        if config.node.consensus_name == "aura" {
-            aura_interface::Aura::init_aura_consensus(
-                config,
-                &consensus_file_path,
-                sender,
-                msg_receiver,
-            );
-        } else if config.node.consensus_name == "poa" {
-            poa_interface::Consensus::init_poa_consensus(
-                config,
-                &consensus_file_path,
-                sender,
-                msg_receiver,
+    ) -> Result<Box<dyn ConsensusEngine>, String> {
+        let created: Result<Box<dyn ConsensusEngine>, String> = ENGINES.lock().unwrap().create(
+            &config.node.consensus_name,
+            config,
+            db,
+            consensus_file_path,
+            sender.clone(),
+        );
+        if let Err(reason) = &created {
+            error!(
+                "kindly provide predefined consensus name string, {}",
+                reason
             );
-        } else {
-            println!("kindly provide predefined consensus name string");
-            std::process::exit(1);
         }
+        created
+    }
+
+    /// this function will create the engine selected by node config & run it, it returns only
+    /// when consensus stops. importer gets the block importer of the engine once it starts.
+    /// Err carries the reason an engine couldn't be created
+    pub fn init_consensus(
+        config: &Configuration,
+        db: DbInstance,
+        consensus_file_path: &str,
+        sender: &mut Sender<Option<MessageTypes>>,
+        msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
+        importer: SharedImporter,
+    ) -> Result<(), String> {
+        let mut engine: Box<dyn ConsensusEngine> =
+            Consensus::create_engine(config, db, consensus_file_path, sender)?;
+        start_engine(
+            engine.as_mut(),
+            config.node.genesis_block,
+            msg_receiver,
+            &importer,
+        );
+        Ok(())
+    }
+
+    /// this function will create the engine selected by node config without running it, full
+    /// nodes store blocks gossiped by peers through its importer
+    pub fn block_importer(
+        config: &Configuration,
+        db: DbInstance,
+        consensus_file_path: &str,
+        sender: &mut Sender<Option<MessageTypes>>,
+    ) -> Result<Option<Arc<dyn BlockImporter>>, String> {
+        let engine: Box<dyn ConsensusEngine> =
+            Consensus::create_engine(config, db, consensus_file_path, sender)?;
+        Ok(engine.block_importer())
     }
 }
@@ -1 +1,6 @@
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
+
 pub mod consensus_interface;
@@ -1,6 +1,10 @@
 extern crate services;
 use actix_rt::System;
-use actix_web::{dev::Server, get, middleware, post, web, App, HttpResponse, HttpServer};
+use actix_web::{
+    dev::Server, get, http::header, middleware, post, web, App, HttpRequest, HttpResponse,
+    HttpServer,
+};
+use db_service::db_layer::DbInstance;
 use futures::channel::mpsc::*;
 use message_handler::messages::MessageTypes;
 use services::client_services::ClientServices;
@@ -34,48 +38,116 @@ async fn fetch_pending_transaction_controller(transaction_hash: web::Bytes) -> H
 }
 
 #[get("/client/fetch_confirm_transaction")]
-async fn fetch_confirm_transaction_controller(transaction_hash: web::Bytes) -> HttpResponse {
-    ClientServices::fetch_confirm_transaction_service(transaction_hash)
+async fn fetch_confirm_transaction_controller(
+    db: web::Data<DbInstance>,
+    transaction_hash: web::Bytes,
+) -> HttpResponse {
+    ClientServices::fetch_confirm_transaction_service(&db, transaction_hash)
+}
+
+#[get("/client/fetch_transaction_receipt")]
+async fn fetch_transaction_receipt_controller(
+    db: web::Data<DbInstance>,
+    transaction_hash: web::Bytes,
+) -> HttpResponse {
+    ClientServices::fetch_transaction_receipt_service(&db, transaction_hash)
+}
+
+#[get("/client/fetch_transaction_status")]
+async fn fetch_transaction_status_controller(
+    db: web::Data<DbInstance>,
+    transaction_hash: web::Bytes,
+) -> HttpResponse {
+    ClientServices::fetch_transaction_status_service(&db, transaction_hash)
 }
 
 #[get("/client/fetch_state")]
-async fn fetch_state_controller(address: web::Bytes) -> HttpResponse {
-    ClientServices::fetch_state_service(address)
+async fn fetch_state_controller(db: web::Data<DbInstance>, address: web::Bytes) -> HttpResponse {
+    ClientServices::fetch_state_service(&db, address)
+}
+
+#[get("/client/query/{app}")]
+async fn query_controller(
+    db: web::Data<DbInstance>,
+    app: web::Path<String>,
+    query: web::Bytes,
+) -> HttpResponse {
+    ClientServices::query_service(&db, app.into_inner(), query)
+}
+
+#[get("/client/events/{app}")]
+async fn events_controller(
+    db: web::Data<DbInstance>,
+    app: web::Path<String>,
+    filter: web::Bytes,
+) -> HttpResponse {
+    ClientServices::events_service(&db, app.into_inner(), filter)
+}
+
+#[get("/client/stream_events/{app}")]
+async fn stream_events_controller(
+    db: web::Data<DbInstance>,
+    app: web::Path<String>,
+    filter: web::Bytes,
+) -> HttpResponse {
+    ClientServices::stream_events_service(&db, app.into_inner(), filter)
 }
 
 #[get("/peer/fetch_block")]
-async fn fetch_block_peer_controller(address: web::Bytes) -> HttpResponse {
-    ClientServices::fetch_block_peer_service(address)
+async fn fetch_block_peer_controller(
+    db: web::Data<DbInstance>,
+    address: web::Bytes,
+) -> HttpResponse {
+    ClientServices::fetch_block_peer_service(&db, address)
 }
 
 #[get("/peer/fetch_latest_block")]
-async fn fetch_latest_block_peer_controller() -> HttpResponse {
-    ClientServices::fetch_latest_block_peer_service()
+async fn fetch_latest_block_peer_controller(db: web::Data<DbInstance>) -> HttpResponse {
+    ClientServices::fetch_latest_block_peer_service(&db)
 }
 
 #[get("/client/fetch_block")]
-async fn fetch_block_controller(address: web::Bytes) -> HttpResponse {
-    ClientServices::fetch_block_service(address)
+async fn fetch_block_controller(db: web::Data<DbInstance>, address: web::Bytes) -> HttpResponse {
+    ClientServices::fetch_block_service(&db, address)
 }
 
 #[get("/client/fetch_latest_block")]
-async fn fetch_latest_block_controller() -> HttpResponse {
-    ClientServices::fetch_latest_block_service()
+async fn fetch_latest_block_controller(db: web::Data<DbInstance>) -> HttpResponse {
+    ClientServices::fetch_latest_block_service(&db)
 }
 
 #[get("/peer/fetch_blockchain_length")]
-async fn fetch_blockchain_length_peer_controller() -> HttpResponse {
-    ClientServices::fetch_blockchain_length_peer_service()
+async fn fetch_blockchain_length_peer_controller(db: web::Data<DbInstance>) -> HttpResponse {
+    ClientServices::fetch_blockchain_length_peer_service(&db)
 }
 
 #[get("/client/fetch_blockchain_length")]
-async fn fetch_blockchain_length_controller() -> HttpResponse {
-    ClientServices::fetch_blockchain_length_service()
+async fn fetch_blockchain_length_controller(db: web::Data<DbInstance>) -> HttpResponse {
+    ClientServices::fetch_blockchain_length_service(&db)
+}
+
+#[post("/admin/seal_block")]
+async fn seal_block_controller(request: HttpRequest) -> HttpResponse {
+    let authorization: Option<&str> = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    ClientServices::seal_block_service(authorization)
+}
+
+// admin routes are registered only for dev consensus with a configured admin_token
+fn admin_routes(cfg: &mut web::ServiceConfig) {
+    if ClientServices::admin_api_enabled() {
+        cfg.service(seal_block_controller);
+    }
 }
 
 #[get("/peer/fetch_transaction")]
-async fn fetch_transaction_peer_controller(transaction_hash: web::Bytes) -> HttpResponse {
-    ClientServices::fetch_transaction_peer_service(transaction_hash)
+async fn fetch_transaction_peer_controller(
+    db: web::Data<DbInstance>,
+    transaction_hash: web::Bytes,
+) -> HttpResponse {
+    ClientServices::fetch_transaction_peer_service(&db, transaction_hash)
 }
 
 //May be useful for storing forwarding/db_layer related info
@@ -89,10 +161,11 @@ struct AppState {
 pub struct ClientController {
     srvr_addr: SocketAddr,
     srvr: Option<Server>,
+    db: DbInstance,
 }
 
 impl ClientController {
-    pub fn new(host: &String, port: u32) -> Self {
+    pub fn new(host: &String, port: u32, db: DbInstance) -> Self {
         //to enable logging use below setting
         std::env::set_var("RUST_LOG", "actix_web=info,actix_server=trace");
         let addr = format!("{}:{}", host, port)
@@ -103,6 +176,7 @@ impl ClientController {
         ClientController {
             srvr_addr: addr,
             srvr: None,
+            db,
         }
     }
 }
@@ -112,17 +186,24 @@ impl Controller for ClientController {
         let sys = System::new("TransactionService");
         info!("Starting api_service at {:?}", self.srvr_addr);
         let app_data = web::Data::new(Mutex::new(AppState { txn_sender: sender }));
+        let db_data = web::Data::new(self.db.clone());
         self.srvr = Some(
             HttpServer::new(move || {
                 App::new()
                     .app_data(app_data.clone())
+                    .app_data(db_data.clone())
                     // enable logger
                     .wrap(middleware::Logger::default())
                     //.service(web::resource("/index.html").to(|| async { "Hello world!" }))
                     .service(submit_transaction_controller)
                     .service(fetch_confirm_transaction_controller)
                     .service(fetch_pending_transaction_controller)
+                    .service(fetch_transaction_receipt_controller)
+                    .service(fetch_transaction_status_controller)
                     .service(fetch_state_controller)
+                    .service(query_controller)
+                    .service(events_controller)
+                    .service(stream_events_controller)
                     .service(fetch_block_controller)
                     .service(fetch_latest_block_controller)
                     .service(fetch_block_peer_controller)
@@ -130,6 +211,7 @@ impl Controller for ClientController {
                     .service(fetch_blockchain_length_peer_controller)
                     .service(fetch_blockchain_length_controller)
                     .service(fetch_transaction_peer_controller)
+                    .configure(admin_routes)
             })
             .bind(self.srvr_addr)
             .unwrap()
@@ -147,15 +229,22 @@ impl Controller for ClientController {
         let sys = System::new("TransactionService");
         info!("Starting api_service at {:?}", self.srvr_addr);
         let app_data = web::Data::new(Mutex::new(AppState { txn_sender: sender }));
+        let db_data = web::Data::new(self.db.clone());
         self.srvr = Some(
             HttpServer::new(move || {
                 App::new()
                     .app_data(app_data.clone())
+                    .app_data(db_data.clone())
                     // enable logger
                     .wrap(middleware::Logger::default())
                     //.service(web::resource("/index.html").to(|| async { "Hello world!" }))
                     .service(fetch_confirm_transaction_controller)
+                    .service(fetch_transaction_receipt_controller)
+                    .service(fetch_transaction_status_controller)
                     .service(fetch_state_controller)
+                    .service(query_controller)
+                    .service(events_controller)
+                    .service(stream_events_controller)
                     .service(fetch_block_controller)
                     .service(fetch_latest_block_controller)
                     .service(fetch_block_peer_controller)
@@ -2,23 +2,23 @@
 mod test_controller_services {
     use crate::client_controller::*;
     use client::client::ClientObj;
-    use db_service::{
-        db_fork_ref::SchemaFork,
-        db_layer::{fork_db, patch_db},
-    };
+    use db_service::{db_fork_ref::SchemaFork, db_layer::DbInstance};
     use exonum_crypto::Hash;
     use exonum_merkledb::ObjectHash;
     use futures::channel::mpsc::*;
     use libp2p::core::{Multiaddr, PeerId};
     use libp2p::identity::PublicKey;
     use message_handler::messages::MessageTypes;
+    use schema::appdata::APPDATA;
     use schema::signed_transaction::SignedTransaction;
     use schema::state::State;
     use schema::transaction_pool::{TxnPool, TxnPoolKeyType, POOL};
-    use sdk::traits::StateContext;
+    use sdk::test_utils::MockApp;
+    use sdk::traits::{AppHandler, ChainStateContext, StateContext};
     use std::collections::HashMap;
     use std::net::IpAddr;
     use std::net::Ipv4Addr;
+    use std::sync::{Arc, Mutex};
     use std::time::SystemTime;
     use std::{thread, time::Duration};
     use utils::configreader;
@@ -27,6 +27,15 @@ mod test_controller_services {
     use utils::global_peer_data::*;
     use utils::serializer::serialize;
 
+    fn register_mock_app() {
+        let app: Box<dyn AppHandler + Send> = Box::new(MockApp::new("app_name"));
+        APPDATA
+            .lock()
+            .unwrap()
+            .appdata
+            .insert(app.name(), Arc::new(Mutex::new(app)));
+    }
+
     fn test_submit_transaction_controller(client: &ClientObj) {
         let mut header = HashMap::default();
         let time_stamp: TxnPoolKeyType = 6565656565;
@@ -81,7 +90,7 @@ mod test_controller_services {
         };
     }
 
-    fn test_fetch_confirm_transaction_controller(client: &ClientObj) {
+    fn test_fetch_confirm_transaction_controller(db: &DbInstance, client: &ClientObj) {
         let mut header = HashMap::default();
         let time_stamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -95,14 +104,14 @@ mod test_controller_services {
             signature: vec![0],
         };
         let txn_hash: Hash = signed_transaction.object_hash();
-        let fork = fork_db();
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             if !schema.contains_txn(&txn_hash) {
                 schema.put_txn(&txn_hash, signed_transaction.clone());
             }
         }
-        patch_db(fork);
+        db.patch(fork);
         match client.fetch_confirm_transaction(&txn_hash) {
             Ok(is_value) => {
                 if None == is_value {
@@ -122,17 +131,17 @@ mod test_controller_services {
         };
     }
 
-    fn test_fetch_state_controller(client: &ClientObj) {
+    fn test_fetch_state_controller(db: &DbInstance, client: &ClientObj) {
         let state_key: String = String::from("dkcnjsdcnosdvnvsfv");
         let state: State = State::new();
-        let fork = fork_db();
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             if !schema.contains(&state_key) {
                 schema.put(&state_key, state.clone());
             }
         }
-        patch_db(fork);
+        db.patch(fork);
         match client.fetch_state(&state_key) {
             Ok(is_value) => {
                 if None == is_value {
@@ -143,13 +152,13 @@ mod test_controller_services {
         };
     }
 
-    fn test_fetch_block_controller(client: &ClientObj) {
-        let fork = fork_db();
+    fn test_fetch_block_controller(db: &DbInstance, client: &ClientObj) {
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             schema.initialize_db(Vec::new(), 0);
         }
-        patch_db(fork);
+        db.patch(fork);
         match client.fetch_block(&0) {
             Ok(is_value) => {
                 if None == is_value {
@@ -192,19 +201,22 @@ mod test_controller_services {
             GLOBALDATA.lock().unwrap().peers.insert(peer_id, peer_data);
         }
         let (sender, _) = channel::<Option<MessageTypes>>(4194304);
+        let db: DbInstance = DbInstance::temporary();
         let host: String = String::from("127.0.0.1");
-        let mut client_controller: ClientController = ClientController::new(&host, 8089);
+        let mut client_controller: ClientController =
+            ClientController::new(&host, 8089, db.clone());
         thread::spawn(move || {
             client_controller.start_validator_controller(sender);
         });
         println!("thread for server started");
         thread::sleep(Duration::from_millis(2000));
         let client: ClientObj = ClientObj::new();
+        register_mock_app();
         test_submit_transaction_controller(&client);
         test_fetch_pending_transaction_controller(&client, config.node.hex_public.clone());
-        test_fetch_confirm_transaction_controller(&client);
-        test_fetch_state_controller(&client);
-        test_fetch_block_controller(&client);
+        test_fetch_confirm_transaction_controller(&db, &client);
+        test_fetch_state_controller(&db, &client);
+        test_fetch_block_controller(&db, &client);
         test_fetch_latest_block_controller(&client);
         test_fetch_blockchain_length_controller(&client);
         std::process::exit(0);
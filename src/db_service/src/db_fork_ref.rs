@@ -2,21 +2,24 @@ extern crate client;
 extern crate schema;
 extern crate utils;
 
-use super::db_layer::fork_db;
+use super::db_layer::DbInstance;
 use client::client::{ClientObj, SyncState};
 use exonum_crypto::Hash;
 use exonum_derive::FromAccess;
 use exonum_merkledb::{
     access::{Access, FromAccess, RawAccessMut},
-    Fork, ListIndex, ObjectHash, ProofMapIndex,
+    Fork, Group, KeySetIndex, ListIndex, MapIndex, ObjectHash, ProofMapIndex,
 };
+use schema::appdata::APPDATA;
 use schema::block::{Block, BlockTraits, SignedBlock};
+use schema::event::Event;
+use schema::receipt::Receipt;
 use schema::signed_transaction::SignedTransaction;
 use schema::state::State;
 use schema::transaction_pool::{
-    TransactionPool, TransactionPoolTraits, TxnPool, TxnPoolKeyType, POOL,
+    config_chain_limits, TransactionPool, TransactionPoolTraits, TxnPool, TxnPoolKeyType, POOL,
 };
-use sdk::traits::{PoolTrait, StateContext};
+use sdk::traits::{BlockContext, ChainStateContext, PoolTrait, StateContext};
 use std::time::SystemTime;
 use utils::configreader;
 use utils::configreader::BlockConfig;
@@ -27,7 +30,21 @@ pub struct SchemaFork<T: Access> {
     txn_trie: ProofMapIndex<T::Base, Hash, SignedTransaction>,
     block_list: ListIndex<T::Base, SignedBlock>,
     state_trie: ProofMapIndex<T::Base, String, State>,
-    storage_trie: ProofMapIndex<T::Base, Hash, SignedTransaction>,
+    // account => root of its contract storage
+    storage_trie: ProofMapIndex<T::Base, String, Hash>,
+    receipt_trie: ProofMapIndex<T::Base, Hash, Receipt>,
+    // sorted keys of state_trie, proof map iterates in key hash order
+    state_keys: KeySetIndex<T::Base, String>,
+    // contract storage of every account, key => value
+    account_storage: Group<T, String, ProofMapIndex<T::Base, String, Vec<u8>>>,
+    // events of every app in block order, indexed from receipts
+    app_events: Group<T, String, ListIndex<T::Base, Event>>,
+    // block height => position of the first event of that block in app_events, per app
+    event_offsets: Group<T, String, MapIndex<T::Base, u64, u64>>,
+    // apps having at least one event
+    event_apps: KeySetIndex<T::Base, String>,
+    // node local states the genesis block starts with, set by the engine, not part of any root
+    genesis_states: MapIndex<T::Base, String, State>,
 }
 
 impl<T: Access> SchemaFork<T> {
@@ -42,11 +59,39 @@ impl<T: Access> SchemaFork<T> {
     }
 }
 
+impl<T: Access> SchemaFork<T>
+where
+    T::Base: RawAccessMut,
+{
+    /// this function will write new root of account storage in storage_trie and
+    /// State::storage_root of the account. No state is created for an account without one,
+    /// it picks the root up from storage_trie once put. Storage emptied for such an account
+    /// leaves no root behind.
+    fn commit_storage_root(&mut self, account: &String) {
+        let (storage_root, is_empty): (Hash, bool) = {
+            let storage = self.account_storage.get(account);
+            (storage.object_hash(), storage.keys().next().is_none())
+        };
+        match self.state_trie.get(account) {
+            Some(state) => {
+                self.storage_trie.put(account, storage_root);
+                self.put(account, state);
+            }
+            None if is_empty => self.storage_trie.remove(account),
+            None => self.storage_trie.put(account, storage_root),
+        }
+    }
+}
+
 impl<T: Access> StateContext for SchemaFork<T>
 where
     T::Base: RawAccessMut,
 {
-    fn put(&mut self, key: &String, state: State) {
+    /// storage_root of the state is owned by the node, whatever the app put there is
+    /// replaced by root of the account storage in storage_trie
+    fn put(&mut self, key: &String, mut state: State) {
+        state.set_storage_root(self.storage_trie.get(key).unwrap_or_else(Hash::zero));
+        self.state_keys.insert(key);
         self.state_trie.put(key, state);
     }
     fn get(&self, key: &String) -> Option<State> {
@@ -55,8 +100,50 @@ where
     fn contains(&self, key: &String) -> bool {
         self.state_trie.contains(key)
     }
-    fn put_txn(&mut self, key: &Hash, txn: SignedTransaction) {
-        self.txn_trie.put(key, txn);
+    /// contract storage of the account goes along with its state
+    fn delete(&mut self, key: &String) {
+        self.account_storage.get(key).clear();
+        self.storage_trie.remove(key);
+        self.state_keys.remove(key);
+        self.state_trie.remove(key);
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        self.iter_prefix_bounded(prefix, usize::MAX)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        self.range_bounded(from, to, usize::MAX)
+    }
+    fn iter_prefix_bounded(&self, prefix: &String, max_entries: usize) -> Vec<(String, State)> {
+        self.state_keys
+            .iter_from(prefix)
+            .take_while(|key| key.starts_with(prefix.as_str()))
+            .take(max_entries)
+            .filter_map(|key| self.state_trie.get(&key).map(|state| (key, state)))
+            .collect()
+    }
+    fn range_bounded(
+        &self,
+        from: &String,
+        to: &String,
+        max_entries: usize,
+    ) -> Vec<(String, State)> {
+        self.state_keys
+            .iter_from(from)
+            .take_while(|key| key < to)
+            .take(max_entries)
+            .filter_map(|key| self.state_trie.get(&key).map(|state| (key, state)))
+            .collect()
+    }
+    fn put_storage(&mut self, account: &String, key: &String, value: Vec<u8>) {
+        self.account_storage.get(account).put(key, value);
+        self.commit_storage_root(account);
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.account_storage.get(account).get(key)
+    }
+    fn delete_storage(&mut self, account: &String, key: &String) {
+        self.account_storage.get(account).remove(key);
+        self.commit_storage_root(account);
     }
     fn get_txn(&self, key: &Hash) -> Option<SignedTransaction> {
         self.txn_trie.get(key)
@@ -64,6 +151,36 @@ where
     fn contains_txn(&self, key: &Hash) -> bool {
         self.txn_trie.contains(key)
     }
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt> {
+        self.receipt_trie.get(key)
+    }
+    fn emit_event(&mut self, topic: &String, _data: Vec<u8>) {
+        warn!("event {} emitted outside block execution dropped", topic);
+    }
+    fn get_block_producer(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T: Access> ChainStateContext for SchemaFork<T>
+where
+    T::Base: RawAccessMut,
+{
+    fn put_txn(&mut self, key: &Hash, txn: SignedTransaction) {
+        self.txn_trie.put(key, txn);
+    }
+    fn put_receipt(&mut self, key: &Hash, receipt: Receipt) {
+        for event in receipt.get_events().iter() {
+            self.event_apps.insert(event.get_app_name());
+            let mut app_events = self.app_events.get(event.get_app_name());
+            let mut event_offsets = self.event_offsets.get(event.get_app_name());
+            if !event_offsets.contains(&event.get_block_height()) {
+                event_offsets.put(&event.get_block_height(), app_events.len());
+            }
+            app_events.push(event.clone());
+        }
+        self.receipt_trie.put(key, receipt);
+    }
 }
 
 impl<T: Access> SchemaFork<T>
@@ -82,19 +199,39 @@ where
         self.storage_trie.object_hash()
     }
 
+    pub fn receipt_trie_merkle_hash(&self) -> Hash {
+        self.receipt_trie.object_hash()
+    }
+
     pub fn blockchain_length(&self) -> u64 {
         self.block_list.len()
     }
 
     pub fn initialize_db(&mut self, custom_headers: Vec<u8>, timestamp: u128) -> SignedBlock {
         self.state_trie.clear();
+        self.state_keys.clear();
         self.txn_trie.clear();
+        for account in self.storage_trie.keys() {
+            self.account_storage.get(&account).clear();
+        }
         self.storage_trie.clear();
+        for app_name in self.event_apps.iter() {
+            self.app_events.get(&app_name).clear();
+            self.event_offsets.get(&app_name).clear();
+        }
+        self.event_apps.clear();
+        self.receipt_trie.clear();
         self.block_list.clear();
         let mut block = Block::genesis_block(custom_headers, timestamp);
+        if let Err(reason) = self.genesis_state(&block) {
+            // node can't start from a genesis state that misses an app's initial state
+            error!("{}", reason);
+            panic!("{}", reason);
+        }
         block.header[0] = self.state_trie_merkle_hash();
         block.header[1] = self.storage_trie_merkle_hash();
         block.header[2] = self.txn_trie_merkle_hash();
+        block.header[3] = self.receipt_trie_merkle_hash();
         let signature: Vec<u8> = Vec::new();
         let sign_headers: Vec<u8> = Vec::new();
         let genesis_block: SignedBlock = SignedBlock::create_block(block, signature, sign_headers);
@@ -102,30 +239,120 @@ where
         return genesis_block;
     }
 
+    /// this function will keep states the genesis block starts with, engines set them from
+    /// their consensus config before genesis block is created or replayed
+    pub fn set_genesis_states(&mut self, states: Vec<(String, State)>) {
+        self.genesis_states.clear();
+        for (key, state) in states {
+            self.genesis_states.put(&key, state);
+        }
+    }
+
+    /// this function will put state of given genesis block, it is the same whether the
+    /// genesis block is created or replayed from peers. Genesis states set by the engine
+    /// are put before app hooks run. Err carries the failed app hook
+    fn genesis_state(&mut self, block: &Block) -> Result<(), String> {
+        config_chain_limits().store(self);
+        let genesis_states: Vec<(String, State)> = self.genesis_states.iter().collect();
+        for (key, state) in genesis_states {
+            self.put(&key, state);
+        }
+        self.init_genesis(block)
+    }
+
+    /// this function will let apps initialize their state for given genesis block
+    fn init_genesis(&mut self, block: &Block) -> Result<(), String> {
+        let block_context = BlockContext {
+            height: block.id,
+            timestamp: block.timestamp,
+            proposer: block.peer_id.clone(),
+        };
+        let state_context = self as &mut dyn StateContext;
+        APPDATA
+            .lock()
+            .unwrap()
+            .init_genesis(&block_context, state_context)
+    }
+
+    /// this function will run begin_block hooks of apps for the block under execution
+    fn begin_block(&mut self, block_context: &BlockContext) {
+        let state_context = self as &mut dyn StateContext;
+        APPDATA
+            .lock()
+            .unwrap()
+            .begin_block(block_context, state_context);
+    }
+
+    /// this function will run end_block hooks of apps for the block under execution
+    fn end_block(&mut self, block_context: &BlockContext) {
+        let state_context = self as &mut dyn StateContext;
+        APPDATA
+            .lock()
+            .unwrap()
+            .end_block(block_context, state_context);
+    }
+
     /**
      * this function will iterate over txn_order_pool and return a vec of SignedTransaction and
      * all changes due to these transaction also updated in state_trie
      */
-    pub fn execute_transactions(&mut self, txn_pool: &mut TransactionPool) -> Vec<Hash> {
-        let txn_pool_as_trait = txn_pool as &dyn PoolTrait<T, State, SignedTransaction>;
-        let state_context = self as &mut dyn StateContext;
+    pub fn execute_transactions(
+        &mut self,
+        txn_pool: &mut TransactionPool,
+        block_producer: &String,
+    ) -> Vec<Hash> {
         let (executed_txns, unknown_app_txns_hash) =
-            txn_pool_as_trait.execute_transactions(state_context);
-        txn_pool.sync_pool(&unknown_app_txns_hash);
+            self.collect_transactions(txn_pool, block_producer);
+        txn_pool.evict_txns(&unknown_app_txns_hash);
         executed_txns
     }
 
+    // fn will execute txns of given pool without touching it, it returns executed txns and
+    // txns which couldn't be executed (unknown app or delayed)
+    fn collect_transactions(
+        &mut self,
+        txn_pool: &TransactionPool,
+        block_producer: &String,
+    ) -> (Vec<Hash>, Vec<Hash>) {
+        let block_height: u64 = self.block_list.len();
+        let txn_pool_as_trait = txn_pool as &dyn PoolTrait<T, State, SignedTransaction>;
+        let state_context = self as &mut dyn ChainStateContext;
+        txn_pool_as_trait.execute_transactions(state_context, block_height, block_producer)
+    }
+
     /// this function only will called when the node willing to propose block and for that agree to compute block
     pub fn create_block(&mut self, kp: &KeypairType, custom_headers: Vec<u8>) -> SignedBlock {
+        let (signed_block, rejected_txns) = self.prepare_block(kp, custom_headers);
+        POOL.evict_txns(&rejected_txns);
+        signed_block
+    }
+
+    /// this function will compute the block like create_block but leaves txn pool untouched,
+    /// it returns hashes of txns which couldn't be executed, caller evicts them once the block
+    /// is kept
+    pub fn prepare_block(
+        &mut self,
+        kp: &KeypairType,
+        custom_headers: Vec<u8>,
+    ) -> (SignedBlock, Vec<Hash>) {
         // all trie's state before current block computation
-        #[allow(unused_assignments)]
-        let mut executed_txns: Vec<Hash> = vec![];
-        {
-            let mut txn_pool = POOL.pool.lock().unwrap();
-            executed_txns = self.execute_transactions(&mut txn_pool);
-        }
-        info!("txn count in proposed block {}", executed_txns.len());
+        let public_key = hex::encode(Keypair::public(&kp).encode());
         let length = self.block_list.len();
+        let block_context = BlockContext {
+            height: length,
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_micros(),
+            proposer: public_key.clone(),
+        };
+        self.begin_block(&block_context);
+        let (executed_txns, rejected_txns) = {
+            let txn_pool = POOL.pool.lock().unwrap();
+            self.collect_transactions(&txn_pool, &public_key)
+        };
+        self.end_block(&block_context);
+        info!("txn count in proposed block {}", executed_txns.len());
         let last_block: SignedBlock = match self.block_list.get(length - 1) {
             Some(block) => block,
             None => {
@@ -134,14 +361,14 @@ where
             }
         };
         let prev_hash = last_block.object_hash();
-        let header: [Hash; 3] = [
+        let header: [Hash; 4] = [
             self.state_trie_merkle_hash(),
             self.storage_trie_merkle_hash(),
             self.txn_trie_merkle_hash(),
+            self.receipt_trie_merkle_hash(),
         ];
         // updated merkle root of all tries
-        let public_key = hex::encode(Keypair::public(&kp).encode());
-        let block = Block::new_block(
+        let mut block = Block::new_block(
             length,
             public_key,
             prev_hash,
@@ -149,15 +376,20 @@ where
             header,
             custom_headers,
         );
+        // hooks have seen this timestamp, validators hand the same to them
+        block.timestamp = block_context.timestamp;
         let signature: Vec<u8> = block.sign(kp);
         let auth_headers: Vec<u8> = Vec::new();
         let signed_block: SignedBlock = SignedBlock::create_block(block, signature, auth_headers);
         self.block_list.push(signed_block.clone());
-        signed_block
+        (signed_block, rejected_txns)
     }
 
+    /// this function will keep re-computing the block on fresh forks of given db instance
+    /// until the block is full or block_creation_time_limit is crossed
     pub fn forge_new_block(
         &self,
+        db: &DbInstance,
         kp: &KeypairType,
         custom_headers: Vec<u8>,
     ) -> (Fork, SignedBlock) {
@@ -172,7 +404,7 @@ where
             .unwrap()
             .as_micros();
         #[allow(unused_assignments)]
-        let mut fork_instance: Fork = fork_db();
+        let mut fork_instance: Fork = db.fork();
         // dummy signed block
         let mut block_instance: SignedBlock =
             SignedBlock::create_block(Block::genesis_block(Vec::new(), 0), Vec::new(), Vec::new());
@@ -190,7 +422,7 @@ where
                     .unwrap()
                     .as_micros();
                 if current_timestamp < timestamp {
-                    fork_instance = fork_db();
+                    fork_instance = db.fork();
                 }
             }
             let sleep_time: u64 = block_config.block_creation_time_limit as u64 / 10;
@@ -204,10 +436,12 @@ where
         &mut self,
         txn_pool: &TransactionPool,
         hash_vec: &Vec<Hash>,
+        block_producer: &String,
     ) -> bool {
+        let block_height: u64 = self.block_list.len();
         let txn_pool_as_trait = txn_pool as &dyn PoolTrait<T, State, SignedTransaction>;
-        let state_context = self as &mut dyn StateContext;
-        txn_pool_as_trait.update_transactions(state_context, hash_vec)
+        let state_context = self as &mut dyn ChainStateContext;
+        txn_pool_as_trait.update_transactions(state_context, hash_vec, block_height, block_producer)
     }
 
     /// this function will update fork for given block
@@ -224,10 +458,15 @@ where
 
         // genesis block check
         if signed_block.block.id == 0 {
-            let header: [Hash; 3] = [
+            if let Err(reason) = self.genesis_state(&signed_block.block) {
+                error!("{}", reason);
+                return false;
+            }
+            let header: [Hash; 4] = [
                 self.state_trie_merkle_hash(),
                 self.storage_trie_merkle_hash(),
                 self.txn_trie_merkle_hash(),
+                self.receipt_trie_merkle_hash(),
             ];
             if header[0] != signed_block.block.header[0] {
                 error!("block header state_trie merkle root error");
@@ -241,6 +480,10 @@ where
                 error!("block header transaction_trie merkle root error");
                 return false;
             }
+            if header[3] != signed_block.block.header[3] {
+                error!("block header receipt_trie merkle root error");
+                return false;
+            }
             self.block_list.push(signed_block.clone());
             return true;
         } else {
@@ -272,20 +515,32 @@ where
                 return false;
             }
 
-            // block txn pool validation
+            // block txn pool validation, surrounded by app hooks as on the proposer
+            let block_context = BlockContext {
+                height: signed_block.block.id,
+                timestamp: signed_block.block.timestamp,
+                proposer: signed_block.block.peer_id.clone(),
+            };
+            self.begin_block(&block_context);
             {
                 let txn_pool = POOL.pool.lock().unwrap();
-                if !self.update_transactions(&txn_pool, &signed_block.block.txn_pool) {
+                if !self.update_transactions(
+                    &txn_pool,
+                    &signed_block.block.txn_pool,
+                    &signed_block.block.peer_id,
+                ) {
                     error!("block txn_pool couldn't updated, block declined");
                     return false;
                 }
             }
+            self.end_block(&block_context);
 
             // block header check
-            let header: [Hash; 3] = [
+            let header: [Hash; 4] = [
                 self.state_trie_merkle_hash(),
                 self.storage_trie_merkle_hash(),
                 self.txn_trie_merkle_hash(),
+                self.receipt_trie_merkle_hash(),
             ];
             if header[0] != signed_block.block.header[0] {
                 error!("block header state_trie merkle root error");
@@ -299,19 +554,51 @@ where
                 error!("block header transaction_trie merkle root error");
                 return false;
             }
+            if header[3] != signed_block.block.header[3] {
+                error!("block header receipt_trie merkle root error");
+                return false;
+            }
             self.block_list.push(signed_block.clone());
             return true;
         }
     }
 
+    /// this function will replace auth headers of the last block, its content stays as is.
+    /// The same block may be finalised with different valid auth headers on different peers,
+    /// a peer adopts the ones the next block was built on.
+    pub fn replace_root_auth_headers(&mut self, auth_headers: Vec<u8>) -> bool {
+        let length = self.block_list.len();
+        if length == 0 {
+            return false;
+        }
+        let mut root_block: SignedBlock = match self.block_list.get(length - 1) {
+            Some(block) => block,
+            None => return false,
+        };
+        root_block.auth_headers = auth_headers;
+        self.block_list.set(length - 1, root_block);
+        true
+    }
+
     /// this function will sync blockchain state with other peers
     pub fn sync_state(&mut self) -> bool {
+        let sync_data: SyncState = self.fetch_sync_state(self.block_list.len());
+        self.apply_sync_state(&sync_data)
+    }
+
+    /// this function will fetch blocks of peers from given height on, nothing is applied
+    pub fn fetch_sync_state(&self, from_height: u64) -> SyncState {
         let client_instance = ClientObj::new();
+        client_instance.fetch_sync_state(from_height)
+    }
+
+    /// this function will apply fetched blocks on top of the chain, blocks below chain length
+    /// are skipped
+    pub fn apply_sync_state(&mut self, sync_data: &SyncState) -> bool {
         let mut own_chain_length = self.block_list.len();
         // let block_threads_vec = vec![];
         #[allow(unused_assignments)]
         let mut block_fetch_flag: bool = true;
-        let sync_data: SyncState = client_instance.fetch_sync_state(own_chain_length.clone());
         if sync_data.index == 0 {
             return false;
         }
@@ -322,18 +609,8 @@ where
                     let signed_block: &SignedBlock = &signed_block;
                     for each in signed_block.block.txn_pool.iter() {
                         if let Some(txn) = sync_data.txn_map.get(each) {
-                            match txn.header.get(&String::from("timestamp")) {
-                                Some(string) => {
-                                    match string.parse::<TxnPoolKeyType>() {
-                                        Ok(timestamp) => POOL.insert_op(&timestamp, &txn),
-                                        Err(error) => {
-                                            block_fetch_flag = false;
-                                            error!("transaction timestamp error {:?}", error);
-                                        }
-                                    };
-                                }
-                                None => block_fetch_flag = false,
-                            }
+                            // committed txns must be executed, pool admission limits don't apply
+                            POOL.insert_committed(&txn);
                         } else {
                             block_fetch_flag = false;
                         }
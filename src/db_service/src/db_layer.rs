@@ -1,46 +1,92 @@
-use exonum_merkledb::{Database, DbOptions, Fork, RocksDB, Snapshot};
-use utils::configreader;
-use utils::configreader::Configuration;
-
-fn create_db_instance() -> RocksDB {
-    let db_options: DbOptions = Default::default();
-    let config: &Configuration = &configreader::GLOBAL_CONFIG;
-    match RocksDB::open(&config.db.dbpath, &db_options) {
-        Ok(connection) => return connection,
-        Err(_) => panic!("can't able to create new db instance"),
-    };
-}
+use exonum_merkledb::{Database, DbOptions, Fork, RocksDB, Snapshot, TemporaryDB};
+use std::fmt;
+use std::sync::Arc;
+use utils::configreader::{Configuration, DBTYPE};
 
-lazy_static! {
-    pub static ref DB_INSTANCE: RocksDB = create_db_instance();
+/// DbInstance is a cheap-to-clone handle on the storage backend. Every node
+/// component gets its fork/snapshot/patch through a handle instead of reaching a
+/// process wide database. Txn pool (POOL) & loaded apps (APPDATA) are still process
+/// wide, nodes sharing a process would share them too.
+#[derive(Clone)]
+pub struct DbInstance {
+    db: Arc<dyn Database>,
 }
 
-pub fn fork_db() -> Fork {
-    DB_INSTANCE.fork()
-    // db.fork()
-}
+impl DbInstance {
+    /// this function will open a RocksDB backed instance at the given path
+    pub fn rocksdb(db_path: &String) -> DbInstance {
+        let db_options: DbOptions = Default::default();
+        match RocksDB::open(db_path, &db_options) {
+            Ok(connection) => DbInstance {
+                db: Arc::new(connection),
+            },
+            Err(_) => panic!("can't able to create new db instance"),
+        }
+    }
+
+    /// this function will create an in-memory instance, nothing is written on disk
+    pub fn temporary() -> DbInstance {
+        DbInstance {
+            db: Arc::new(TemporaryDB::new()),
+        }
+    }
+
+    /// this function will create instance as per the db section of node config
+    pub fn from_config(config: &Configuration) -> DbInstance {
+        match config.db.db_type {
+            DBTYPE::RocksDB => DbInstance::rocksdb(&config.db.dbpath),
+            DBTYPE::TemporaryDB => DbInstance::temporary(),
+        }
+    }
 
-pub fn snapshot_db() -> Box<dyn Snapshot> {
-    DB_INSTANCE.snapshot()
+    pub fn fork(&self) -> Fork {
+        self.db.fork()
+    }
+
+    pub fn snapshot(&self) -> Box<dyn Snapshot> {
+        self.db.snapshot()
+    }
+
+    pub fn patch(&self, fork: Fork) {
+        if let Err(error) = self.db.merge(fork.into_patch()) {
+            error!("error occurred in patch_db process {:?}", error);
+        }
+    }
 }
 
-pub fn patch_db(fork: Fork) {
-    if let Err(error) = DB_INSTANCE.merge(fork.into_patch()) {
-        error!("error occurred in patch_db process {:?}", error);
-        println!("error occurred in patch_db process {:?}", error);
+impl fmt::Debug for DbInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DbInstance")
     }
 }
 
 #[cfg(test)]
 mod test_db_layer {
     use super::*;
+    use exonum_merkledb::access::CopyAccessExt;
+    use utils::configreader;
     use utils::configreader::initialize_config;
 
     #[test]
     #[should_panic]
     fn test_create_db_instance() {
         initialize_config("../../config.toml");
-        fork_db();
-        create_db_instance();
+        let config: &Configuration = &configreader::GLOBAL_CONFIG;
+        let _db: DbInstance = DbInstance::from_config(config);
+        // rocksdb at the same path is locked by the first instance
+        DbInstance::from_config(config);
+    }
+
+    #[test]
+    fn test_temporary_db_instances() {
+        let first: DbInstance = DbInstance::temporary();
+        let second: DbInstance = DbInstance::temporary();
+        let fork: Fork = first.fork();
+        (&fork).get_list("test_list").push(1_u64);
+        first.patch(fork);
+        let snapshot: Box<dyn Snapshot> = first.snapshot();
+        assert_eq!((&snapshot).get_list::<_, u64>("test_list").len(), 1);
+        let snapshot: Box<dyn Snapshot> = second.clone().snapshot();
+        assert_eq!((&snapshot).get_list::<_, u64>("test_list").len(), 0);
     }
 }
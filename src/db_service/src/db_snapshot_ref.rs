@@ -5,18 +5,27 @@ use exonum_crypto::Hash;
 use exonum_derive::FromAccess;
 use exonum_merkledb::{
     access::{Access, FromAccess, RawAccess},
-    ListIndex, ObjectHash, ProofMapIndex,
+    Group, KeySetIndex, ListIndex, MapIndex, ObjectHash, ProofMapIndex,
 };
 use schema::block::SignedBlock;
+use schema::event::{Event, EventFilter};
+use schema::receipt::Receipt;
 use schema::signed_transaction::SignedTransaction;
 use schema::state::State;
+use sdk::traits::ReadStateContext;
 
 #[derive(FromAccess)]
 pub struct SchemaSnap<T: Access> {
     pub txn_trie: ProofMapIndex<T::Base, Hash, SignedTransaction>,
     block_list: ListIndex<T::Base, SignedBlock>,
     state_trie: ProofMapIndex<T::Base, String, State>,
-    storage_trie: ProofMapIndex<T::Base, Hash, SignedTransaction>,
+    storage_trie: ProofMapIndex<T::Base, String, Hash>,
+    receipt_trie: ProofMapIndex<T::Base, Hash, Receipt>,
+    state_keys: KeySetIndex<T::Base, String>,
+    account_storage: Group<T, String, ProofMapIndex<T::Base, String, Vec<u8>>>,
+    app_events: Group<T, String, ListIndex<T::Base, Event>>,
+    event_offsets: Group<T, String, MapIndex<T::Base, u64, u64>>,
+    event_apps: KeySetIndex<T::Base, String>,
 }
 
 impl<T: Access> SchemaSnap<T> {
@@ -49,10 +58,14 @@ where
         &self.state_trie
     }
 
-    pub fn storage(&self) -> &ProofMapIndex<T::Base, Hash, SignedTransaction> {
+    pub fn storage(&self) -> &ProofMapIndex<T::Base, String, Hash> {
         &self.storage_trie
     }
 
+    pub fn receipts(&self) -> &ProofMapIndex<T::Base, Hash, Receipt> {
+        &self.receipt_trie
+    }
+
     pub fn get_transaction_trie_hash(&self) -> Hash {
         self.txn_trie.object_hash()
     }
@@ -65,10 +78,18 @@ where
         self.storage_trie.object_hash()
     }
 
+    pub fn get_receipt_trie_hash(&self) -> Hash {
+        self.receipt_trie.object_hash()
+    }
+
     pub fn get_transaction(&self, hash: Hash) -> Option<SignedTransaction> {
         self.transactions().get(&hash)
     }
 
+    pub fn get_receipt(&self, hash: Hash) -> Option<Receipt> {
+        self.receipts().get(&hash)
+    }
+
     pub fn get_root_block(&self) -> Option<SignedBlock> {
         let length: u64 = self.get_blockchain_length();
         if length > 0 {
@@ -107,4 +128,65 @@ where
     pub fn get_state(&self, public_key: String) -> Option<State> {
         self.state().get(&public_key)
     }
+
+    /// events of given app matching the filter, in block order. Reading starts at the
+    /// first event of from_height, looked up in event_offsets.
+    pub fn get_events(&self, app_name: &String, filter: &EventFilter) -> Vec<Event> {
+        if !self.event_apps.contains(app_name) {
+            return Vec::new();
+        }
+        let first_event: u64 = match self
+            .event_offsets
+            .get(app_name)
+            .values_from(&filter.from_height)
+            .next()
+        {
+            Some(first_event) => first_event,
+            None => return Vec::new(),
+        };
+        self.app_events
+            .get(app_name)
+            .iter_from(first_event)
+            .take_while(|event| match filter.to_height {
+                Some(to_height) => event.get_block_height() <= to_height,
+                None => true,
+            })
+            .filter(|event| filter.matches(event))
+            .collect()
+    }
+}
+
+impl<T: Access> ReadStateContext for SchemaSnap<T>
+where
+    T::Base: RawAccess,
+{
+    fn get(&self, key: &String) -> Option<State> {
+        self.state_trie.get(key)
+    }
+    fn contains(&self, key: &String) -> bool {
+        self.state_trie.contains(key)
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        self.state_keys
+            .iter_from(prefix)
+            .take_while(|key| key.starts_with(prefix.as_str()))
+            .filter_map(|key| self.state_trie.get(&key).map(|state| (key, state)))
+            .collect()
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        self.state_keys
+            .iter_from(from)
+            .take_while(|key| key < to)
+            .filter_map(|key| self.state_trie.get(&key).map(|state| (key, state)))
+            .collect()
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.account_storage.get(account).get(key)
+    }
+    fn get_txn(&self, key: &Hash) -> Option<SignedTransaction> {
+        self.txn_trie.get(key)
+    }
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt> {
+        self.receipt_trie.get(key)
+    }
 }
@@ -1,10 +1,8 @@
-#[macro_use]
-extern crate lazy_static;
-
 #[macro_use]
 extern crate log;
 
 pub mod db_fork_ref;
 pub mod db_layer;
 pub mod db_snapshot_ref;
+pub mod pool_journal;
 pub mod tests;
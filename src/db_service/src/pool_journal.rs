@@ -0,0 +1,178 @@
+extern crate schema;
+
+use super::db_layer::DbInstance;
+use super::db_snapshot_ref::SchemaSnap;
+use exonum_crypto::Hash;
+use exonum_merkledb::{access::CopyAccessExt, Fork, MapIndex, ObjectHash, Snapshot};
+use schema::transaction_pool::{
+    JournalBatch, JournaledTxn, Pool, PoolJournal, TxnPool, TxnPoolKeyType,
+};
+
+/// name of the map index of node db which keeps pooled txns, it is not part of any state root
+pub const TXN_POOL_JOURNAL: &str = "txn_pool_journal";
+
+/// DbPoolJournal keeps pooled txns in a plain map index of node db
+#[derive(Debug, Clone)]
+pub struct DbPoolJournal {
+    db: DbInstance,
+}
+
+impl DbPoolJournal {
+    pub fn new(db: DbInstance) -> DbPoolJournal {
+        DbPoolJournal { db }
+    }
+
+    /// this function will reload journaled txns into the given pool. Txns which are already
+    /// committed in txn_trie, expired or rejected by the pool are dropped from journal.
+    /// Txns expire & are ordered by the time the pool first received them, not by their
+    /// timestamp header. It returns the number of restored txns.
+    pub fn restore(
+        &self,
+        pool: &Pool,
+        current_timestamp: TxnPoolKeyType,
+        delay_limit: u128,
+    ) -> usize {
+        let snapshot: Box<dyn Snapshot> = self.db.snapshot();
+        let schema = SchemaSnap::new(&snapshot);
+        let mut restored: usize = 0;
+        let mut dropped: JournalBatch = JournalBatch::new();
+        let mut journaled: Vec<JournaledTxn> = self.load();
+        journaled.sort_by_key(|journaled_txn| journaled_txn.received_timestamp);
+        for journaled_txn in journaled.iter() {
+            let hash: Hash = journaled_txn.txn.object_hash();
+            let expired: bool =
+                current_timestamp > journaled_txn.received_timestamp.saturating_add(delay_limit);
+            if expired || schema.txn_trie.contains(&hash) {
+                dropped.insert(hash, None);
+                continue;
+            }
+            match pool.restore_op(journaled_txn) {
+                Ok(()) => restored = restored + 1,
+                Err(reason) => {
+                    warn!("journaled txn {} dropped: {}", hash.to_hex(), reason);
+                    dropped.insert(hash, None);
+                }
+            }
+        }
+        if !dropped.is_empty() {
+            self.write(&dropped);
+        }
+        restored
+    }
+}
+
+impl PoolJournal for DbPoolJournal {
+    /// all writes of the batch go in a single fork & patch
+    fn write(&self, batch: &JournalBatch) {
+        let fork: Fork = self.db.fork();
+        {
+            let mut journal: MapIndex<_, Hash, JournaledTxn> = (&fork).get_map(TXN_POOL_JOURNAL);
+            for (hash, txn) in batch.iter() {
+                match txn {
+                    Some(txn) => journal.put(hash, txn.clone()),
+                    None => journal.remove(hash),
+                }
+            }
+        }
+        self.db.patch(fork);
+    }
+
+    fn load(&self) -> Vec<JournaledTxn> {
+        let snapshot: Box<dyn Snapshot> = self.db.snapshot();
+        let journal: MapIndex<_, Hash, JournaledTxn> = (&snapshot).get_map(TXN_POOL_JOURNAL);
+        journal.values().collect()
+    }
+}
+
+#[cfg(test)]
+mod test_pool_journal {
+    use super::*;
+    use crate::db_fork_ref::SchemaFork;
+    use schema::signed_transaction::SignedTransaction;
+    use sdk::traits::ChainStateContext;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    fn journal_txn(timestamp: u128, fxn_call: &str) -> SignedTransaction {
+        let mut header = HashMap::new();
+        header.insert(String::from("timestamp"), timestamp.to_string());
+        SignedTransaction {
+            txn: fxn_call.as_bytes().to_vec(),
+            app_name: String::from("journal_app"),
+            signature: vec![],
+            header,
+        }
+    }
+
+    #[test]
+    fn test_pool_journal_restore() {
+        let db: DbInstance = DbInstance::temporary();
+        let journal: DbPoolJournal = DbPoolJournal::new(db.clone());
+        let now: u128 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        let pending: SignedTransaction = journal_txn(now - 10_000, "pending");
+        let committed: SignedTransaction = journal_txn(now, "committed");
+
+        let pool: Pool = Pool::new();
+        pool.set_journal(Arc::new(journal.clone()));
+        for txn in [&pending, &committed].iter() {
+            assert_eq!(pool.insert_op(txn), Ok(()));
+        }
+        assert_eq!(journal.load().len(), 2);
+        // removal from pool is reflected in journal
+        pool.delete_txn_hash(&pending.object_hash());
+        assert_eq!(journal.load().len(), 1);
+        pool.insert_op(&pending).unwrap();
+        let received_timestamp: u128 = journal
+            .load()
+            .iter()
+            .find(|journaled_txn| journaled_txn.txn == pending)
+            .unwrap()
+            .received_timestamp;
+        assert!(received_timestamp >= now);
+
+        // txns received long ago expire even though their timestamp header is recent,
+        // one received a little while ago is restored
+        let expired: SignedTransaction = journal_txn(now, "expired");
+        let aged: SignedTransaction = journal_txn(now, "aged");
+        let mut batch: JournalBatch = JournalBatch::new();
+        for (txn, received_timestamp) in [(&expired, now - 10_000), (&aged, now - 4_000)].iter() {
+            batch.insert(
+                txn.object_hash(),
+                Some(JournaledTxn {
+                    txn: (*txn).clone(),
+                    received_timestamp: *received_timestamp,
+                }),
+            );
+        }
+        journal.write(&batch);
+
+        let fork: Fork = db.fork();
+        SchemaFork::new(&fork).put_txn(&committed.object_hash(), committed.clone());
+        db.patch(fork);
+
+        // node restart, a fresh pool is rebuilt from journal
+        let restarted_pool: Pool = Pool::new();
+        assert_eq!(journal.restore(&restarted_pool, now, 5_000), 2);
+        assert!(restarted_pool.get(&pending.object_hash()).is_some());
+        assert!(restarted_pool.get(&aged.object_hash()).is_some());
+        assert_eq!(restarted_pool.length_order_pool(), 2);
+        let mut journaled: Vec<SignedTransaction> = journal
+            .load()
+            .into_iter()
+            .map(|journaled_txn| journaled_txn.txn)
+            .collect();
+        journaled.sort_by_key(|txn| txn.object_hash());
+        let mut expected: Vec<SignedTransaction> = vec![pending.clone(), aged.clone()];
+        expected.sort_by_key(|txn| txn.object_hash());
+        assert_eq!(journaled, expected);
+
+        // restored txns keep their receive time, the aged one expires first
+        assert_eq!(restarted_pool.expire_txns(now + 2_000, 5_000), 1);
+        assert!(restarted_pool.get(&aged.object_hash()).is_none());
+        assert!(restarted_pool.get(&pending.object_hash()).is_some());
+    }
+}
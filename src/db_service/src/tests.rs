@@ -1,28 +1,37 @@
 #[cfg(test)]
 mod test_db_service {
     use crate::db_fork_ref::*;
-    use crate::db_layer::{fork_db, patch_db, snapshot_db};
+    use crate::db_layer::DbInstance;
     use crate::db_snapshot_ref::*;
     use exonum_crypto::Hash;
     use exonum_merkledb::{Fork, ObjectHash, Snapshot};
+    use schema::appdata::APPDATA;
     use schema::block::{Block, BlockTraits, SignedBlock};
+    use schema::event::{Event, EventFilter};
+    use schema::receipt::Receipt;
     use schema::signed_transaction::SignedTransaction;
     use schema::state::State;
-    use sdk::traits::StateContext;
+    use schema::transaction_pool::{config_chain_limits, TransactionPool, TransactionPoolTraits};
+    use sdk::metering::{ChainLimits, ExecutionLimits};
+    use sdk::namespace::namespaced_key;
+    use sdk::traits::{
+        AppHandler, BlockContext, ChainStateContext, ReadStateContext, StateContext,
+    };
     use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
     use std::time::SystemTime;
     use utils::configreader;
     use utils::configreader::BlockConfig;
     use utils::keypair::{CryptoKeypair, Keypair, KeypairType};
 
-    fn test_db_initialization_check() {
+    fn test_db_initialization_check(db: &DbInstance) {
         // reset_db_state
         let kp: KeypairType = Keypair::generate();
         let pk: String = hex::encode(kp.public().encode());
         let block: Block = Block::genesis_block(Vec::new(), 0);
         #[allow(unused_assignments)]
         let mut signed_block: SignedBlock = SignedBlock::create_block(block, vec![0], Vec::new());
-        let fork: Fork = fork_db();
+        let fork: Fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             signed_block = schema.initialize_db(Vec::new(), 0);
@@ -31,9 +40,9 @@ mod test_db_service {
                 false
             );
         }
-        patch_db(fork);
-        // not using patch_db so that we can update this block
-        let fork: Fork = fork_db();
+        db.patch(fork);
+        // not patching the fork so that we can update this block
+        let fork: Fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             assert_eq!(false, schema.update_block(&signed_block));
@@ -41,26 +50,75 @@ mod test_db_service {
 
         // check db state to check whether fork and patch are working using snapshot
         let empty_hash: Hash = signed_block.block.header[0].clone();
-        let snapshot: Box<dyn Snapshot> = snapshot_db();
+        let snapshot: Box<dyn Snapshot> = db.snapshot();
         {
             let schema = SchemaSnap::new(&snapshot);
             assert_eq!(schema.get_blockchain_length(), 1);
             assert_eq!(schema.get_state_trie_hash(), empty_hash);
             assert_eq!(schema.get_storage_trie_hash(), empty_hash);
             assert_eq!(schema.get_transaction_trie_hash(), empty_hash);
+            assert_eq!(schema.get_receipt_trie_hash(), empty_hash);
         }
     }
 
-    fn test_db_read_write_check() {
+    fn test_db_genesis_replay(db: &DbInstance) {
+        let genesis_block: SignedBlock = {
+            let fork: Fork = db.fork();
+            let mut schema = SchemaFork::new(&fork);
+            schema.initialize_db(Vec::new(), 0)
+        };
+        // peer syncing the chain replays genesis block on an empty db
+        let peer_db: DbInstance = DbInstance::temporary();
+        let fork: Fork = peer_db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        assert_eq!(schema.update_block(&genesis_block), true);
+        assert_eq!(
+            schema.state_trie_merkle_hash(),
+            genesis_block.block.header[0]
+        );
+        assert_eq!(ChainLimits::load(&schema), Some(config_chain_limits()));
+
+        // genesis states set by the engine are put on both sides
+        let genesis_key: String = namespaced_key("validator_set", "active");
+        let mut genesis_state: State = State::new();
+        genesis_state.set_data(&vec![1, 2, 3]);
+        let origin_db: DbInstance = DbInstance::temporary();
+        let genesis_block: SignedBlock = {
+            let fork: Fork = origin_db.fork();
+            let mut schema = SchemaFork::new(&fork);
+            schema.set_genesis_states(vec![(genesis_key.clone(), genesis_state.clone())]);
+            schema.initialize_db(Vec::new(), 0)
+        };
+        let peer_db: DbInstance = DbInstance::temporary();
+        let fork: Fork = peer_db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            schema.set_genesis_states(vec![(genesis_key.clone(), genesis_state.clone())]);
+            assert_eq!(schema.update_block(&genesis_block), true);
+            assert_eq!(
+                schema
+                    .get(&genesis_key)
+                    .map(|state| state.get_data().clone()),
+                Some(vec![1, 2, 3])
+            );
+        }
+        // peer whose engine starts genesis with other states can't replay it
+        let other_db: DbInstance = DbInstance::temporary();
+        let fork: Fork = other_db.fork();
+        let mut schema = SchemaFork::new(&fork);
+        assert_eq!(schema.update_block(&genesis_block), false);
+    }
+
+    fn test_db_read_write_check(db: &DbInstance) {
         // reset_db_state
         let kp: KeypairType = Keypair::generate();
-        let snapshot: Box<dyn Snapshot> = snapshot_db();
+        let snapshot: Box<dyn Snapshot> = db.snapshot();
         {
             let schema = SchemaSnap::new(&snapshot);
             if !schema.is_db_initialized() {}
         }
         // db is initialized create one block and verify it with snapshot
-        let fork: Fork = fork_db();
+        let fork: Fork = db.fork();
         let block: Block = Block::genesis_block(Vec::new(), 0);
         #[allow(unused_assignments)]
         let mut signed_block: SignedBlock = SignedBlock::create_block(block, vec![0], Vec::new());
@@ -68,8 +126,8 @@ mod test_db_service {
             let mut schema = SchemaFork::new(&fork);
             signed_block = schema.create_block(&kp, Vec::new());
         }
-        // not using patch_db so that we can update this block
-        let fork: Fork = fork_db();
+        // not patching the fork so that we can update this block
+        let fork: Fork = db.fork();
         #[allow(unused_assignments)]
         let mut update_flag: bool = false;
         {
@@ -78,20 +136,20 @@ mod test_db_service {
         }
         assert_eq!(update_flag, true);
         if update_flag {
-            patch_db(fork);
+            db.patch(fork);
         }
         // check db state to check whether fork and patch are working using snapshot
-        let snapshot: Box<dyn Snapshot> = snapshot_db();
+        let snapshot: Box<dyn Snapshot> = db.snapshot();
         {
             let schema = SchemaSnap::new(&snapshot);
             assert_eq!(schema.get_root_block_hash(), signed_block.get_hash());
         }
     }
 
-    fn test_db_state_context() {
+    fn test_db_state_context(db: &DbInstance) {
         let kp: KeypairType = Keypair::generate();
         let pk: String = hex::encode(kp.public().encode());
-        let fork: Fork = fork_db();
+        let fork: Fork = db.fork();
         let state: State = State::new();
         let txn: SignedTransaction = SignedTransaction {
             txn: vec![0],
@@ -100,14 +158,16 @@ mod test_db_service {
             signature: vec![0],
         };
         let txn_hash: Hash = txn.object_hash();
+        let receipt: Receipt = Receipt::new(txn_hash, &Ok(()), 1, 0);
         {
             let mut schema = SchemaFork::new(&fork);
             // let state_context = schema as &mut dyn StateContext;
             schema.put(&pk, state.clone());
             schema.put_txn(&txn_hash, txn.clone());
+            schema.put_receipt(&txn_hash, receipt.clone());
         }
-        patch_db(fork);
-        let fork: Fork = fork_db();
+        db.patch(fork);
+        let fork: Fork = db.fork();
         {
             let schema = SchemaFork::new(&fork);
             let is_contains: bool = schema.contains(&pk);
@@ -119,22 +179,83 @@ mod test_db_service {
             println!("{:?}", is_contains);
             assert_eq!(is_contains, true);
             assert_eq!(schema.get_txn(&txn_hash).unwrap(), txn);
+            assert_eq!(schema.get_receipt(&txn_hash).unwrap(), receipt);
+        }
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            for key in ["iter_app/b", "iter_app/a", "iter_apq/c", "iter_app/c"].iter() {
+                schema.put(&key.to_string(), state.clone());
+            }
+            let keys: Vec<String> = schema
+                .iter_prefix(&String::from("iter_app/"))
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect();
+            assert_eq!(keys, vec!["iter_app/a", "iter_app/b", "iter_app/c"]);
+            schema.delete(&String::from("iter_app/b"));
+            assert_eq!(schema.contains(&String::from("iter_app/b")), false);
+            let ranged: Vec<(String, State)> =
+                schema.range(&String::from("iter_app/"), &String::from("iter_apq/z"));
+            assert_eq!(ranged.len(), 3);
+        }
+        db.patch(fork);
+        // contract storage writes are committed in account state & storage_trie
+        let fork: Fork = db.fork();
+        let storage_key: String = String::from("counter");
+        {
+            let mut schema = SchemaFork::new(&fork);
+            let storage_hash: Hash = schema.storage_trie_merkle_hash();
+            schema.put_storage(&pk, &storage_key, vec![1]);
+            let storage_root: Hash = schema.get(&pk).unwrap().get_storage_root();
+            assert_ne!(storage_root, Hash::zero());
+            assert_ne!(schema.storage_trie_merkle_hash(), storage_hash);
+            assert_eq!(schema.get_storage(&pk, &storage_key), Some(vec![1]));
+            schema.put_storage(&pk, &String::from("owner"), vec![2]);
+            schema.delete_storage(&pk, &String::from("owner"));
+            assert_eq!(schema.get(&pk).unwrap().get_storage_root(), storage_root);
+            // a state put with stale or forged storage root keeps the committed one
+            let mut stale_state: State = schema.get(&pk).unwrap();
+            stale_state.set_storage_root(Hash::zero());
+            schema.put(&pk, stale_state);
+            assert_eq!(schema.get(&pk).unwrap().get_storage_root(), storage_root);
+            // storage of an account without state doesn't create a state
+            let absent: String = String::from("absent_account");
+            let storage_hash: Hash = schema.storage_trie_merkle_hash();
+            schema.put_storage(&absent, &storage_key, vec![1]);
+            assert!(!schema.contains(&absent));
+            schema.delete_storage(&absent, &storage_key);
+            assert_eq!(schema.storage_trie_merkle_hash(), storage_hash);
+            // deleted state takes its storage along
+            let deleted: String = String::from("deleted_account");
+            schema.put(&deleted, State::new());
+            schema.put_storage(&deleted, &storage_key, vec![1]);
+            schema.delete(&deleted);
+            assert_eq!(schema.get_storage(&deleted, &storage_key), None);
+            assert_eq!(schema.storage_trie_merkle_hash(), storage_hash);
+        }
+        db.patch(fork);
+        let snapshot: Box<dyn Snapshot> = db.snapshot();
+        {
+            let schema = SchemaSnap::new(&snapshot);
+            assert_eq!(schema.get_storage(&pk, &storage_key), Some(vec![1]));
+            assert_eq!(schema.get_storage(&pk, &String::from("owner")), None);
         }
     }
 
-    fn test_db_sync_state() {
+    fn test_db_sync_state(db: &DbInstance) {
         // since it is unit test case sync-state should return zero-state not error
-        let fork: Fork = fork_db();
+        let fork: Fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             assert_eq!(schema.sync_state(), false);
         }
     }
 
-    fn test_failed_scenarios() {
+    fn test_failed_scenarios(db: &DbInstance) {
         let kp: KeypairType = Keypair::generate();
         // db is initialized create one block and verify it with snapshot
-        let fork: Fork = fork_db();
+        let fork: Fork = db.fork();
         let block: Block = Block::genesis_block(Vec::new(), 0);
         #[allow(unused_assignments)]
         let mut signed_block: SignedBlock = SignedBlock::create_block(block, vec![0], Vec::new());
@@ -144,7 +265,12 @@ mod test_db_service {
         }
         // signature error
         let mut wrong_block: SignedBlock = signed_block.clone();
-        let fork: Fork = fork_db();
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            wrong_block.block.header[3] = Hash::zero();
+            assert_eq!(false, schema.update_block(&wrong_block));
+        }
         {
             let mut schema = SchemaFork::new(&fork);
             wrong_block.block.header[2] = Hash::zero();
@@ -179,9 +305,9 @@ mod test_db_service {
             let mut schema = SchemaFork::new(&fork);
             assert_eq!(true, schema.update_block(&signed_block));
         }
-        patch_db(fork);
+        db.patch(fork);
         // check db state to check whether fork and patch are working using snapshot
-        let snapshot: Box<dyn Snapshot> = snapshot_db();
+        let snapshot: Box<dyn Snapshot> = db.snapshot();
         {
             let schema = SchemaSnap::new(&snapshot);
             assert_eq!(
@@ -191,10 +317,273 @@ mod test_db_service {
         }
     }
 
-    fn test_block_creation_config() {
+    /// HookApp records the height every hook ran at in its namespace, end_block fails
+    /// after recording
+    struct HookApp {}
+
+    impl HookApp {
+        fn record(state_context: &mut dyn StateContext, hook: &str, block: &BlockContext) {
+            let mut state: State = State::new();
+            state.set_data(&block.height.to_be_bytes().to_vec());
+            state_context.put(&String::from(hook), state);
+        }
+    }
+
+    impl AppHandler for HookApp {
+        fn execute(
+            &self,
+            _txn: &SignedTransaction,
+            _state_context: &mut dyn StateContext,
+        ) -> Result<(), String> {
+            Ok(())
+        }
+        fn check_transaction(&self, _txn: &SignedTransaction) -> Result<(), String> {
+            Ok(())
+        }
+        fn sender(&self, _txn: &SignedTransaction) -> Option<String> {
+            None
+        }
+        fn nonce(&self, _txn: &SignedTransaction) -> Option<u64> {
+            None
+        }
+        fn next_nonce(&self, _sender: &String, _state_context: &dyn StateContext) -> Option<u64> {
+            None
+        }
+        fn fee(&self, _txn: &SignedTransaction) -> u64 {
+            0
+        }
+        fn query(
+            &self,
+            _path: &String,
+            _params: &HashMap<String, String>,
+            _state_context: &dyn ReadStateContext,
+        ) -> Result<Vec<u8>, String> {
+            Err(String::from("no queries"))
+        }
+        fn init_genesis(
+            &self,
+            block: &BlockContext,
+            state_context: &mut dyn StateContext,
+        ) -> Result<(), String> {
+            HookApp::record(state_context, "init_genesis", block);
+            Ok(())
+        }
+        fn begin_block(
+            &self,
+            block: &BlockContext,
+            state_context: &mut dyn StateContext,
+        ) -> Result<(), String> {
+            HookApp::record(state_context, "begin_block", block);
+            Ok(())
+        }
+        fn end_block(
+            &self,
+            block: &BlockContext,
+            state_context: &mut dyn StateContext,
+        ) -> Result<(), String> {
+            HookApp::record(state_context, "end_block", block);
+            Err(format!("end_block failed at {}", block.height))
+        }
+        fn name(&self) -> String {
+            String::from("hook_app")
+        }
+    }
+
+    fn test_db_block_hooks(db: &DbInstance) {
+        let kp: KeypairType = Keypair::generate();
+        let app: Box<dyn AppHandler + Send> = Box::new(HookApp {});
+        APPDATA
+            .lock()
+            .unwrap()
+            .appdata
+            .insert(app.name(), Arc::new(Mutex::new(app)));
+        let hook_height = |schema: &SchemaFork<&Fork>, hook: &str| -> Option<Vec<u8>> {
+            schema
+                .get(&namespaced_key("hook_app", hook))
+                .map(|state| state.get_data().clone())
+        };
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            let genesis_block: SignedBlock = schema.initialize_db(Vec::new(), 0);
+            assert_eq!(ChainLimits::load(&schema), Some(config_chain_limits()));
+            assert_eq!(
+                hook_height(&schema, "init_genesis"),
+                Some(0_u64.to_be_bytes().to_vec())
+            );
+            assert_eq!(
+                genesis_block.block.header[0],
+                schema.state_trie_merkle_hash()
+            );
+        }
+        db.patch(fork);
+        let fork: Fork = db.fork();
+        #[allow(unused_assignments)]
+        let mut signed_block: SignedBlock =
+            SignedBlock::create_block(Block::genesis_block(Vec::new(), 0), vec![0], Vec::new());
+        {
+            let mut schema = SchemaFork::new(&fork);
+            signed_block = schema.create_block(&kp, Vec::new());
+            assert_eq!(
+                hook_height(&schema, "begin_block"),
+                Some(1_u64.to_be_bytes().to_vec())
+            );
+            // writes of the failed end_block hook are rolled back
+            assert_eq!(hook_height(&schema, "end_block"), None);
+            assert_eq!(
+                signed_block.block.header[0],
+                schema.state_trie_merkle_hash()
+            );
+        }
+        // validator re-runs hooks and reaches the same header roots
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            assert_eq!(true, schema.update_block(&signed_block));
+        }
+        db.patch(fork);
+        APPDATA.lock().unwrap().appdata.remove("hook_app");
+    }
+
+    /// RollbackApp writes state for every txn, then fails txns with empty payload
+    struct RollbackApp {}
+
+    impl AppHandler for RollbackApp {
+        fn execute(
+            &self,
+            txn: &SignedTransaction,
+            state_context: &mut dyn StateContext,
+        ) -> Result<(), String> {
+            let mut state: State = State::new();
+            state.set_data(&txn.txn);
+            state_context.put(&format!("txn_{}", txn.txn.len()), state);
+            state_context.emit_event(&String::from("written"), txn.txn.clone());
+            if txn.txn.is_empty() {
+                return Err(String::from("empty payload"));
+            }
+            Ok(())
+        }
+        fn check_transaction(&self, _txn: &SignedTransaction) -> Result<(), String> {
+            Ok(())
+        }
+        fn sender(&self, _txn: &SignedTransaction) -> Option<String> {
+            None
+        }
+        fn nonce(&self, _txn: &SignedTransaction) -> Option<u64> {
+            None
+        }
+        fn next_nonce(&self, _sender: &String, _state_context: &dyn StateContext) -> Option<u64> {
+            None
+        }
+        fn fee(&self, _txn: &SignedTransaction) -> u64 {
+            0
+        }
+        fn query(
+            &self,
+            _path: &String,
+            _params: &HashMap<String, String>,
+            _state_context: &dyn ReadStateContext,
+        ) -> Result<Vec<u8>, String> {
+            Err(String::from("no queries"))
+        }
+        fn name(&self) -> String {
+            String::from("rollback_app")
+        }
+    }
+
+    fn test_db_failed_txn_rollback(db: &DbInstance) {
+        let app: Box<dyn AppHandler + Send> = Box::new(RollbackApp {});
+        APPDATA
+            .lock()
+            .unwrap()
+            .appdata
+            .insert(app.name(), Arc::new(Mutex::new(app)));
+        let timestamp: u128 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros()
+            - 1000;
+        let rollback_txn = |payload: Vec<u8>| -> SignedTransaction {
+            let mut header = HashMap::new();
+            header.insert(String::from("timestamp"), timestamp.to_string());
+            SignedTransaction {
+                txn: payload,
+                app_name: String::from("rollback_app"),
+                header,
+                signature: vec![],
+            }
+        };
+        let failing: SignedTransaction = rollback_txn(vec![]);
+        let passing: SignedTransaction = rollback_txn(vec![1]);
+        let mut txn_pool: TransactionPool = TransactionPool::new();
+        txn_pool.insert_op(&failing).unwrap();
+        txn_pool.insert_op(&passing).unwrap();
+        let block_producer: String = String::from("block_producer");
+
+        let fork: Fork = db.fork();
+        let (executed_txns, roots) = {
+            let mut schema = SchemaFork::new(&fork);
+            let executed_txns: Vec<Hash> =
+                schema.execute_transactions(&mut txn_pool, &block_producer);
+            assert_eq!(executed_txns.len(), 2);
+            // writes of the failed txn are discarded, the txn & its receipt are recorded
+            assert!(!schema.contains(&namespaced_key("rollback_app", "txn_0")));
+            assert!(schema.contains(&namespaced_key("rollback_app", "txn_1")));
+            assert!(schema.contains_txn(&failing.object_hash()));
+            let receipt: Receipt = schema.get_receipt(&failing.object_hash()).unwrap();
+            assert!(!receipt.is_success());
+            assert_eq!(receipt.get_error(), "empty payload");
+            assert!(receipt.get_events().is_empty());
+            let receipt: Receipt = schema.get_receipt(&passing.object_hash()).unwrap();
+            assert_eq!(receipt.get_events().len(), 1);
+            (
+                executed_txns,
+                [
+                    schema.state_trie_merkle_hash(),
+                    schema.txn_trie_merkle_hash(),
+                    schema.receipt_trie_merkle_hash(),
+                ],
+            )
+        };
+        // validator replays the block txns and reaches the same roots
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            assert!(schema.update_transactions(&txn_pool, &executed_txns, &block_producer));
+            assert_eq!(
+                roots,
+                [
+                    schema.state_trie_merkle_hash(),
+                    schema.txn_trie_merkle_hash(),
+                    schema.receipt_trie_merkle_hash(),
+                ]
+            );
+        }
+        // chain limits kept in state apply whatever node config says, payload is charged
+        let oversized: SignedTransaction = rollback_txn(vec![1; 8]);
+        let mut txn_pool: TransactionPool = TransactionPool::new();
+        txn_pool.insert_op(&oversized).unwrap();
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            let mut chain_limits: ChainLimits = config_chain_limits();
+            chain_limits.txn_limits = ExecutionLimits::new(10, 10, 4);
+            chain_limits.store(&mut schema);
+            schema.execute_transactions(&mut txn_pool, &block_producer);
+            let receipt: Receipt = schema.get_receipt(&oversized.object_hash()).unwrap();
+            assert_eq!(
+                receipt.get_error(),
+                "execution aborted, state bytes exceed limit 4"
+            );
+            assert!(!schema.contains(&namespaced_key("rollback_app", "txn_8")));
+        }
+        APPDATA.lock().unwrap().appdata.remove("rollback_app");
+    }
+
+    fn test_block_creation_config(db: &DbInstance) {
         let kp: KeypairType = Keypair::generate();
         let block_config: &BlockConfig = &configreader::GLOBAL_CONFIG.block_config;
-        let fork = fork_db();
+        let fork = db.fork();
         {
             let schema = SchemaFork::new(&fork);
             let mut timestamp: u128 = SystemTime::now()
@@ -202,7 +591,7 @@ mod test_db_service {
                 .unwrap()
                 .as_micros();
             timestamp = timestamp + block_config.block_creation_time_limit;
-            let (_fork_instance, _signed_block) = schema.forge_new_block(&kp, Vec::new());
+            let (_fork_instance, _signed_block) = schema.forge_new_block(db, &kp, Vec::new());
             let current_timestamp: u128 = SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap()
@@ -233,14 +622,98 @@ mod test_db_service {
         }
     }
 
+    fn test_db_replace_root_auth_headers(db: &DbInstance) {
+        let kp: KeypairType = Keypair::generate();
+        let (signed_block, child_block): (SignedBlock, SignedBlock) = {
+            let fork: Fork = db.fork();
+            let mut schema = SchemaFork::new(&fork);
+            let signed_block: SignedBlock = schema.create_block(&kp, Vec::new());
+            (signed_block, schema.create_block(&kp, Vec::new()))
+        };
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            assert_eq!(schema.update_block(&signed_block), true);
+            let length: u64 = schema.blockchain_length();
+            assert_eq!(schema.replace_root_auth_headers(vec![1, 2, 3]), true);
+            assert_eq!(schema.blockchain_length(), length);
+        }
+        // not patched, shared db stays as is
+        {
+            let schema = SchemaSnap::new(&fork);
+            let root_block: SignedBlock = schema.get_root_block().unwrap();
+            assert_eq!(root_block.block, signed_block.block);
+            assert_eq!(root_block.auth_headers, vec![1, 2, 3]);
+            assert_ne!(root_block.get_hash(), signed_block.get_hash());
+        }
+        // child was built on the block with its former auth headers
+        let mut schema = SchemaFork::new(&fork);
+        assert_eq!(schema.update_block(&child_block), false);
+        assert_eq!(
+            schema.replace_root_auth_headers(signed_block.auth_headers.clone()),
+            true
+        );
+        assert_eq!(schema.update_block(&child_block), true);
+    }
+
+    fn test_db_event_index(db: &DbInstance) {
+        let event_heights = |events: Vec<Event>| -> Vec<u64> {
+            events
+                .iter()
+                .map(|event| event.get_block_height())
+                .collect()
+        };
+        let fork: Fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            for (position, block_height) in [1, 1, 3, 7].iter().enumerate() {
+                let txn_hash: Hash = exonum_crypto::hash(&[position as u8]);
+                let mut receipt: Receipt = Receipt::new(txn_hash, &Ok(()), *block_height, 0);
+                receipt.set_events(vec![Event::new(
+                    String::from("event_app"),
+                    String::from("topic"),
+                    vec![],
+                    txn_hash,
+                    *block_height,
+                    0,
+                )]);
+                schema.put_receipt(&txn_hash, receipt);
+            }
+        }
+        let schema = SchemaSnap::new(&fork);
+        let app_name: String = String::from("event_app");
+        let mut filter: EventFilter = EventFilter::default();
+        assert_eq!(
+            event_heights(schema.get_events(&app_name, &filter)),
+            vec![1, 1, 3, 7]
+        );
+        filter.from_height = 2;
+        assert_eq!(
+            event_heights(schema.get_events(&app_name, &filter)),
+            vec![3, 7]
+        );
+        filter.from_height = 4;
+        filter.to_height = Some(6);
+        assert!(schema.get_events(&app_name, &filter).is_empty());
+        filter.from_height = 8;
+        filter.to_height = None;
+        assert!(schema.get_events(&app_name, &filter).is_empty());
+    }
+
     #[test]
     fn test_db_services_checks() {
         std::thread::sleep(std::time::Duration::from_millis(100));
-        test_db_initialization_check();
-        test_db_read_write_check();
-        test_db_state_context();
-        test_db_sync_state();
-        test_failed_scenarios();
-        test_block_creation_config();
+        let db: DbInstance = DbInstance::temporary();
+        test_db_initialization_check(&db);
+        test_db_genesis_replay(&db);
+        test_db_read_write_check(&db);
+        test_db_state_context(&db);
+        test_db_block_hooks(&db);
+        test_db_failed_txn_rollback(&db);
+        test_db_replace_root_auth_headers(&db);
+        test_db_event_index(&db);
+        test_db_sync_state(&db);
+        test_failed_scenarios(&db);
+        test_block_creation_config(&db);
     }
 }
@@ -0,0 +1,9 @@
+//! governance is the built-in app through which current validators vote to add or remove
+//! validators. A change which got majority is applied at a deterministic future height and
+//! consensus engines read the active set from chain state, so no node has to be restarted.
+
+#[macro_use]
+extern crate log;
+
+pub mod state;
+pub mod transaction;
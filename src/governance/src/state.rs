@@ -0,0 +1,156 @@
+use sdk::namespace::namespaced_key;
+use sdk::state::State;
+use sdk::traits::{ReadStateContext, StateContext};
+use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
+
+/// name of the governance app, its state lives under "validator_set/" keys
+pub const APPNAME: &str = "validator_set";
+/// blocks between the block in which a change got majority and the block applying it,
+/// it is kept above unfinalized block queues of engines so every validator switches
+/// set at same height
+pub const ACTIVATION_DELAY: u64 = 10;
+
+// keys in app namespace
+pub(crate) const ACTIVE_KEY: &str = "active";
+pub(crate) const PASSED_KEY: &str = "passed";
+pub(crate) const SCHEDULED_KEY: &str = "scheduled";
+pub(crate) const PROPOSAL_PREFIX: &str = "proposal/";
+pub(crate) const NONCE_PREFIX: &str = "nonce/";
+
+/// change of validator set, validators are identified by hex encoded public key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValidatorChange {
+    Add(String),
+    Remove(String),
+}
+
+impl ValidatorChange {
+    /// key under which votes on this change are collected
+    pub(crate) fn proposal_key(&self) -> String {
+        match self {
+            ValidatorChange::Add(validator) => format!("{}add/{}", PROPOSAL_PREFIX, validator),
+            ValidatorChange::Remove(validator) => {
+                format!("{}remove/{}", PROPOSAL_PREFIX, validator)
+            }
+        }
+    }
+
+    /// this function will apply the change on given set, Err if the change is a no-op
+    /// or would leave no validator
+    pub fn apply(&self, validators: &mut Vec<String>) -> Result<(), String> {
+        match self {
+            ValidatorChange::Add(validator) => {
+                if validators.contains(validator) {
+                    return Err(format!("{} is already a validator", validator));
+                }
+                validators.push(validator.clone());
+            }
+            ValidatorChange::Remove(validator) => {
+                let index: usize = match validators.iter().position(|each| each == validator) {
+                    Some(index) => index,
+                    None => return Err(format!("{} is not a validator", validator)),
+                };
+                if validators.len() == 1 {
+                    return Err(String::from("last validator can't be removed"));
+                }
+                validators.remove(index);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// change which got majority, applied while block at activation_height is executed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledChange {
+    pub change: ValidatorChange,
+    pub activation_height: u64,
+}
+
+/// votes of current validators on an open change
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Proposal {
+    pub votes: Vec<String>,
+}
+
+/// this function will read value of key in app namespace, None if it is absent
+pub(crate) fn get_value<T>(state_context: &dyn StateContext, key: &str) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let state: State = state_context.get(&String::from(key))?;
+    match deserialize(state.get_data().as_slice()) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            error!("{} state of {} couldn't deserialize", key, APPNAME);
+            None
+        }
+    }
+}
+
+/// this function will write value of key in app namespace
+pub(crate) fn put_value<T: Serialize>(
+    state_context: &mut dyn StateContext,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    let mut state: State = State::new();
+    match serialize(value) {
+        Ok(data) => state.set_data(&data),
+        Err(_) => return Err(format!("{} state couldn't serialize", key)),
+    }
+    state_context.put(&String::from(key), state);
+    Ok(())
+}
+
+/// read-only counterpart of get_value, key is taken as it is
+pub(crate) fn read_value<T>(state_context: &dyn ReadStateContext, key: &str) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let state: State = state_context.get(&String::from(key))?;
+    deserialize(state.get_data().as_slice()).ok()
+}
+
+/// this function will build the active set state of given validators of consensus config,
+/// engines reading validators from chain state start their genesis block with it. The set
+/// is kept in given order, engines derive author order from it. Err if there is none
+pub fn genesis_validator_state(validators: Vec<String>) -> Result<(String, State), String> {
+    if validators.is_empty() {
+        return Err(String::from("genesis validator set is empty"));
+    }
+    let mut state: State = State::new();
+    match serialize(&validators) {
+        Ok(data) => state.set_data(&data),
+        Err(_) => return Err(format!("{} state couldn't serialize", ACTIVE_KEY)),
+    }
+    Ok((namespaced_key(APPNAME, ACTIVE_KEY), state))
+}
+
+/// active validators as per given chain state in the order they joined, engines derive
+/// author order from it. None if the set wasn't seeded at genesis.
+pub fn active_validators(state_context: &dyn ReadStateContext) -> Option<Vec<String>> {
+    read_value(state_context, &namespaced_key(APPNAME, ACTIVE_KEY))
+}
+
+/// changes waiting for their activation height as per given chain state
+pub fn scheduled_changes(state_context: &dyn ReadStateContext) -> Vec<ScheduledChange> {
+    read_value(state_context, &namespaced_key(APPNAME, SCHEDULED_KEY)).unwrap_or_default()
+}
+
+/// validators of block at given height as per given chain state in author order, changes
+/// scheduled below that height are applied on top of active set. Heights up to
+/// ACTIVATION_DELAY above the state are resolved exactly, heights at or below it get active
+/// set. None if the set wasn't seeded at genesis.
+pub fn validators_at(state_context: &dyn ReadStateContext, height: u64) -> Option<Vec<String>> {
+    let mut validators: Vec<String> = active_validators(state_context)?;
+    // end_block applies a change while block at its activation height is executed
+    for scheduled in scheduled_changes(state_context) {
+        if scheduled.activation_height < height {
+            if let Err(reason) = scheduled.change.apply(&mut validators) {
+                debug!("{:?} skipped: {}", scheduled.change, reason);
+            }
+        }
+    }
+    Some(validators)
+}
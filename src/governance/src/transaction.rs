@@ -0,0 +1,493 @@
+use super::state::{
+    get_value, put_value, read_value, Proposal, ScheduledChange, ValidatorChange, ACTIVATION_DELAY,
+    ACTIVE_KEY, APPNAME, NONCE_PREFIX, PASSED_KEY, SCHEDULED_KEY,
+};
+use exonum_crypto::Hash;
+use exonum_merkledb::ObjectHash;
+use sdk::dispatch::NoPayload;
+pub use sdk::signed_transaction::SignedTransaction;
+use sdk::traits::{AppHandler, BlockContext, ReadStateContext, StateContext};
+use std::collections::HashMap;
+use utils::keypair::{CryptoKeypair, Keypair, KeypairType, PublicKey, Verify};
+use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
+
+/// vote of a current validator on a validator set change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSetTransaction {
+    pub nonce: u64,
+    // voting validator
+    pub from: String,
+    // "add_validator" or "remove_validator"
+    pub fxn_call: String,
+    // validator to be added or removed
+    pub validator: String,
+}
+
+pub trait TransactionTrait {
+    fn validate(&self) -> bool;
+    fn sign(&self, kp: &KeypairType) -> Vec<u8>;
+    fn get_hash(&self) -> Hash;
+}
+
+impl TransactionTrait for SignedTransaction {
+    fn validate(&self) -> bool {
+        let txn: ValidatorSetTransaction = match deserialize(&self.txn) {
+            Result::Ok(value) => value,
+            Result::Err(_) => return false,
+        };
+        PublicKey::verify_from_encoded_pk(&txn.from, &self.signing_bytes(), &self.signature)
+    }
+
+    fn sign(&self, kp: &KeypairType) -> Vec<u8> {
+        Keypair::sign(kp, &self.signing_bytes())
+    }
+
+    fn get_hash(&self) -> Hash {
+        self.object_hash()
+    }
+}
+
+sdk::app_calls! {
+    /// typed calls of governance app, every call is a vote of its signer
+    pub enum ValidatorSetCall: NoPayload => pub trait GovernanceTraits {
+        AddValidator => fn add_validator();
+        RemoveValidator => fn remove_validator();
+    }
+}
+
+impl GovernanceTraits for ValidatorSetTransaction {
+    fn add_validator(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        self.vote(state_context, ValidatorChange::Add(self.validator.clone()))
+    }
+
+    fn remove_validator(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        self.vote(
+            state_context,
+            ValidatorChange::Remove(self.validator.clone()),
+        )
+    }
+}
+
+impl ValidatorSetTransaction {
+    fn vote(
+        &self,
+        state_context: &mut dyn StateContext,
+        change: ValidatorChange,
+    ) -> Result<(), String> {
+        let result: Result<(), String> = self.record_vote(state_context, change);
+        if let Err(reason) = &result {
+            info!(
+                "{} vote of {} rejected: {}",
+                self.fxn_call, self.from, reason
+            );
+        }
+        result
+    }
+
+    // fn will record vote of the signer, the change passes once more than half of active
+    // validators voted for it and gets scheduled in end_block of this block
+    fn record_vote(
+        &self,
+        state_context: &mut dyn StateContext,
+        change: ValidatorChange,
+    ) -> Result<(), String> {
+        let validators: Vec<String> = match get_value(state_context, ACTIVE_KEY) {
+            Some(validators) => validators,
+            None => return Err(String::from("validator set isn't seeded at genesis")),
+        };
+        if !validators.contains(&self.from) {
+            return Err(format!("{} is not a validator", self.from));
+        }
+        let nonce_key: String = format!("{}{}", NONCE_PREFIX, self.from);
+        let nonce: u64 = get_value(state_context, &nonce_key).unwrap_or(0);
+        if self.nonce != nonce + 1 {
+            return Err(format!("nonce mismatched {:?} {:?}", self.nonce, nonce + 1));
+        }
+        // change is checked against the set it will be applied to
+        let scheduled: Vec<ScheduledChange> =
+            get_value(state_context, SCHEDULED_KEY).unwrap_or_default();
+        let mut passed: Vec<ValidatorChange> =
+            get_value(state_context, PASSED_KEY).unwrap_or_default();
+        let mut next_validators: Vec<String> = validators.clone();
+        for pending in scheduled
+            .iter()
+            .map(|each| &each.change)
+            .chain(passed.iter())
+        {
+            if *pending == change {
+                return Err(String::from("change is already scheduled"));
+            }
+            let _ = pending.apply(&mut next_validators);
+        }
+        change.apply(&mut next_validators)?;
+
+        let proposal_key: String = change.proposal_key();
+        let mut proposal: Proposal = get_value(state_context, &proposal_key).unwrap_or_default();
+        // votes of validators removed meanwhile don't count
+        proposal.votes.retain(|voter| validators.contains(voter));
+        if proposal.votes.contains(&self.from) {
+            return Err(String::from("validator already voted"));
+        }
+        proposal.votes.push(self.from.clone());
+        put_value(state_context, &nonce_key, &self.nonce)?;
+        if proposal.votes.len() * 2 > validators.len() {
+            info!("{:?} got majority of validators", change);
+            state_context.delete(&proposal_key);
+            passed.push(change);
+            put_value(state_context, PASSED_KEY, &passed)
+        } else {
+            put_value(state_context, &proposal_key, &proposal)
+        }
+    }
+}
+
+/// ValidatorSetApp is the built-in governance app, the node registers it ahead of
+/// configured apps
+pub struct ValidatorSetApp {
+    name: String,
+}
+
+impl ValidatorSetApp {
+    pub fn new() -> ValidatorSetApp {
+        ValidatorSetApp {
+            name: String::from(APPNAME),
+        }
+    }
+}
+
+impl AppHandler for ValidatorSetApp {
+    fn execute(
+        &self,
+        txn: &SignedTransaction,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        let mut result: Result<(), String> =
+            Err(String::from("transaction signature couldn't verified"));
+        if txn.validate() {
+            let validator_txn: ValidatorSetTransaction = match deserialize(&txn.txn) {
+                Result::Ok(value) => value,
+                Result::Err(_) => return Err(String::from("transaction couldn't deserialize")),
+            };
+            result = match ValidatorSetCall::decode(&validator_txn.fxn_call, &[]) {
+                Ok(call) => call.dispatch(&validator_txn, state_context),
+                Err(reason) => Err(reason),
+            };
+        }
+        result
+    }
+
+    fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String> {
+        if txn.app_name != APPNAME {
+            return Err(format!("transaction addressed to app {}", txn.app_name));
+        }
+        let validator_txn: ValidatorSetTransaction = match deserialize(&txn.txn) {
+            Result::Ok(value) => value,
+            Result::Err(_) => return Err(String::from("transaction couldn't deserialize")),
+        };
+        if !txn.validate() {
+            return Err(String::from("transaction signature couldn't verified"));
+        }
+        match hex::decode(&validator_txn.validator) {
+            Ok(public_key) if public_key.len() == 32 => {}
+            _ => return Err(String::from("validator must be hex encoded public key")),
+        }
+        ValidatorSetCall::decode(&validator_txn.fxn_call, &[]).map(|_| ())
+    }
+
+    fn sender(&self, txn: &SignedTransaction) -> Option<String> {
+        match deserialize::<ValidatorSetTransaction>(&txn.txn) {
+            Result::Ok(value) => Some(value.from),
+            Result::Err(_) => None,
+        }
+    }
+
+    fn nonce(&self, txn: &SignedTransaction) -> Option<u64> {
+        match deserialize::<ValidatorSetTransaction>(&txn.txn) {
+            Result::Ok(value) => Some(value.nonce),
+            Result::Err(_) => None,
+        }
+    }
+
+    fn next_nonce(&self, sender: &String, state_context: &dyn StateContext) -> Option<u64> {
+        let nonce: u64 =
+            get_value(state_context, &format!("{}{}", NONCE_PREFIX, sender)).unwrap_or(0);
+        Some(nonce + 1)
+    }
+
+    // votes are free, only validators can cast them
+    fn fee(&self, _txn: &SignedTransaction) -> u64 {
+        0
+    }
+
+    /// supported paths are "validators", "scheduled" & "nonce" of validator given in
+    /// "address" param, response is serialized Vec<String>, Vec<ScheduledChange> & u64
+    fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String> {
+        let response: Result<Vec<u8>, String> = match path.as_str() {
+            "validators" => {
+                let validators: Vec<String> =
+                    read_value(state_context, ACTIVE_KEY).unwrap_or_default();
+                serialize(&validators)
+            }
+            "scheduled" => {
+                let scheduled: Vec<ScheduledChange> =
+                    read_value(state_context, SCHEDULED_KEY).unwrap_or_default();
+                serialize(&scheduled)
+            }
+            "nonce" => {
+                let address: &String = match params.get("address") {
+                    Some(address) => address,
+                    None => return Err(String::from("address param missing")),
+                };
+                let nonce: u64 =
+                    read_value(state_context, &format!("{}{}", NONCE_PREFIX, address)).unwrap_or(0);
+                serialize(&nonce)
+            }
+            _ => return Err(format!("unknown query path {}", path)),
+        };
+        match response {
+            Result::Ok(value) => Ok(value),
+            Result::Err(_) => Err(String::from("query response couldn't serialize")),
+        }
+    }
+
+    /// applies changes whose activation height is reached and schedules changes which got
+    /// majority in this block, blocks on top of the activation height use the new set
+    fn end_block(
+        &self,
+        block: &BlockContext,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        let scheduled: Vec<ScheduledChange> =
+            get_value(state_context, SCHEDULED_KEY).unwrap_or_default();
+        let passed: Vec<ValidatorChange> = get_value(state_context, PASSED_KEY).unwrap_or_default();
+        if scheduled.is_empty() && passed.is_empty() {
+            return Ok(());
+        }
+        let (due, mut waiting): (Vec<ScheduledChange>, Vec<ScheduledChange>) = scheduled
+            .into_iter()
+            .partition(|each| each.activation_height <= block.height);
+        if !due.is_empty() {
+            let mut validators: Vec<String> =
+                get_value(state_context, ACTIVE_KEY).unwrap_or_default();
+            for each in due.iter() {
+                match each.change.apply(&mut validators) {
+                    Ok(()) => info!(
+                        "{:?} applied at height {}, validators {:?}",
+                        each.change, block.height, validators
+                    ),
+                    Err(reason) => warn!("{:?} skipped: {}", each.change, reason),
+                }
+            }
+            put_value(state_context, ACTIVE_KEY, &validators)?;
+        }
+        if !passed.is_empty() {
+            for change in passed {
+                info!(
+                    "{:?} scheduled for height {}",
+                    change,
+                    block.height + ACTIVATION_DELAY
+                );
+                waiting.push(ScheduledChange {
+                    change,
+                    activation_height: block.height + ACTIVATION_DELAY,
+                });
+            }
+            state_context.delete(&String::from(PASSED_KEY));
+        }
+        if waiting.is_empty() {
+            state_context.delete(&String::from(SCHEDULED_KEY));
+            Ok(())
+        } else {
+            put_value(state_context, SCHEDULED_KEY, &waiting)
+        }
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod test_transaction {
+    use super::*;
+    use crate::state::{genesis_validator_state, validators_at};
+    use sdk::namespace::namespaced_key;
+    use sdk::test_utils::MemoryStateContext;
+
+    fn sign_transaction(kp: &KeypairType, txn: &ValidatorSetTransaction) -> SignedTransaction {
+        let mut signed_txn = SignedTransaction {
+            txn: serialize(txn).unwrap(),
+            app_name: String::from(APPNAME),
+            header: HashMap::default(),
+            signature: Vec::new(),
+        };
+        signed_txn.signature = signed_txn.sign(kp);
+        signed_txn
+    }
+
+    fn vote(kp: &KeypairType, nonce: u64, fxn_call: &str, validator: &str) -> SignedTransaction {
+        let txn = ValidatorSetTransaction {
+            nonce,
+            from: hex::encode(kp.public().encode()),
+            fxn_call: String::from(fxn_call),
+            validator: validator.to_string(),
+        };
+        sign_transaction(kp, &txn)
+    }
+
+    fn block(height: u64) -> BlockContext {
+        BlockContext {
+            height,
+            timestamp: 0,
+            proposer: String::from("proposer"),
+        }
+    }
+
+    fn active(state_context: &MemoryStateContext) -> Vec<String> {
+        get_value(state_context, ACTIVE_KEY).unwrap()
+    }
+
+    #[test]
+    fn test_validator_set_change() {
+        let keypairs: Vec<KeypairType> = (0..3).map(|_| Keypair::generate()).collect();
+        let validators: Vec<String> = keypairs
+            .iter()
+            .map(|kp| hex::encode(kp.public().encode()))
+            .collect();
+        let outsider = Keypair::generate();
+        let new_validator: String = hex::encode(outsider.public().encode());
+        let app = ValidatorSetApp::new();
+        let mut state_context = MemoryStateContext::new();
+        assert!(genesis_validator_state(Vec::new()).is_err());
+        let (key, state) = genesis_validator_state(validators.clone()).unwrap();
+        assert_eq!(key, namespaced_key(APPNAME, ACTIVE_KEY));
+        // app sees the active set in its own namespace
+        state_context.put(&String::from(ACTIVE_KEY), state);
+        assert_eq!(active(&state_context), validators);
+
+        // only current validators vote
+        let txn = vote(&outsider, 1, "add_validator", &new_validator);
+        assert!(app.check_transaction(&txn).is_ok());
+        assert!(app.execute(&txn, &mut state_context).is_err());
+
+        let txn = vote(&keypairs[0], 1, "add_validator", &new_validator);
+        assert_eq!(app.execute(&txn, &mut state_context), Ok(()));
+        let txn = vote(&keypairs[0], 2, "add_validator", &new_validator);
+        assert!(app.execute(&txn, &mut state_context).is_err());
+        assert_eq!(
+            app.next_nonce(&validators[0], &state_context),
+            Some(2),
+            "failed vote shouldn't consume nonce"
+        );
+        // second of three votes is majority
+        let txn = vote(&keypairs[1], 1, "add_validator", &new_validator);
+        assert_eq!(app.execute(&txn, &mut state_context), Ok(()));
+        let txn = vote(&keypairs[2], 1, "add_validator", &new_validator);
+        assert!(app.execute(&txn, &mut state_context).is_err());
+        assert_eq!(app.end_block(&block(5), &mut state_context), Ok(()));
+        let scheduled: Vec<ScheduledChange> = get_value(&state_context, SCHEDULED_KEY).unwrap();
+        assert_eq!(
+            scheduled,
+            vec![ScheduledChange {
+                change: ValidatorChange::Add(new_validator.clone()),
+                activation_height: 5 + ACTIVATION_DELAY,
+            }]
+        );
+
+        assert_eq!(
+            app.end_block(&block(4 + ACTIVATION_DELAY), &mut state_context),
+            Ok(())
+        );
+        assert_eq!(active(&state_context), validators);
+        assert_eq!(
+            app.end_block(&block(5 + ACTIVATION_DELAY), &mut state_context),
+            Ok(())
+        );
+        let mut expected: Vec<String> = validators.clone();
+        expected.push(new_validator.clone());
+        assert_eq!(active(&state_context), expected);
+        assert!(!state_context.contains(&String::from(SCHEDULED_KEY)));
+
+        // new validator votes now, removal needs 3 of 4 votes
+        let txn = vote(&outsider, 1, "remove_validator", &validators[2]);
+        assert_eq!(app.execute(&txn, &mut state_context), Ok(()));
+        let txn = vote(&keypairs[0], 2, "remove_validator", &validators[2]);
+        assert_eq!(app.execute(&txn, &mut state_context), Ok(()));
+        assert!(!state_context.contains(&String::from(PASSED_KEY)));
+        let txn = vote(&keypairs[1], 2, "remove_validator", &validators[2]);
+        assert_eq!(app.execute(&txn, &mut state_context), Ok(()));
+        assert!(state_context.contains(&String::from(PASSED_KEY)));
+    }
+
+    #[test]
+    fn test_validators_at() {
+        let validators: Vec<String> = vec![String::from("a"), String::from("b")];
+        let mut state_context = MemoryStateContext::new();
+        assert_eq!(validators_at(&state_context.read_only(), 1), None);
+        put_value(
+            &mut state_context,
+            &namespaced_key(APPNAME, ACTIVE_KEY),
+            &validators,
+        )
+        .unwrap();
+        let scheduled: Vec<ScheduledChange> = vec![
+            ScheduledChange {
+                change: ValidatorChange::Add(String::from("c")),
+                activation_height: 10,
+            },
+            ScheduledChange {
+                change: ValidatorChange::Remove(String::from("a")),
+                activation_height: 12,
+            },
+        ];
+        put_value(
+            &mut state_context,
+            &namespaced_key(APPNAME, SCHEDULED_KEY),
+            &scheduled,
+        )
+        .unwrap();
+        let read_context = state_context.read_only();
+        // block at activation height still uses the old set
+        assert_eq!(validators_at(&read_context, 10), Some(validators.clone()));
+        assert_eq!(
+            validators_at(&read_context, 11),
+            Some(vec![
+                String::from("a"),
+                String::from("b"),
+                String::from("c")
+            ])
+        );
+        assert_eq!(
+            validators_at(&read_context, 13),
+            Some(vec![String::from("b"), String::from("c")])
+        );
+    }
+
+    #[test]
+    fn test_validator_change_apply() {
+        let mut validators: Vec<String> = vec![String::from("a")];
+        assert!(ValidatorChange::Add(String::from("a"))
+            .apply(&mut validators)
+            .is_err());
+        assert!(ValidatorChange::Remove(String::from("b"))
+            .apply(&mut validators)
+            .is_err());
+        assert!(ValidatorChange::Remove(String::from("a"))
+            .apply(&mut validators)
+            .is_err());
+        assert_eq!(
+            ValidatorChange::Add(String::from("b")).apply(&mut validators),
+            Ok(())
+        );
+        assert_eq!(
+            ValidatorChange::Remove(String::from("a")).apply(&mut validators),
+            Ok(())
+        );
+        assert_eq!(validators, vec![String::from("b")]);
+    }
+}
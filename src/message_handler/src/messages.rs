@@ -27,6 +27,15 @@ impl From<MessageTypes> for Vec<Topic> {
     }
 }
 
+/// ConsensusRequest is handed by the node to its own consensus engine through the request
+/// channel of MSG_DISPATCHER, peers can't send them. Engines which don't serve a request
+/// ignore it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConsensusRequest {
+    // seal a block right away, even without transactions
+    SealBlock,
+}
+
 ///Process FloodSubMessages
 ///
 pub trait MsgProcess {
@@ -67,17 +76,22 @@ pub struct MessageDispatcher {
     pub node_msg_receiver: Arc<Mutex<Receiver<Option<NodeMessageTypes>>>>,
     pub consensus_msg_dispatcher: Sender<Option<Vec<u8>>>,
     pub consensus_msg_receiver: Arc<Mutex<Receiver<Option<Vec<u8>>>>>,
+    pub consensus_request_dispatcher: Sender<Option<ConsensusRequest>>,
+    pub consensus_request_receiver: Arc<Mutex<Receiver<Option<ConsensusRequest>>>>,
 }
 
 impl MessageDispatcher {
     pub fn new() -> Self {
         let (tx, rx) = channel::<Option<NodeMessageTypes>>(1024);
         let (tx_consensus, rx_consensus) = channel::<Option<Vec<u8>>>(1024);
+        let (tx_request, rx_request) = channel::<Option<ConsensusRequest>>(1024);
         MessageDispatcher {
             node_msg_dispatcher: tx,
             node_msg_receiver: Arc::new(Mutex::new(rx)),
             consensus_msg_dispatcher: tx_consensus,
             consensus_msg_receiver: Arc::new(Mutex::new(rx_consensus)),
+            consensus_request_dispatcher: tx_request,
+            consensus_request_receiver: Arc::new(Mutex::new(rx_request)),
         }
     }
     pub fn set_node_msg_dispatcher(&mut self, tx: &Sender<Option<NodeMessageTypes>>) {
@@ -87,6 +101,15 @@ impl MessageDispatcher {
     pub fn set_consensus_msg_dispatcher(&mut self, tx: &Sender<Option<Vec<u8>>>) {
         self.consensus_msg_dispatcher = tx.clone();
     }
+
+    /// this function will hand given request to the consensus engine of the node, it isn't
+    /// sent to peers
+    pub fn request_consensus(&self, request: ConsensusRequest) -> Result<(), String> {
+        self.consensus_request_dispatcher
+            .clone()
+            .try_send(Some(request))
+            .map_err(|error| format!("consensus request couldn't sent: {:?}", error))
+    }
 }
 
 lazy_static! {
@@ -3,7 +3,7 @@ mod test_message_handler {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use crate::constants;
     use crate::message_sender::MessageSender;
-    use crate::messages::{MessageTypes, MSG_DISPATCHER};
+    use crate::messages::{ConsensusRequest, MessageDispatcher, MessageTypes, MSG_DISPATCHER};
     use crate::node_messages::NodeMessageTypes;
     use futures::channel::mpsc::*;
     use libp2p::floodsub::Topic;
@@ -135,4 +135,27 @@ mod test_message_handler {
         let dispatched_data = deserialize::<String>(&dispatched_data.unwrap().unwrap()).unwrap();
         assert_eq!(dispatched_data, peer_id);
     }
+
+    #[test]
+    fn test_consensus_request() {
+        let dispatcher: MessageDispatcher = MessageDispatcher::new();
+        assert_eq!(
+            dispatcher.request_consensus(ConsensusRequest::SealBlock),
+            Ok(())
+        );
+        let request: Option<ConsensusRequest> = dispatcher
+            .consensus_request_receiver
+            .lock()
+            .unwrap()
+            .try_next()
+            .unwrap();
+        assert_eq!(request, Some(ConsensusRequest::SealBlock));
+        // requests never reach the channel of peer consensus messages
+        assert!(dispatcher
+            .consensus_msg_receiver
+            .lock()
+            .unwrap()
+            .try_next()
+            .is_err());
+    }
 }
@@ -2,6 +2,7 @@ extern crate consensus;
 extern crate controllers;
 extern crate ctrlc;
 extern crate db_service;
+extern crate governance;
 extern crate p2plib;
 extern crate schema;
 
@@ -10,11 +11,14 @@ extern crate log;
 
 mod nodemsgprocessor;
 use consensus::consensus_interface;
+use consensus::consensus_interface::SharedImporter;
 use controllers::client_controller::{ClientController, Controller};
-use libloading::{Library, Symbol};
+use db_service::db_layer::DbInstance;
+use db_service::pool_journal::DbPoolJournal;
+use governance::transaction::ValidatorSetApp;
 use schema::appdata::APPDATA;
-use sdk::traits::AppHandler;
-use std::path::Path;
+use schema::transaction_pool::{start_expiry_sweeper, TxnPool, POOL};
+use std::time::SystemTime;
 
 use clap::{App, Arg};
 use libp2p::{identity::PublicKey, PeerId};
@@ -24,7 +28,7 @@ use nodemsgprocessor::*;
 use p2plib::simpleswarm::SimpleSwarm;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc, Mutex,
+    Arc, RwLock,
 };
 use std::thread;
 use utils::configreader;
@@ -32,7 +36,7 @@ use utils::configreader::initialize_config;
 use utils::configreader::{Configuration, NODETYPE};
 use utils::logger::logger_init_from_yml;
 
-fn validator_process(consensus_file_path: String) {
+fn validator_process(db: DbInstance, consensus_file_path: String) {
     let config: &Configuration = &configreader::GLOBAL_CONFIG;
     let pk: PublicKey = PublicKey::Ed25519(config.node.public.clone());
     let peer_id = PeerId::from_public_key(pk);
@@ -42,7 +46,10 @@ fn validator_process(consensus_file_path: String) {
         .topic_list
         .push(String::from(constants::CONSENSUS.clone()));
     swarm.topic_list.push(String::from(constants::NODE.clone()));
-    let mut node_msg_processor = NodeMsgProcessor::new(MSG_DISPATCHER.node_msg_receiver.clone());
+    // gossiped blocks are stored through the engine once it is started
+    let importer: SharedImporter = Arc::new(RwLock::new(None));
+    let mut node_msg_processor =
+        NodeMsgProcessor::new(MSG_DISPATCHER.node_msg_receiver.clone(), importer.clone());
     let mut sender = swarm.tx.clone();
     let txn_sender = swarm.tx.clone();
     {
@@ -54,13 +61,19 @@ fn validator_process(consensus_file_path: String) {
     // this thread will be responsible for whole consensus part.
     // in future this thread will spwan new child thread accrding to consensus requirement.
     let consensus_msg_receiver_clone = MSG_DISPATCHER.consensus_msg_receiver.clone();
+    let consensus_db: DbInstance = db.clone();
     thread::spawn(move || {
-        consensus_interface::Consensus::init_consensus(
+        if let Err(reason) = consensus_interface::Consensus::init_consensus(
             config,
+            consensus_db,
             &consensus_file_path,
             &mut sender,
             consensus_msg_receiver_clone,
-        )
+            importer,
+        ) {
+            eprintln!("consensus couldn't start: {}", reason);
+            std::process::exit(1);
+        }
     });
     thread::spawn(move || {
         let process = swarm.process(peer_id, config);
@@ -74,7 +87,7 @@ fn validator_process(consensus_file_path: String) {
     //TODO: host/port details need to come from config
     let port_from_config = config.node.client_port;
     let host_from_config = config.node.client_host.clone();
-    let mut api_service = ClientController::new(&host_from_config, port_from_config);
+    let mut api_service = ClientController::new(&host_from_config, port_from_config, db);
     info!("Starting api_service");
     api_service.start_validator_controller(txn_sender);
     info!("Started api_service");
@@ -90,13 +103,28 @@ fn validator_process(consensus_file_path: String) {
     api_service.stop(); //blocking call
 }
 
-fn fullnode_process() {
+fn fullnode_process(db: DbInstance, consensus_file_path: String) {
     let config: &Configuration = &configreader::GLOBAL_CONFIG;
     let pk: PublicKey = PublicKey::Ed25519(config.node.public.clone());
     let peer_id = PeerId::from_public_key(pk);
     let mut swarm = SimpleSwarm::new();
     swarm.topic_list.push(String::from(constants::NODE.clone()));
-    let mut node_msg_processor = NodeMsgProcessor::new(MSG_DISPATCHER.node_msg_receiver.clone());
+    let mut sender = swarm.tx.clone();
+    // full node doesn't run the engine, gossiped blocks are verified the way it does
+    let importer: SharedImporter = match consensus_interface::Consensus::block_importer(
+        config,
+        db.clone(),
+        &consensus_file_path,
+        &mut sender,
+    ) {
+        Ok(importer) => Arc::new(RwLock::new(importer)),
+        Err(reason) => {
+            eprintln!("consensus engine couldn't be created: {}", reason);
+            std::process::exit(1);
+        }
+    };
+    let mut node_msg_processor =
+        NodeMsgProcessor::new(MSG_DISPATCHER.node_msg_receiver.clone(), importer);
     let txn_sender = swarm.tx.clone();
     {
         thread::spawn(move || {
@@ -115,7 +143,7 @@ fn fullnode_process() {
     //TODO: host/port details need to come from config
     let port_from_config = config.node.client_port;
     let host_from_config = config.node.client_host.clone();
-    let mut api_service = ClientController::new(&host_from_config, port_from_config);
+    let mut api_service = ClientController::new(&host_from_config, port_from_config, db);
     info!("Starting api_service");
     api_service.start_fullnode_controller(txn_sender);
     info!("Started api_service");
@@ -140,33 +168,41 @@ fn register_signals(terminate: Arc<AtomicBool>) {
     .expect("Error setting Ctrl-C handler");
 }
 
-fn load_apps() {
+/// reload txns journaled before last shutdown and keep journaling POOL from now on
+fn restore_txn_pool(config: &Configuration, db: &DbInstance) {
+    let journal: DbPoolJournal = DbPoolJournal::new(db.clone());
+    let current_timestamp: u128 = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_micros();
+    let restored: usize = journal.restore(
+        &POOL,
+        current_timestamp,
+        config.block_config.transaction_execution_delay_limit,
+    );
+    info!("{} txns restored into txn pool from journal", restored);
+    POOL.set_journal(Arc::new(journal));
+}
+
+/// this function will register built-in governance app & load all configured apps, an app
+/// failing to load or built against another sdk/rustc is refused and Err carries the reason
+fn load_apps() -> Result<(), String> {
     let config: &Configuration = &configreader::GLOBAL_CONFIG;
-    println!("config = {:?}", config.node);
-    let mut app_iter = IntoIterator::into_iter(&config.node.client_apps);
-    while let Some(app) = app_iter.next() {
-        println!("loading library {:?}", app);
-        let app_path = Path::new(app);
-        println!("is file = {:?}", app_path.is_file());
-        let applib = Library::new(app)
-            .expect(format!("Loading App library {:?} failed", app.clone()).as_str());
-        let app_register: Symbol<fn() -> Box<dyn AppHandler + Send>> =
-            unsafe { applib.get(b"register_app") }.expect(
-                format!(
-                    "register_app symbol is not found in library {:?}",
-                    app.clone()
-                )
-                .as_str(),
-            );
-        let app_handle = Arc::new(Mutex::new(app_register()));
-        let app_name = app_handle.lock().unwrap().name();
-        println!("Loaded app {:?}", app_name);
-        let mut locked_app_data = APPDATA.lock().unwrap();
-        locked_app_data.lib.push(Arc::new(applib));
-        locked_app_data
-            .appdata
-            .insert(app_name.clone(), app_handle.clone());
+    // consensus engines read the active validator set from its state
+    let governance_app: ValidatorSetApp = ValidatorSetApp::new();
+    APPDATA
+        .lock()
+        .unwrap()
+        .register_app(Box::new(governance_app))?;
+    for app in config.node.client_apps.iter() {
+        info!("loading library {:?}", app);
+        let app_name: String = APPDATA
+            .lock()
+            .unwrap()
+            .load_app(app, config.node.client_app_hashes.get(app))?;
+        info!("Loaded app {:?}", app_name);
     }
+    Ok(())
 }
 
 fn main() {
@@ -209,13 +245,25 @@ fn main() {
     logger_init_from_yml(logger_file_path);
     info!("Node Bootstrapping");
     let config: &Configuration = &configreader::GLOBAL_CONFIG;
-    load_apps();
+    if let Err(reason) = load_apps() {
+        error!("app loading failed: {}", reason);
+        eprintln!("app loading failed: {}", reason);
+        std::process::exit(1);
+    }
+    // storage of the node is opened once & handed to every component
+    let db: DbInstance = DbInstance::from_config(config);
+    POOL.set_config(config.pool_config.clone());
+    restore_txn_pool(config, &db);
+    start_expiry_sweeper(
+        config.pool_config.expiry_sweep_interval,
+        config.block_config.transaction_execution_delay_limit,
+    );
     match config.node.node_type {
         NODETYPE::Validator => {
-            validator_process(consensus_file_path);
+            validator_process(db, consensus_file_path);
         }
         NODETYPE::FullNode => {
-            fullnode_process();
+            fullnode_process(db, consensus_file_path);
         }
     }
 }
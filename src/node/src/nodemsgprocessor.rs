@@ -1,12 +1,12 @@
 extern crate db_service;
-use db_service::db_fork_ref::SchemaFork;
-use db_service::db_layer::{fork_db, patch_db};
+use consensus::consensus_interface::SharedImporter;
 use exonum_merkledb::ObjectHash;
 use futures::{channel::mpsc::*, executor::*, future, prelude::*, task::*};
 use message_handler::node_messages::NodeMessageTypes;
+use schema::appdata::{AppRef, APPDATA};
 use schema::block::SignedBlock;
 use schema::signed_transaction::SignedTransaction;
-use schema::transaction_pool::{TxnPool, TxnPoolKeyType, POOL};
+use schema::transaction_pool::{TxnPool, POOL};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
@@ -15,6 +15,8 @@ use std::time::Duration;
 #[derive(Debug)]
 pub struct NodeMsgProcessor {
     pub _rx: Arc<Mutex<Receiver<Option<NodeMessageTypes>>>>,
+    // importer of the running engine, gossiped blocks are stored through it
+    pub importer: SharedImporter,
 }
 
 pub struct Blocks {
@@ -22,10 +24,13 @@ pub struct Blocks {
 }
 
 impl NodeMsgProcessor {
-    pub fn new(rx: Arc<Mutex<Receiver<Option<NodeMessageTypes>>>>) -> Self {
+    pub fn new(
+        rx: Arc<Mutex<Receiver<Option<NodeMessageTypes>>>>,
+        importer: SharedImporter,
+    ) -> Self {
         // let (mut tx, mut rx) = channel::<Option<NodeMessageTypes>>(1024);
         // NodeMsgProcessor { _tx: tx, _rx: rx }
-        NodeMsgProcessor { _rx: rx }
+        NodeMsgProcessor { _rx: rx, importer }
     }
 
     pub fn start(&mut self) {
@@ -35,7 +40,10 @@ impl NodeMsgProcessor {
             pending_blocks: std::collections::VecDeque::new(),
         };
         let arc_pending_blocks = Arc::new(Mutex::new(pending_blocks_obj));
-        NodeMsgProcessor::pending_block_processing_thread(arc_pending_blocks.clone());
+        NodeMsgProcessor::pending_block_processing_thread(
+            arc_pending_blocks.clone(),
+            self.importer.clone(),
+        );
         let pending_blocks = arc_pending_blocks.clone();
         block_on(future::poll_fn(move |cx: &mut Context| {
             loop {
@@ -64,11 +72,14 @@ impl NodeMsgProcessor {
                                         "Signed Transaction msg in NodeMsgProcessor with Hash {:?}",
                                         txn.object_hash()
                                     );
-                                    if let Some(string) = txn.header.get(&String::from("timestamp"))
-                                    {
-                                        if let Ok(timestamp) = string.parse::<TxnPoolKeyType>() {
-                                            POOL.insert_op(&timestamp, &txn);
-                                        }
+                                    let app_result: Result<AppRef, String> =
+                                        APPDATA.lock().unwrap().get_app(&txn.app_name);
+                                    let check_result: Result<(), String> =
+                                        app_result.and_then(|app| app.check_transaction(&txn));
+                                    if let Err(reason) = check_result {
+                                        warn!("transaction rejected at admission {:?}", reason);
+                                    } else if let Err(reason) = POOL.insert_op(&txn) {
+                                        warn!("transaction rejected by txn pool {:?}", reason);
                                     }
                                 }
                             },
@@ -85,7 +96,10 @@ impl NodeMsgProcessor {
         }));
     }
 
-    fn pending_block_processing_thread(pending_blocks: Arc<Mutex<Blocks>>) {
+    fn pending_block_processing_thread(
+        pending_blocks: Arc<Mutex<Blocks>>,
+        importer: SharedImporter,
+    ) {
         thread::spawn(move || {
             loop {
                 thread::sleep(Duration::from_millis(2000));
@@ -93,35 +107,28 @@ impl NodeMsgProcessor {
                 // if one want to change the create_block and txn priority then change/ implment that part in
                 // schema operations and p2p module
                 let mut block_queue = pending_blocks.lock().unwrap();
-                if block_queue.pending_blocks.len() > 0 {
-                    let fork = fork_db();
-                    let mut flag = false;
-                    {
-                        let mut schema = SchemaFork::new(&fork);
-                        let block: &SignedBlock = block_queue.pending_blocks.get_mut(0).unwrap();
-                        if schema.update_block(block) {
-                            POOL.sync_pool(&block.block.txn_pool);
-                            info!(
-                                "block height {}, block hash {}",
-                                block.block.id,
-                                block.object_hash()
-                            );
-                            flag = true;
-                        } else {
-                            info!("block couldn't verified");
-                            if schema.blockchain_length() > block.block.id {
-                                block_queue.pending_blocks.pop_front();
-                            } else {
-                                flag = true;
-                                schema.sync_state();
-                            }
-                        }
-                    }
-                    if flag {
-                        patch_db(fork);
-                        block_queue.pending_blocks.pop_front();
-                        info!("block updated in db");
+                let block: SignedBlock = match block_queue.pending_blocks.pop_front() {
+                    Some(block) => block,
+                    None => continue,
+                };
+                // engines agreeing on blocks through their own messages don't import them
+                let importer = match importer.read().unwrap().clone() {
+                    Some(importer) => importer,
+                    None => {
+                        block_queue.pending_blocks.clear();
+                        warn!("gossiped blocks dropped, consensus engine doesn't import them");
+                        continue;
                     }
+                };
+                if importer.import_block(&block) {
+                    info!(
+                        "block height {}, block hash {}",
+                        block.block.id,
+                        block.object_hash()
+                    );
+                    info!("block updated in db");
+                } else {
+                    info!("block couldn't verified");
                 }
             }
         });
@@ -1,13 +1,48 @@
-use libloading::Library;
-use sdk::traits::AppHandler;
+use libloading::{Library, Symbol};
+use sdk::abi::{AppManifest, MANIFEST_SYMBOL, REGISTER_SYMBOL, SDK_VERSION};
+use sdk::metering::{ExecutionLimits, MeteredStateContext};
+use sdk::namespace::{check_app_name, NamespacedReadContext, NamespacedStateContext};
+use sdk::signed_transaction::SignedTransaction;
+use sdk::traits::{AppHandler, BlockContext, ReadStateContext, StateContext};
 use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_char;
 use std::sync::{Arc, Mutex};
+use wasm_runtime::wasm_app::WasmApp;
 
 pub struct AppData {
     pub appdata: HashMap<String, Arc<Mutex<Box<dyn AppHandler + Send>>>>,
     pub lib: Vec<Arc<Library>>,
 }
 
+/// handle of a registered app, it only locks the app itself
+pub struct AppRef {
+    name: String,
+    app: Arc<Mutex<Box<dyn AppHandler + Send>>>,
+}
+
+impl AppRef {
+    /// this function will run the admission check of the app on given transaction
+    pub fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String> {
+        self.app.lock().unwrap().check_transaction(txn)
+    }
+
+    /// this function will route a read-only query to the app, the app sees its own
+    /// namespace of state only
+    pub fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String> {
+        self.app.lock().unwrap().query(
+            path,
+            params,
+            &NamespacedReadContext::new(state_context, &self.name),
+        )
+    }
+}
+
 impl AppData {
     pub fn new() -> AppData {
         AppData {
@@ -15,42 +50,239 @@ impl AppData {
             lib: Vec::new(),
         }
     }
-}
 
-lazy_static! {
-    pub static ref APPDATA: Arc<Mutex<AppData>> = Arc::new(Mutex::new(AppData::new()));
-}
+    /// this function will load the app library at given path. Its ABI manifest is checked
+    /// against the sdk of this node before register_app is called, Ok carries the app name.
+    /// Paths ending in `.wasm` are loaded as sandboxed wasm apps instead, their code hash
+    /// must match the pinned one.
+    pub fn load_app(
+        &mut self,
+        app_path: &str,
+        pinned_code_hash: Option<&String>,
+    ) -> Result<String, String> {
+        if app_path.ends_with(".wasm") {
+            return self.load_wasm_app(app_path, pinned_code_hash);
+        }
+        let applib: Library = match Library::new(app_path) {
+            Ok(applib) => applib,
+            Err(error) => return Err(format!("app library {} couldn't load: {}", app_path, error)),
+        };
+        let manifest: AppManifest = AppData::read_manifest(&applib, app_path)?;
+        manifest.check_compatible()?;
+        if self.appdata.contains_key(&manifest.app_name) {
+            return Err(format!("app {} is already loaded", manifest.app_name));
+        }
+        // safe as long as the manifest is honest, it is produced by sdk::export_app!
+        let app: Box<dyn AppHandler + Send> = unsafe {
+            let app_register: Symbol<fn() -> Box<dyn AppHandler + Send>> =
+                match applib.get(REGISTER_SYMBOL) {
+                    Ok(symbol) => symbol,
+                    Err(_) => {
+                        return Err(format!(
+                            "register_app symbol is not found in library {}",
+                            app_path
+                        ))
+                    }
+                };
+            app_register()
+        };
+        let app_name: String = app.name();
+        if app_name != manifest.app_name {
+            return Err(format!(
+                "app library {} registered {} but its manifest names {}",
+                app_path, app_name, manifest.app_name
+            ));
+        }
+        check_app_name(&app_name)?;
+        info!(
+            "loaded app {} {} from {}",
+            app_name, manifest.app_version, app_path
+        );
+        self.appdata
+            .insert(app_name.clone(), Arc::new(Mutex::new(app)));
+        self.lib.push(Arc::new(applib));
+        Ok(app_name)
+    }
 
-#[cfg(test)]
-mod tests_app_data {
+    /// this function will register an app built into the node, Ok carries the app name
+    pub fn register_app(&mut self, app: Box<dyn AppHandler + Send>) -> Result<String, String> {
+        let app_name: String = app.name();
+        check_app_name(&app_name)?;
+        if self.appdata.contains_key(&app_name) {
+            return Err(format!("app {} is already loaded", app_name));
+        }
+        info!("registered built-in app {}", app_name);
+        self.appdata
+            .insert(app_name.clone(), Arc::new(Mutex::new(app)));
+        Ok(app_name)
+    }
 
-    use super::*;
-    use sdk::signed_transaction::SignedTransaction;
-    use sdk::traits::StateContext;
-    const APPNAME: &str = "MockApp";
+    /// wasm apps carry no manifest, they are identified by hash of their module. A module
+    /// without pinned code hash or with another one is refused.
+    fn load_wasm_app(
+        &mut self,
+        app_path: &str,
+        pinned_code_hash: Option<&String>,
+    ) -> Result<String, String> {
+        let app: WasmApp = WasmApp::load(app_path)?;
+        let code_hash: String = app.code_hash().to_hex();
+        match pinned_code_hash {
+            Some(pinned) if pinned.eq_ignore_ascii_case(&code_hash) => {}
+            Some(pinned) => {
+                return Err(format!(
+                    "wasm app {} has code hash {} but {} is pinned",
+                    app_path, code_hash, pinned
+                ))
+            }
+            None => {
+                return Err(format!(
+                    "wasm app {} with code hash {} has no code hash pinned",
+                    app_path, code_hash
+                ))
+            }
+        }
+        let app_name: String = app.name();
+        check_app_name(&app_name)?;
+        if self.appdata.contains_key(&app_name) {
+            return Err(format!("app {} is already loaded", app_name));
+        }
+        info!(
+            "loaded wasm app {} from {} with code hash {}",
+            app_name, app_path, code_hash
+        );
+        let app: Box<dyn AppHandler + Send> = Box::new(app);
+        self.appdata
+            .insert(app_name.clone(), Arc::new(Mutex::new(app)));
+        Ok(app_name)
+    }
 
-    pub struct MockApp {
-        name: String,
+    /// manifest is a C string, reading it doesn't depend on the rustc the app is built with
+    fn read_manifest(applib: &Library, app_path: &str) -> Result<AppManifest, String> {
+        let encoded: String = unsafe {
+            let app_manifest: Symbol<extern "C" fn() -> *const c_char> =
+                match applib.get(MANIFEST_SYMBOL) {
+                    Ok(symbol) => symbol,
+                    Err(_) => {
+                        return Err(format!(
+                            "app library {} has no ABI manifest, rebuild it with sdk {} \
+                             and export it through sdk::export_app!",
+                            app_path, SDK_VERSION
+                        ))
+                    }
+                };
+            let raw_manifest: *const c_char = app_manifest();
+            if raw_manifest.is_null() {
+                return Err(format!("app library {} returned empty manifest", app_path));
+            }
+            CStr::from_ptr(raw_manifest).to_string_lossy().into_owned()
+        };
+        match AppManifest::decode(&encoded) {
+            Ok(manifest) => Ok(manifest),
+            Err(reason) => Err(format!("app library {}: {}", app_path, reason)),
+        }
     }
 
-    impl MockApp {
-        pub fn new(s: &String) -> MockApp {
-            MockApp { name: s.clone() }
+    /// this function will return a handle of the app registered under app_name. Callers
+    /// drop the APPDATA lock before using it so a slow app doesn't hold up other apps
+    pub fn get_app(&self, app_name: &String) -> Result<AppRef, String> {
+        match self.appdata.get(app_name) {
+            Some(app) => Ok(AppRef {
+                name: app_name.clone(),
+                app: app.clone(),
+            }),
+            None => Err(format!("unknown app {}", app_name)),
         }
     }
 
-    impl AppHandler for MockApp {
-        fn execute(&self, _txn: &SignedTransaction, _state_context: &mut dyn StateContext) -> bool {
-            true
+    /// this function will run init_genesis hook of every app. A failed hook is fatal, Err
+    /// carries the reason as the genesis state would miss the app's initial state
+    pub fn init_genesis(
+        &self,
+        block: &BlockContext,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        let failures: Vec<(String, String)> = self.run_hook(state_context, |app, app_context| {
+            app.init_genesis(block, app_context)
+        });
+        match failures.into_iter().next() {
+            Some((app_name, reason)) => Err(format!(
+                "init_genesis hook of app {} failed: {}",
+                app_name, reason
+            )),
+            None => Ok(()),
         }
+    }
+
+    /// this function will run begin_block hook of every app, failed hooks are logged and
+    /// don't stop the block
+    pub fn begin_block(&self, block: &BlockContext, state_context: &mut dyn StateContext) {
+        let failures: Vec<(String, String)> = self.run_hook(state_context, |app, app_context| {
+            app.begin_block(block, app_context)
+        });
+        for (app_name, reason) in failures {
+            error!("begin_block hook of app {} failed: {}", app_name, reason);
+        }
+    }
 
-        fn name(&self) -> String {
-            self.name.clone()
+    /// this function will run end_block hook of every app, failed hooks are logged and
+    /// don't stop the block
+    pub fn end_block(&self, block: &BlockContext, state_context: &mut dyn StateContext) {
+        let failures: Vec<(String, String)> = self.run_hook(state_context, |app, app_context| {
+            app.end_block(block, app_context)
+        });
+        for (app_name, reason) in failures {
+            error!("end_block hook of app {} failed: {}", app_name, reason);
         }
     }
 
+    /// hooks run in app name order so every node computes the same roots, each app sees
+    /// its own namespace only. Every hook runs inside a checkpoint, a failed hook leaves
+    /// no state changes behind. It returns (app name, reason) of every failed hook.
+    fn run_hook<F>(&self, state_context: &mut dyn StateContext, hook_fn: F) -> Vec<(String, String)>
+    where
+        F: Fn(&(dyn AppHandler + Send), &mut dyn StateContext) -> Result<(), String>,
+    {
+        let mut failures: Vec<(String, String)> = Vec::new();
+        let mut app_names: Vec<&String> = self.appdata.keys().collect();
+        app_names.sort();
+        for app_name in app_names {
+            let app = self.appdata[app_name].lock().unwrap();
+            let mut checkpoint = MeteredStateContext::new(
+                state_context,
+                ExecutionLimits::new(u64::MAX, u64::MAX, u64::MAX),
+            );
+            let result: Result<(), String> = hook_fn(
+                app.as_ref(),
+                &mut NamespacedStateContext::new(&mut checkpoint, app_name),
+            );
+            match result {
+                Ok(()) => {
+                    checkpoint.commit();
+                }
+                Err(reason) => {
+                    checkpoint.rollback();
+                    failures.push((app_name.clone(), reason));
+                }
+            }
+        }
+        failures
+    }
+}
+
+lazy_static! {
+    pub static ref APPDATA: Arc<Mutex<AppData>> = Arc::new(Mutex::new(AppData::new()));
+}
+
+#[cfg(test)]
+mod tests_app_data {
+
+    use super::*;
+    use sdk::test_utils::{MemoryStateContext, MockApp};
+    use std::collections::HashMap;
+    const APPNAME: &str = "MockApp";
+
     pub fn register_app() -> Box<dyn AppHandler + Send> {
-        Box::new(MockApp::new(&String::from(APPNAME)))
+        Box::new(MockApp::new(APPNAME))
     }
 
     #[test]
@@ -71,4 +303,89 @@ mod tests_app_data {
             "Issue with appdata insert"
         );
     }
+
+    #[test]
+    pub fn test_app_data_check_transaction() {
+        let mut app_data = AppData::new();
+        let app_handle = Arc::new(Mutex::new(register_app()));
+        app_data.appdata.insert(String::from(APPNAME), app_handle);
+        let mut signed_txn = SignedTransaction {
+            txn: vec![0],
+            app_name: String::from(APPNAME),
+            header: HashMap::default(),
+            signature: vec![0],
+        };
+        let app: AppRef = app_data.get_app(&signed_txn.app_name).unwrap();
+        assert_eq!(app.check_transaction(&signed_txn), Ok(()));
+        signed_txn.signature = vec![];
+        assert_eq!(
+            app.check_transaction(&signed_txn),
+            Err(String::from("signature missing"))
+        );
+        assert_eq!(
+            app_data.get_app(&String::from("unknown")).err(),
+            Some(String::from("unknown app unknown"))
+        );
+    }
+
+    #[test]
+    pub fn test_app_data_register_app() {
+        let mut app_data = AppData::new();
+        assert_eq!(
+            app_data.register_app(register_app()),
+            Ok(String::from(APPNAME))
+        );
+        assert_eq!(
+            app_data.register_app(register_app()),
+            Err(format!("app {} is already loaded", APPNAME))
+        );
+        assert_eq!(app_data.appdata.len(), 1);
+        assert_eq!(
+            app_data.register_app(Box::new(MockApp::new("MockApp/nested"))),
+            Err(String::from(
+                "app name MockApp/nested contains invalid character '/'"
+            ))
+        );
+        assert_eq!(app_data.appdata.len(), 1);
+    }
+
+    #[test]
+    pub fn test_app_data_init_genesis_failure() {
+        let mut app_data = AppData::new();
+        let block = BlockContext {
+            height: 0,
+            timestamp: 0,
+            proposer: String::from("genesis_block"),
+        };
+        app_data.register_app(register_app()).unwrap();
+        let mut state_context = MemoryStateContext::new();
+        assert_eq!(app_data.init_genesis(&block, &mut state_context), Ok(()));
+        app_data
+            .register_app(Box::new(MockApp::with_genesis_error(
+                "FailingApp",
+                "genesis file malformed",
+            )))
+            .unwrap();
+        assert_eq!(
+            app_data.init_genesis(&block, &mut state_context),
+            Err(String::from(
+                "init_genesis hook of app FailingApp failed: genesis file malformed"
+            ))
+        );
+    }
+
+    #[test]
+    pub fn test_app_data_load_failure() {
+        let mut app_data = AppData::new();
+        let result: Result<String, String> = app_data.load_app("missing_app_library.so", None);
+        assert!(result
+            .unwrap_err()
+            .starts_with("app library missing_app_library.so couldn't load"));
+        let result: Result<String, String> = app_data.load_app("missing_app.wasm", None);
+        assert!(result
+            .unwrap_err()
+            .starts_with("wasm app missing_app.wasm couldn't load"));
+        assert!(app_data.appdata.is_empty());
+        assert!(app_data.lib.is_empty());
+    }
 }
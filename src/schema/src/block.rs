@@ -16,7 +16,7 @@ pub trait BlockTraits<T> {
         peer_id: String,
         prev_hash: Hash,
         txn_pool: Vec<Hash>,
-        header: [Hash; 3],
+        header: [Hash; 4],
         custom_headers: Vec<u8>,
     ) -> Self;
 }
@@ -34,8 +34,8 @@ pub struct Block {
     pub peer_id: String,
     pub prev_hash: Hash,
     pub txn_pool: Vec<Hash>,
-    // txn_trie, state_trie, storage_trie
-    pub header: [Hash; 3],
+    // state_trie, storage_trie, txn_trie, receipt_trie
+    pub header: [Hash; 4],
     // block creation time
     pub timestamp: u128,
     // custom defined data
@@ -131,7 +131,7 @@ impl BlockTraits<KeypairType> for Block {
             peer_id: String::from("genesis_block"),
             prev_hash: Hash::zero(),
             txn_pool: vec![],
-            header: [Hash::zero(), Hash::zero(), Hash::zero()],
+            header: [Hash::zero(), Hash::zero(), Hash::zero(), Hash::zero()],
             timestamp,
             custom_headers,
         }
@@ -142,7 +142,7 @@ impl BlockTraits<KeypairType> for Block {
         peer_id: String,
         prev_hash: Hash,
         txn_pool: Vec<Hash>,
-        header: [Hash; 3],
+        header: [Hash; 4],
         custom_headers: Vec<u8>,
     ) -> Block {
         let timestamp: u128 = SystemTime::now()
@@ -175,7 +175,7 @@ mod tests_block {
             pk.clone(),
             Hash::zero(),
             vec![Hash::zero()],
-            [Hash::zero(), Hash::zero(), Hash::zero()],
+            [Hash::zero(), Hash::zero(), Hash::zero(), Hash::zero()],
             Vec::new(),
         );
         let signed_block: SignedBlock =
@@ -0,0 +1 @@
+pub use sdk::event::{Event, EventFilter};
@@ -11,6 +11,8 @@ pub mod types;
 
 pub mod appdata;
 pub mod block;
+pub mod event;
+pub mod receipt;
 pub mod signed_transaction;
 pub mod state;
 pub mod transaction_pool;
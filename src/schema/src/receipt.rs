@@ -0,0 +1 @@
+pub use sdk::receipt::{Receipt, TransactionStatus};
@@ -1,55 +1,407 @@
 extern crate utils;
 use super::appdata::APPDATA;
+use super::event::Event;
+use super::receipt::{Receipt, TransactionStatus};
 use super::signed_transaction::SignedTransaction;
 use super::state::State;
 use exonum_crypto::Hash;
 use exonum_merkledb::{
     access::{Access, RawAccessMut},
-    ObjectHash,
+    BinaryValue, ObjectHash,
 };
-use sdk::traits::{PoolTrait, StateContext};
-use std::collections::BTreeMap;
-use std::sync::{Arc, Mutex};
-use std::time::SystemTime;
+use sdk::metering::{ChainLimits, ExecutionLimits, ExecutionMeter, MeteredStateContext};
+use sdk::namespace::NamespacedStateContext;
+use sdk::traits::{AppHandler, ChainStateContext, PoolTrait, StateContext};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, SystemTime};
 use utils::configreader;
-use utils::configreader::BlockConfig;
+use utils::configreader::{BlockConfig, PoolConfig, EVICTIONPOLICY};
+use utils::serializer::{Deserialize, Serialize};
 
 pub type TxnPoolKeyType = u128;
 pub type TxnPoolValueType = SignedTransaction;
 
+/// TxnOrderKey orders txns by the time the pool received them, ties are broken by the
+/// pool receive sequence and finally by txn hash, so two txns never share a key. The
+/// timestamp header is set by clients, it isn't used for ordering or expiry.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TxnOrderKey {
+    pub timestamp: TxnPoolKeyType,
+    pub sequence: u64,
+    pub hash: Hash,
+}
+
+/// this function will parse the mandatory timestamp header of given txn
+pub fn txn_timestamp(txn: &TxnPoolValueType) -> Option<TxnPoolKeyType> {
+    match txn.header.get(&String::from("timestamp")) {
+        Some(string) => match string.parse::<TxnPoolKeyType>() {
+            Ok(timestamp) => Some(timestamp),
+            Err(_) => None,
+        },
+        None => None,
+    }
+}
+
+/// TxnMeta keeps per txn details the pool limits are checked against
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxnMeta {
+    pub sender: Option<String>,
+    pub nonce: Option<u64>,
+    pub priority: u64,
+    pub size: usize,
+}
+
+impl TxnMeta {
+    /// this function will ask the app the txn is addressed to for its sender, nonce & priority
+    pub fn from_txn(txn: &TxnPoolValueType) -> TxnMeta {
+        let (sender, nonce, priority) = match APPDATA.lock().unwrap().appdata.get(&txn.app_name) {
+            Some(app) => {
+                let locked_app = app.lock().unwrap();
+                (
+                    locked_app.sender(txn),
+                    locked_app.nonce(txn),
+                    locked_app.fee(txn),
+                )
+            }
+            None => (None, None, 0),
+        };
+        TxnMeta {
+            sender,
+            nonce,
+            priority,
+            size: txn.to_bytes().len(),
+        }
+    }
+}
+
+/// execution limits of node config, they become the chain limits at genesis. Chains
+/// initialized before the limits were kept in state keep using them.
+pub fn config_chain_limits() -> ChainLimits {
+    let block_config: &BlockConfig = &configreader::GLOBAL_CONFIG.block_config;
+    ChainLimits {
+        txn_limits: ExecutionLimits::new(
+            block_config.txn_state_read_limit,
+            block_config.txn_state_write_limit,
+            block_config.txn_state_bytes_limit,
+        ),
+        block_limits: ExecutionLimits::new(
+            block_config.block_state_read_limit,
+            block_config.block_state_write_limit,
+            block_config.block_state_bytes_limit,
+        ),
+    }
+}
+
+/// BlockStateContext exposes the block producer to apps on top of the state
+/// context the block is executed against. Events emitted by the txn under
+/// execution are buffered here until its receipt is written. It also keeps the
+/// state usage of the block executed so far against the block limits, limits are
+/// the chain limits kept in state.
+struct BlockStateContext<'a> {
+    state_context: &'a mut dyn ChainStateContext,
+    block_producer: String,
+    events: Vec<(String, Vec<u8>)>,
+    txn_limits: ExecutionLimits,
+    block_limits: ExecutionLimits,
+    block_meter: ExecutionMeter,
+}
+
+impl<'a> BlockStateContext<'a> {
+    fn new(state_context: &'a mut dyn ChainStateContext, block_producer: &String) -> Self {
+        let chain_limits: ChainLimits =
+            ChainLimits::load(&*state_context).unwrap_or_else(config_chain_limits);
+        BlockStateContext {
+            state_context,
+            block_producer: block_producer.clone(),
+            events: Vec::new(),
+            txn_limits: chain_limits.txn_limits,
+            block_limits: chain_limits.block_limits,
+            block_meter: ExecutionMeter::default(),
+        }
+    }
+
+    /// true once txns of the block used up any of the block limits
+    fn is_exhausted(&self) -> bool {
+        self.block_meter
+            .remaining(&self.block_limits)
+            .is_exhausted()
+    }
+
+    /// this function will execute txn inside a checkpoint, a metered view of block state
+    /// whose writes are applied only if execution succeeds. A failed txn or a txn crossing
+    /// its own limits or what is left of block limits leaves no state changes behind. Txn
+    /// payload is charged ahead of execution. The txn itself is recorded by the node
+    /// whatever the result, apps can't write txns.
+    fn execute_checkpointed(
+        &mut self,
+        app: &dyn AppHandler,
+        txn: &SignedTransaction,
+    ) -> Result<(), String> {
+        let limits: ExecutionLimits = self
+            .txn_limits
+            .min(&self.block_meter.remaining(&self.block_limits));
+        let (result, meter) = {
+            let mut checkpoint = MeteredStateContext::new(self, limits);
+            let result = if checkpoint.charge_bytes(txn.txn.len()) {
+                app.execute(
+                    txn,
+                    &mut NamespacedStateContext::new(&mut checkpoint, &txn.app_name),
+                )
+            } else {
+                Ok(())
+            };
+            match (checkpoint.exceeded(), result) {
+                (Some(reason), _) => (
+                    Err(format!("execution aborted, {}", reason)),
+                    checkpoint.rollback(),
+                ),
+                (None, Ok(())) => (Ok(()), checkpoint.commit()),
+                (None, Err(reason)) => (Err(reason), checkpoint.rollback()),
+            }
+        };
+        self.block_meter.add(&meter);
+        let txn_hash: Hash = txn.object_hash();
+        if !self.state_context.contains_txn(&txn_hash) {
+            self.state_context.put_txn(&txn_hash, txn.clone());
+        }
+        result
+    }
+
+    /// this function will write receipt of executed txn along with the events it emitted,
+    /// events of a failed execution are dropped
+    fn put_receipt_with_events(
+        &mut self,
+        txn_hash: &Hash,
+        app_name: &String,
+        result: &Result<(), String>,
+        block_height: u64,
+        index: u64,
+    ) {
+        let emitted: Vec<(String, Vec<u8>)> = std::mem::replace(&mut self.events, Vec::new());
+        let mut receipt: Receipt = Receipt::new(txn_hash.clone(), result, block_height, index);
+        if result.is_ok() {
+            let events: Vec<Event> = emitted
+                .into_iter()
+                .enumerate()
+                .map(|(event_index, (topic, data))| {
+                    Event::new(
+                        app_name.clone(),
+                        topic,
+                        data,
+                        txn_hash.clone(),
+                        block_height,
+                        event_index as u64,
+                    )
+                })
+                .collect();
+            receipt.set_events(events);
+        }
+        self.state_context.put_receipt(txn_hash, receipt);
+    }
+}
+
+impl<'a> StateContext for BlockStateContext<'a> {
+    fn put(&mut self, key: &String, state: State) {
+        self.state_context.put(key, state);
+    }
+    fn get(&self, key: &String) -> Option<State> {
+        self.state_context.get(key)
+    }
+    fn contains(&self, key: &String) -> bool {
+        self.state_context.contains(key)
+    }
+    fn delete(&mut self, key: &String) {
+        self.state_context.delete(key);
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        self.state_context.iter_prefix(prefix)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        self.state_context.range(from, to)
+    }
+    fn iter_prefix_bounded(&self, prefix: &String, max_entries: usize) -> Vec<(String, State)> {
+        self.state_context.iter_prefix_bounded(prefix, max_entries)
+    }
+    fn range_bounded(
+        &self,
+        from: &String,
+        to: &String,
+        max_entries: usize,
+    ) -> Vec<(String, State)> {
+        self.state_context.range_bounded(from, to, max_entries)
+    }
+    fn put_storage(&mut self, account: &String, key: &String, value: Vec<u8>) {
+        self.state_context.put_storage(account, key, value);
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.state_context.get_storage(account, key)
+    }
+    fn delete_storage(&mut self, account: &String, key: &String) {
+        self.state_context.delete_storage(account, key);
+    }
+    fn get_txn(&self, key: &Hash) -> Option<SignedTransaction> {
+        self.state_context.get_txn(key)
+    }
+    fn contains_txn(&self, key: &Hash) -> bool {
+        self.state_context.contains_txn(key)
+    }
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt> {
+        self.state_context.get_receipt(key)
+    }
+    fn emit_event(&mut self, topic: &String, data: Vec<u8>) {
+        self.events.push((topic.clone(), data));
+    }
+    fn get_block_producer(&self) -> Option<String> {
+        Some(self.block_producer.clone())
+    }
+}
+
+/// this function will decrease pending txn count of given key, the key is dropped at zero
+fn decrease_count(counts: &mut HashMap<String, usize>, key: String) {
+    let count: usize = counts.get(&key).cloned().unwrap_or(1);
+    if count > 1 {
+        counts.insert(key, count - 1);
+    } else {
+        counts.remove(&key);
+    }
+}
+
+/// key of a per account nonce queue, (app_name, sender)
+pub type NonceQueueKey = (String, String);
+
+/// max count of evicted transaction records kept for status queries
+pub const EVICTED_TXN_LIMIT: usize = 10000;
+
+/// JournaledTxn keeps a pooled txn along with the time the pool received it, restored
+/// txns are ordered & expired by it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BinaryValue, ObjectHash)]
+#[binary_value(codec = "bincode")]
+pub struct JournaledTxn {
+    pub txn: TxnPoolValueType,
+    pub received_timestamp: TxnPoolKeyType,
+}
+
+/// pending journal writes, txn hash => txn to keep or None to remove it
+pub type JournalBatch = HashMap<Hash, Option<JournaledTxn>>;
+
+/// PoolJournal mirrors pooled txns into a local store so that the pool can be
+/// reloaded after a node restart
+pub trait PoolJournal: fmt::Debug + Send + Sync {
+    /// this function will apply all puts & removes of given batch at once
+    fn write(&self, batch: &JournalBatch);
+    fn load(&self) -> Vec<JournaledTxn>;
+}
+
 pub trait TransactionPoolTraits {
     fn new() -> Self;
     fn delete_txn_hash(&mut self, key: &Hash);
-    fn delete_txn_order(&mut self, key: &TxnPoolKeyType);
-    fn insert_op(&mut self, key: &TxnPoolKeyType, value: &TxnPoolValueType);
+    fn delete_txn_order(&mut self, key: &TxnOrderKey);
+    fn insert_op(&mut self, value: &TxnPoolValueType) -> Result<(), String>;
+    fn restore_op(&mut self, value: &JournaledTxn) -> Result<(), String>;
+    fn insert_committed(&mut self, value: &TxnPoolValueType);
     fn length_order_pool(&self) -> usize;
     fn length_hash_pool(&self) -> usize;
     fn get(&self, key: &Hash) -> Option<TxnPoolValueType>;
     fn sync_pool(&mut self, txn_hash_vec: &Vec<Hash>);
+    fn evict_txns(&mut self, txn_hash_vec: &Vec<Hash>);
+    fn get_evicted_status(&self, key: &Hash) -> Option<TransactionStatus>;
+    fn set_config(&mut self, config: PoolConfig);
+    fn expire_txns(&mut self, current_timestamp: TxnPoolKeyType, delay_limit: u128) -> usize;
+    fn set_journal(&mut self, journal: Arc<dyn PoolJournal>);
 }
 
 pub trait TxnPool {
     fn new() -> Self;
     fn delete_txn_hash(&self, key: &Hash);
-    fn delete_txn_order(&self, key: &TxnPoolKeyType);
-    fn insert_op(&self, key: &TxnPoolKeyType, value: &TxnPoolValueType);
+    fn delete_txn_order(&self, key: &TxnOrderKey);
+    fn insert_op(&self, value: &TxnPoolValueType) -> Result<(), String>;
+    fn restore_op(&self, value: &JournaledTxn) -> Result<(), String>;
+    fn insert_committed(&self, value: &TxnPoolValueType);
     fn length_order_pool(&self) -> usize;
     fn length_hash_pool(&self) -> usize;
     fn get(&self, key: &Hash) -> Option<TxnPoolValueType>;
     fn sync_pool(&self, txn_hash_vec: &Vec<Hash>);
+    fn evict_txns(&self, txn_hash_vec: &Vec<Hash>);
+    fn get_evicted_status(&self, key: &Hash) -> Option<TransactionStatus>;
+    fn set_config(&self, config: PoolConfig);
+    fn expire_txns(&self, current_timestamp: TxnPoolKeyType, delay_limit: u128) -> usize;
+    fn set_journal(&self, journal: Arc<dyn PoolJournal>);
+}
+
+/// EvictedTxns is a bounded record of transactions removed from the pool without
+/// being included in a block, oldest records are rotated out first.
+#[derive(Debug, Clone)]
+pub struct EvictedTxns {
+    order: VecDeque<Hash>,
+    status: HashMap<Hash, TransactionStatus>,
+}
+
+impl EvictedTxns {
+    pub fn new() -> EvictedTxns {
+        EvictedTxns {
+            order: VecDeque::new(),
+            status: HashMap::new(),
+        }
+    }
+
+    /// this function will record evicted txn status, dropping the oldest record if limit crossed
+    pub fn insert(&mut self, key: Hash, status: TransactionStatus) {
+        if self.status.insert(key, status).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > EVICTED_TXN_LIMIT {
+            if let Some(oldest) = self.order.pop_front() {
+                self.status.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, key: &Hash) -> Option<TransactionStatus> {
+        self.status.get(key).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
 }
 /**
  * BTreeMap is used here for in-order push-pop values and at the same time, search operation also supported.
 */
 /// TransactionPool object to maintain in-coming txn and txn-order.
+/// hash_pool maps txn hash to its order key and order_pool keeps the txn itself.
+/// meta_pool, sender_count, app_count & pool_bytes track usage against config limits.
+/// Txns whose app reports no sender are counted per app in app_count.
+/// priority_pool orders txns by fee (highest first) and then by order key, it is used
+/// for block building and lowest priority eviction.
+/// nonce_pool keeps txns of nonce ordered accounts sorted by nonce.
+/// journal, when set, gets every insert & removal so the pool survives restarts, they are
+/// collected in journal_batch and written by Pool once the pool lock is released.
 #[derive(Debug, Clone)]
 pub struct TransactionPool {
-    hash_pool: BTreeMap<Hash, TxnPoolValueType>,
-    order_pool: BTreeMap<TxnPoolKeyType, TxnPoolValueType>,
+    hash_pool: BTreeMap<Hash, TxnOrderKey>,
+    order_pool: BTreeMap<TxnOrderKey, TxnPoolValueType>,
+    evicted_pool: EvictedTxns,
+    sequence: u64,
+    meta_pool: HashMap<Hash, TxnMeta>,
+    priority_pool: BTreeSet<(Reverse<u64>, TxnOrderKey)>,
+    sender_count: HashMap<String, usize>,
+    app_count: HashMap<String, usize>,
+    nonce_pool: HashMap<NonceQueueKey, BTreeMap<u64, Hash>>,
+    pool_bytes: usize,
+    config: PoolConfig,
+    journal: Option<Arc<dyn PoolJournal>>,
+    journal_batch: JournalBatch,
 }
 
+/// Pool shares TransactionPool across threads. journal_order keeps journal batches
+/// written in the order pool changes were made.
 pub struct Pool {
     pub pool: Arc<std::sync::Mutex<TransactionPool>>,
+    journal_order: Mutex<()>,
 }
 
 impl TransactionPoolTraits for TransactionPool {
@@ -58,27 +410,75 @@ impl TransactionPoolTraits for TransactionPool {
         TransactionPool {
             hash_pool: BTreeMap::new(),
             order_pool: BTreeMap::new(),
+            evicted_pool: EvictedTxns::new(),
+            sequence: 0,
+            meta_pool: HashMap::new(),
+            priority_pool: BTreeSet::new(),
+            sender_count: HashMap::new(),
+            app_count: HashMap::new(),
+            nonce_pool: HashMap::new(),
+            pool_bytes: 0,
+            config: PoolConfig::default(),
+            journal: None,
+            journal_batch: HashMap::new(),
         }
     }
 
-    /// this function will delete txn using hash if present, from hash_pool
+    /// this function will delete txn using hash if present, from both (hash & order) pool
     fn delete_txn_hash(&mut self, key: &Hash) {
-        if self.hash_pool.contains_key(key) {
-            self.hash_pool.remove(key);
+        if let Some(order_key) = self.hash_pool.remove(key) {
+            if self.journal.is_some() {
+                self.journal_batch.insert(key.clone(), None);
+            }
+            let txn: Option<TxnPoolValueType> = self.order_pool.remove(&order_key);
+            if let Some(meta) = self.meta_pool.remove(key) {
+                if let (Some(txn), Some(sender), Some(nonce)) = (&txn, &meta.sender, meta.nonce) {
+                    let queue_key: NonceQueueKey = (txn.app_name.clone(), sender.clone());
+                    if let Some(queue) = self.nonce_pool.get_mut(&queue_key) {
+                        queue.remove(&nonce);
+                        if queue.is_empty() {
+                            self.nonce_pool.remove(&queue_key);
+                        }
+                    }
+                }
+                self.priority_pool
+                    .remove(&(Reverse(meta.priority), order_key));
+                self.pool_bytes = self.pool_bytes - meta.size;
+                match (meta.sender, txn) {
+                    (Some(sender), _) => decrease_count(&mut self.sender_count, sender),
+                    (None, Some(txn)) => decrease_count(&mut self.app_count, txn.app_name),
+                    (None, None) => {}
+                }
+            }
         }
     }
 
-    /// this function will delete txn using order_value if present, from order_pool
-    fn delete_txn_order(&mut self, key: &TxnPoolKeyType) {
+    /// this function will delete txn using order_key if present, from both (hash & order) pool
+    fn delete_txn_order(&mut self, key: &TxnOrderKey) {
         if self.order_pool.contains_key(key) {
-            self.order_pool.remove(key);
+            self.delete_txn_hash(&key.hash);
         }
     }
 
-    /// this function will push value in both (hash & order) pool
-    fn insert_op(&mut self, key: &TxnPoolKeyType, value: &TxnPoolValueType) {
-        self.hash_pool.insert(value.object_hash(), value.clone());
-        self.order_pool.insert(key.clone(), value.clone());
+    /// this function will push value in both (hash & order) pool, Err carries
+    /// the reason when txn is rejected
+    fn insert_op(&mut self, value: &TxnPoolValueType) -> Result<(), String> {
+        let meta: TxnMeta = TxnMeta::from_txn(value);
+        self.insert_txn(value, meta)
+    }
+
+    /// this function will push journaled txn back keyed by the time the pool first
+    /// received it, pool limits are checked as for a new txn
+    fn restore_op(&mut self, value: &JournaledTxn) -> Result<(), String> {
+        let meta: TxnMeta = TxnMeta::from_txn(&value.txn);
+        self.insert_received_txn(&value.txn, meta, value.received_timestamp)
+    }
+
+    /// this function will push txn of a block committed by other node, pool limits are
+    /// bypassed as the block can't be synced without executing it
+    fn insert_committed(&mut self, value: &TxnPoolValueType) {
+        let meta: TxnMeta = TxnMeta::from_txn(value);
+        self.insert_committed_txn(value, meta);
     }
 
     /// length of order_pool
@@ -93,25 +493,385 @@ impl TransactionPoolTraits for TransactionPool {
 
     /// get transaction usinng hash from hash_pool
     fn get(&self, key: &Hash) -> Option<TxnPoolValueType> {
-        if self.hash_pool.contains_key(key) {
-            return Some(self.hash_pool.get(&key).unwrap().clone());
-        } else {
-            return Option::None;
+        match self.hash_pool.get(key) {
+            Some(order_key) => self.order_pool.get(order_key).cloned(),
+            None => Option::None,
         }
     }
 
     /// sync both (hash & order ) pool when block committed is created by the other node
     fn sync_pool(&mut self, txn_hash_vec: &Vec<Hash>) {
+        for each_hash in txn_hash_vec.iter() {
+            self.delete_txn_hash(each_hash);
+        }
+    }
+
+    /// remove txns which couldn't be executed from both (hash & order) pool and
+    /// keep the reason in evicted_pool
+    fn evict_txns(&mut self, txn_hash_vec: &Vec<Hash>) {
         for each_hash in txn_hash_vec.iter() {
             if let Some(txn) = self.get(each_hash) {
-                if let Some(string) = txn.header.get(&String::from("timestamp")) {
-                    if let Ok(timestamp) = string.parse::<TxnPoolKeyType>() {
-                        self.delete_txn_order(&timestamp);
-                        self.delete_txn_hash(each_hash);
-                    }
+                let status: TransactionStatus =
+                    if APPDATA.lock().unwrap().appdata.contains_key(&txn.app_name) {
+                        TransactionStatus::Expired
+                    } else {
+                        TransactionStatus::Dropped(format!("unknown app {}", txn.app_name))
+                    };
+                self.evicted_pool.insert(each_hash.clone(), status);
+            }
+        }
+        self.sync_pool(txn_hash_vec);
+    }
+
+    /// get evicted txn status using hash from evicted_pool
+    fn get_evicted_status(&self, key: &Hash) -> Option<TransactionStatus> {
+        self.evicted_pool.get(key)
+    }
+
+    /// replace pool limits, already pooled txns are kept as it is
+    fn set_config(&mut self, config: PoolConfig) {
+        self.config = config;
+    }
+
+    /// this function will remove txns pooled for longer than delay_limit and mark them as
+    /// expired
+    fn expire_txns(&mut self, current_timestamp: TxnPoolKeyType, delay_limit: u128) -> usize {
+        let expired_keys: Vec<TxnOrderKey> = self
+            .order_pool
+            .keys()
+            .take_while(|order_key| {
+                current_timestamp > order_key.timestamp.saturating_add(delay_limit)
+            })
+            .cloned()
+            .collect();
+        for order_key in expired_keys.iter() {
+            self.evicted_pool
+                .insert(order_key.hash, TransactionStatus::Expired);
+            self.delete_txn_order(order_key);
+        }
+        expired_keys.len()
+    }
+
+    /// attach journal, txns already in pool are not written to it
+    fn set_journal(&mut self, journal: Arc<dyn PoolJournal>) {
+        self.journal = Some(journal);
+    }
+}
+
+impl TransactionPool {
+    /// this function will push value with its meta in both (hash & order) pool after
+    /// checking pool limits. When pool is full, a txn is evicted as per eviction policy.
+    /// A timestamp header further ahead of node clock than max_timestamp_drift is rejected.
+    pub fn insert_txn(&mut self, value: &TxnPoolValueType, meta: TxnMeta) -> Result<(), String> {
+        let current_timestamp: TxnPoolKeyType = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        self.insert_received_txn(value, meta, current_timestamp)
+    }
+
+    /// this function will insert txn like insert_txn, the txn is keyed by given receive time
+    fn insert_received_txn(
+        &mut self,
+        value: &TxnPoolValueType,
+        meta: TxnMeta,
+        received_timestamp: TxnPoolKeyType,
+    ) -> Result<(), String> {
+        let timestamp: TxnPoolKeyType = match txn_timestamp(value) {
+            Some(timestamp) => timestamp,
+            None => return Err(String::from("txn timestamp header missing or invalid")),
+        };
+        let current_timestamp: TxnPoolKeyType = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        if timestamp > current_timestamp.saturating_add(self.config.max_timestamp_drift) {
+            return Err(String::from("txn timestamp header too far ahead"));
+        }
+        let hash: Hash = value.object_hash();
+        if self.hash_pool.contains_key(&hash) {
+            return Ok(());
+        }
+        if meta.size > self.config.max_pool_bytes {
+            return Err(String::from("txn size crossed txn pool bytes limit"));
+        }
+        // pending txn with the same sender & nonce is replaced by the new one
+        let queue_key: Option<NonceQueueKey> = match (&meta.sender, meta.nonce) {
+            (Some(sender), Some(_)) => Some((value.app_name.clone(), sender.clone())),
+            _ => None,
+        };
+        let replace_hash: Option<Hash> = match (&queue_key, meta.nonce) {
+            (Some(queue_key), Some(nonce)) => match self.nonce_pool.get(queue_key) {
+                Some(queue) => queue.get(&nonce).cloned(),
+                None => None,
+            },
+            _ => None,
+        };
+        // every rejection is checked before anything is removed from the pool, so a
+        // rejected txn leaves the pending txn it would replace in place
+        let replaced_meta: Option<TxnMeta> = match replace_hash {
+            Some(replace_hash) => self.meta_pool.get(&replace_hash).cloned(),
+            None => None,
+        };
+        if let Some(pending_meta) = &replaced_meta {
+            if meta.priority < pending_meta.priority {
+                return Err(String::from(
+                    "txn with same nonce and higher priority already pending",
+                ));
+            }
+        }
+        let (replaced_count, replaced_size): (usize, usize) = match &replaced_meta {
+            Some(pending_meta) => (1, pending_meta.size),
+            None => (0, 0),
+        };
+        match &meta.sender {
+            Some(sender) => {
+                let pending_count: usize = self.sender_count.get(sender).cloned().unwrap_or(0);
+                if pending_count.saturating_sub(replaced_count) >= self.config.max_txns_per_sender {
+                    return Err(format!("sender {} crossed pending txn limit", sender));
+                }
+            }
+            None => {
+                let pending_count: usize =
+                    self.app_count.get(&value.app_name).cloned().unwrap_or(0);
+                if pending_count >= self.config.max_txns_per_app {
+                    return Err(format!(
+                        "app {} crossed pending limit of txns without sender",
+                        value.app_name
+                    ));
+                }
+            }
+        }
+        let evict_keys: Vec<TxnOrderKey> =
+            self.full_pool_evictions(&meta, replace_hash, replaced_count, replaced_size)?;
+        if let Some(replace_hash) = replace_hash {
+            self.evicted_pool.insert(
+                replace_hash,
+                TransactionStatus::Dropped(format!("replaced by txn {}", hash.to_hex())),
+            );
+            self.delete_txn_hash(&replace_hash);
+        }
+        for evict_key in evict_keys.iter() {
+            self.evicted_pool.insert(
+                evict_key.hash,
+                TransactionStatus::Dropped(String::from("evicted from full txn pool")),
+            );
+            self.delete_txn_order(evict_key);
+        }
+        self.push_txn(value, meta, queue_key, received_timestamp);
+        Ok(())
+    }
+
+    /// txns which have to be evicted as per eviction policy to make room for a txn with
+    /// given meta, the pending txn it replaces is not counted. Err if the pool is full of
+    /// txns with the same or higher priority.
+    fn full_pool_evictions(
+        &self,
+        meta: &TxnMeta,
+        replace_hash: Option<Hash>,
+        replaced_count: usize,
+        replaced_size: usize,
+    ) -> Result<Vec<TxnOrderKey>, String> {
+        let mut pool_size: usize = self.order_pool.len() - replaced_count;
+        let mut pool_bytes: usize = self.pool_bytes - replaced_size;
+        let mut candidates: Box<dyn Iterator<Item = (u64, &TxnOrderKey)> + '_> =
+            match self.config.eviction_policy {
+                EVICTIONPOLICY::Oldest => Box::new(self.order_pool.keys().map(|key| (0, key))),
+                EVICTIONPOLICY::LowestPriority => Box::new(
+                    self.priority_pool
+                        .iter()
+                        .rev()
+                        .map(|(Reverse(priority), key)| (*priority, key)),
+                ),
+            };
+        let mut evict_keys: Vec<TxnOrderKey> = Vec::new();
+        while pool_size >= self.config.max_pool_size
+            || pool_bytes + meta.size > self.config.max_pool_bytes
+        {
+            let (priority, order_key) = match candidates
+                .by_ref()
+                .find(|(_, key)| Some(key.hash) != replace_hash)
+            {
+                Some(candidate) => candidate,
+                None => break,
+            };
+            if self.config.eviction_policy == EVICTIONPOLICY::LowestPriority
+                && priority >= meta.priority
+            {
+                return Err(String::from("txn pool is full"));
+            }
+            if let Some(evict_meta) = self.meta_pool.get(&order_key.hash) {
+                pool_bytes = pool_bytes - evict_meta.size;
+            }
+            pool_size = pool_size - 1;
+            evict_keys.push(order_key.clone());
+        }
+        Ok(evict_keys)
+    }
+
+    /// this function will push txn of a block committed by other node without checking
+    /// pool limits. Pending txn with the same sender & nonce can't be executed anymore,
+    /// it is replaced by the committed one.
+    pub fn insert_committed_txn(&mut self, value: &TxnPoolValueType, meta: TxnMeta) {
+        let hash: Hash = value.object_hash();
+        if self.hash_pool.contains_key(&hash) {
+            return;
+        }
+        let queue_key: Option<NonceQueueKey> = match (&meta.sender, meta.nonce) {
+            (Some(sender), Some(_)) => Some((value.app_name.clone(), sender.clone())),
+            _ => None,
+        };
+        let replace_hash: Option<Hash> = match (&queue_key, meta.nonce) {
+            (Some(queue_key), Some(nonce)) => match self.nonce_pool.get(queue_key) {
+                Some(queue) => queue.get(&nonce).cloned(),
+                None => None,
+            },
+            _ => None,
+        };
+        if let Some(replace_hash) = replace_hash {
+            self.evicted_pool.insert(
+                replace_hash,
+                TransactionStatus::Dropped(format!("replaced by txn {}", hash.to_hex())),
+            );
+            self.delete_txn_hash(&replace_hash);
+        }
+        let received_timestamp: TxnPoolKeyType = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        self.push_txn(value, meta, queue_key, received_timestamp);
+    }
+
+    /// this function will push value with its meta in all pools keyed by given receive
+    /// time, limits are checked by caller
+    fn push_txn(
+        &mut self,
+        value: &TxnPoolValueType,
+        meta: TxnMeta,
+        queue_key: Option<NonceQueueKey>,
+        received_timestamp: TxnPoolKeyType,
+    ) {
+        let hash: Hash = value.object_hash();
+        self.sequence = self.sequence + 1;
+        let order_key: TxnOrderKey = TxnOrderKey {
+            timestamp: received_timestamp,
+            sequence: self.sequence,
+            hash,
+        };
+        match &meta.sender {
+            Some(sender) => *self.sender_count.entry(sender.clone()).or_insert(0) += 1,
+            None => *self.app_count.entry(value.app_name.clone()).or_insert(0) += 1,
+        }
+        if let (Some(queue_key), Some(nonce)) = (queue_key, meta.nonce) {
+            self.nonce_pool
+                .entry(queue_key)
+                .or_insert_with(BTreeMap::new)
+                .insert(nonce, hash);
+        }
+        self.pool_bytes = self.pool_bytes + meta.size;
+        self.priority_pool
+            .insert((Reverse(meta.priority), order_key.clone()));
+        self.meta_pool.insert(hash, meta);
+        self.hash_pool.insert(hash, order_key.clone());
+        self.order_pool.insert(order_key, value.clone());
+        if self.journal.is_some() {
+            self.journal_batch.insert(
+                hash,
+                Some(JournaledTxn {
+                    txn: value.clone(),
+                    received_timestamp,
+                }),
+            );
+        }
+    }
+
+    /// this function will hand over journal writes collected since the last call along
+    /// with the journal they are meant for, None if there is nothing to write
+    pub fn take_journal_batch(&mut self) -> Option<(Arc<dyn PoolJournal>, JournalBatch)> {
+        match &self.journal {
+            Some(journal) if !self.journal_batch.is_empty() => Some((
+                journal.clone(),
+                std::mem::replace(&mut self.journal_batch, HashMap::new()),
+            )),
+            _ => None,
+        }
+    }
+
+    /// serialized size of all pooled txns
+    pub fn pool_bytes(&self) -> usize {
+        self.pool_bytes
+    }
+
+    /// nonce queue key of given pooled txn, None if its app doesn't order txns by nonce
+    fn nonce_queue_key(&self, txn: &TxnPoolValueType, key: &Hash) -> Option<NonceQueueKey> {
+        match self.meta_pool.get(key) {
+            Some(TxnMeta {
+                sender: Some(sender),
+                nonce: Some(_),
+                ..
+            }) => Some((txn.app_name.clone(), sender.clone())),
+            _ => None,
+        }
+    }
+
+    /// this function will execute given pooled txn if it isn't pooled for longer than the
+    /// delay limit & its app is known. returns true when the txn got executed and added in
+    /// temp_vec
+    fn execute_txn(
+        &self,
+        order_key: &TxnOrderKey,
+        sign_txn: &TxnPoolValueType,
+        block_context: &mut BlockStateContext,
+        block_height: u64,
+        temp_vec: &mut Vec<Hash>,
+        remove_txn_list: &mut Vec<Hash>,
+    ) -> bool {
+        let block_config: &BlockConfig = &configreader::GLOBAL_CONFIG.block_config;
+        let current_timestamp: TxnPoolKeyType = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        let txn_hash: Hash = order_key.hash;
+        let timestamp: TxnPoolKeyType = order_key.timestamp;
+        if current_timestamp
+            > timestamp.saturating_add(block_config.transaction_execution_delay_limit)
+        {
+            remove_txn_list.push(txn_hash);
+        } else {
+            match APPDATA.lock().unwrap().appdata.get(&sign_txn.app_name) {
+                Some(app) => {
+                    let result =
+                        block_context.execute_checkpointed(&**app.lock().unwrap(), sign_txn);
+                    block_context.put_receipt_with_events(
+                        &txn_hash,
+                        &sign_txn.app_name,
+                        &result,
+                        block_height,
+                        temp_vec.len() as u64,
+                    );
+                    temp_vec.push(txn_hash);
+                    debug!("transaction with hash {:?} executed", txn_hash);
+                    return true;
+                }
+                None => {
+                    remove_txn_list.push(txn_hash);
+                    info!("unknown app transaction came for execution");
                 }
             }
         }
+        false
+    }
+}
+
+impl Pool {
+    /// this function will release the pool lock and write journal batch collected under it,
+    /// journal_order is taken before the release so batches can't overtake each other
+    fn release_and_journal(&self, mut txn_pool: MutexGuard<TransactionPool>) {
+        if let Some((journal, batch)) = txn_pool.take_journal_batch() {
+            let _order = self.journal_order.lock().unwrap();
+            drop(txn_pool);
+            journal.write(&batch);
+        }
     }
 }
 
@@ -120,25 +880,48 @@ impl TxnPool for Pool {
     fn new() -> Pool {
         Pool {
             pool: Arc::new(Mutex::new(TransactionPool::new())),
+            journal_order: Mutex::new(()),
         }
     }
 
-    /// this function will delete txn using hash if present, from hash_pool
+    /// this function will delete txn using hash if present, from both (hash & order) pool
     fn delete_txn_hash(&self, key: &Hash) {
         let mut txn_pool = self.pool.lock().unwrap();
         txn_pool.delete_txn_hash(key);
+        self.release_and_journal(txn_pool);
     }
 
-    /// this function will delete txn using order_value if present, from order_pool
-    fn delete_txn_order(&self, key: &TxnPoolKeyType) {
+    /// this function will delete txn using order_key if present, from both (hash & order) pool
+    fn delete_txn_order(&self, key: &TxnOrderKey) {
         let mut txn_pool = self.pool.lock().unwrap();
         txn_pool.delete_txn_order(key);
+        self.release_and_journal(txn_pool);
+    }
+
+    /// this function will push value in both (hash & order) pool, Err carries
+    /// the reason when txn is rejected
+    fn insert_op(&self, value: &TxnPoolValueType) -> Result<(), String> {
+        let mut txn_pool = self.pool.lock().unwrap();
+        let result: Result<(), String> = txn_pool.insert_op(value);
+        self.release_and_journal(txn_pool);
+        result
+    }
+
+    /// this function will push journaled txn back keyed by the time the pool first
+    /// received it, Err carries the reason when txn is rejected
+    fn restore_op(&self, value: &JournaledTxn) -> Result<(), String> {
+        let mut txn_pool = self.pool.lock().unwrap();
+        let result: Result<(), String> = txn_pool.restore_op(value);
+        self.release_and_journal(txn_pool);
+        result
     }
 
-    /// this function will push value in both (hash & order) pool
-    fn insert_op(&self, key: &TxnPoolKeyType, value: &TxnPoolValueType) {
+    /// this function will push txn of a block committed by other node, pool limits are
+    /// bypassed as the block can't be synced without executing it
+    fn insert_committed(&self, value: &TxnPoolValueType) {
         let mut txn_pool = self.pool.lock().unwrap();
-        txn_pool.insert_op(key, value);
+        txn_pool.insert_committed(value);
+        self.release_and_journal(txn_pool);
     }
 
     /// length of order_pool
@@ -163,6 +946,40 @@ impl TxnPool for Pool {
     fn sync_pool(&self, txn_hash_vec: &Vec<Hash>) {
         let mut txn_pool = self.pool.lock().unwrap();
         txn_pool.sync_pool(txn_hash_vec);
+        self.release_and_journal(txn_pool);
+    }
+
+    /// remove txns which couldn't be executed and keep the reason in evicted_pool
+    fn evict_txns(&self, txn_hash_vec: &Vec<Hash>) {
+        let mut txn_pool = self.pool.lock().unwrap();
+        txn_pool.evict_txns(txn_hash_vec);
+        self.release_and_journal(txn_pool);
+    }
+
+    /// get evicted txn status using hash from evicted_pool
+    fn get_evicted_status(&self, key: &Hash) -> Option<TransactionStatus> {
+        let txn_pool = self.pool.lock().unwrap();
+        txn_pool.get_evicted_status(key)
+    }
+
+    /// replace pool limits, already pooled txns are kept as it is
+    fn set_config(&self, config: PoolConfig) {
+        let mut txn_pool = self.pool.lock().unwrap();
+        txn_pool.set_config(config);
+    }
+
+    /// this function will remove txns older than delay_limit and mark them as expired
+    fn expire_txns(&self, current_timestamp: TxnPoolKeyType, delay_limit: u128) -> usize {
+        let mut txn_pool = self.pool.lock().unwrap();
+        let expired_count: usize = txn_pool.expire_txns(current_timestamp, delay_limit);
+        self.release_and_journal(txn_pool);
+        expired_count
+    }
+
+    /// attach journal, txns already in pool are not written to it
+    fn set_journal(&self, journal: Arc<dyn PoolJournal>) {
+        let mut txn_pool = self.pool.lock().unwrap();
+        txn_pool.set_journal(journal);
     }
 }
 
@@ -170,46 +987,100 @@ impl<T: Access> PoolTrait<T, State, SignedTransaction> for TransactionPool
 where
     T::Base: RawAccessMut,
 {
-    fn execute_transactions(&self, state_context: &mut dyn StateContext) -> (Vec<Hash>, Vec<Hash>) {
+    fn execute_transactions(
+        &self,
+        state_context: &mut dyn ChainStateContext,
+        block_height: u64,
+        block_producer: &String,
+    ) -> (Vec<Hash>, Vec<Hash>) {
+        let mut block_context: BlockStateContext =
+            BlockStateContext::new(state_context, block_producer);
         let mut temp_vec: Vec<Hash> = Vec::with_capacity(15);
         // compute until order_pool exhusted or transaction limit crossed
         // let txn_pool = self.pool.lock().unwrap();
 
         let block_config: &BlockConfig = &configreader::GLOBAL_CONFIG.block_config;
+        let txn_limit: usize = block_config.block_transaction_limit as usize;
         // delayed transaction & transaction from unknown app will be added
         // in this list. This list will be used to sync-up txn_pool.
         let mut remove_txn_list: Vec<Hash> = Vec::new();
-        let current_timestamp: TxnPoolKeyType = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_micros();
-        for (timestamp, sign_txn) in self.order_pool.iter() {
-            if temp_vec.len() < block_config.block_transaction_limit as usize {
-                let txn_hash: Hash = sign_txn.object_hash();
-                // check is transaction already added in the previously
-                if !state_context.contains_txn(&txn_hash) {
-                    let timestamp: TxnPoolKeyType = timestamp.clone();
-                    if current_timestamp
-                        > (timestamp + block_config.transaction_execution_delay_limit)
-                    {
-                        remove_txn_list.push(txn_hash);
-                    } else if current_timestamp > timestamp {
-                        match APPDATA.lock().unwrap().appdata.get(&sign_txn.app_name) {
-                            Some(app) => {
-                                app.lock().unwrap().execute(sign_txn, state_context);
-                                temp_vec.push(txn_hash);
-                                debug!("transaction with hash {:?} executed", txn_hash);
-                            }
-                            None => {
-                                remove_txn_list.push(txn_hash);
-                                debug!("transaction with hash {:?} executed", txn_hash);
-                                info!("unknown app transaction came for execution");
-                            }
-                        }
+        // nonce queues already processed for this block
+        let mut visited_queues: HashSet<NonceQueueKey> = HashSet::new();
+        // higher fee txns are executed first, same fee txns in arrival order
+        for (_, order_key) in self.priority_pool.iter() {
+            if temp_vec.len() >= txn_limit || block_context.is_exhausted() {
+                break;
+            }
+            let sign_txn: &TxnPoolValueType = match self.order_pool.get(order_key) {
+                Some(sign_txn) => sign_txn,
+                None => continue,
+            };
+            // check is transaction already added in the previously
+            if block_context.contains_txn(&order_key.hash) {
+                continue;
+            }
+            let queue_key: NonceQueueKey = match self.nonce_queue_key(sign_txn, &order_key.hash) {
+                Some(queue_key) => queue_key,
+                None => {
+                    self.execute_txn(
+                        order_key,
+                        sign_txn,
+                        &mut block_context,
+                        block_height,
+                        &mut temp_vec,
+                        &mut remove_txn_list,
+                    );
+                    continue;
+                }
+            };
+            // txns of a nonce ordered account are executed from its queue in nonce order,
+            // future nonce txns are held in the pool until the gap fills
+            if !visited_queues.insert(queue_key.clone()) {
+                continue;
+            }
+            let queue: &BTreeMap<u64, Hash> = match self.nonce_pool.get(&queue_key) {
+                Some(queue) => queue,
+                None => continue,
+            };
+            for (nonce, queued_hash) in queue.iter() {
+                if temp_vec.len() >= txn_limit || block_context.is_exhausted() {
+                    break;
+                }
+                if block_context.contains_txn(queued_hash) {
+                    continue;
+                }
+                let next_nonce: Option<u64> =
+                    match APPDATA.lock().unwrap().appdata.get(&queue_key.0) {
+                        Some(app) => app.lock().unwrap().next_nonce(
+                            &queue_key.1,
+                            &NamespacedStateContext::new(&mut block_context, &queue_key.0),
+                        ),
+                        None => None,
+                    };
+                if let Some(next_nonce) = next_nonce {
+                    if *nonce > next_nonce {
+                        debug!("txn with future nonce {} held in txn pool", nonce);
+                        break;
                     }
                 }
-            } else {
-                break;
+                let queued_key: &TxnOrderKey = match self.hash_pool.get(queued_hash) {
+                    Some(queued_key) => queued_key,
+                    None => break,
+                };
+                let queued_txn: &TxnPoolValueType = match self.order_pool.get(queued_key) {
+                    Some(queued_txn) => queued_txn,
+                    None => break,
+                };
+                if !self.execute_txn(
+                    queued_key,
+                    queued_txn,
+                    &mut block_context,
+                    block_height,
+                    &mut temp_vec,
+                    &mut remove_txn_list,
+                ) {
+                    break;
+                }
             }
         }
         (temp_vec, remove_txn_list)
@@ -217,14 +1088,18 @@ where
 
     fn update_transactions(
         &self,
-        state_context: &mut dyn StateContext,
+        state_context: &mut dyn ChainStateContext,
         hash_vec: &Vec<Hash>,
+        block_height: u64,
+        block_producer: &String,
     ) -> bool {
+        let mut block_context: BlockStateContext =
+            BlockStateContext::new(state_context, block_producer);
         // compute until order_pool exhusted or transaction limit crossed
         // let txn_pool = self.pool.lock().unwrap();
-        for each in hash_vec.iter() {
+        for (index, each) in hash_vec.iter().enumerate() {
             let signed_txn = self.get(each);
-            if state_context.contains_txn(each) {
+            if block_context.contains_txn(each) {
                 warn!("try to execute duplicate transaction");
                 return false;
             }
@@ -232,7 +1107,15 @@ where
                 match APPDATA.lock().unwrap().appdata.get(&txn.app_name) {
                     Some(app) => {
                         debug!("transaction with hash {:?} updated", each);
-                        app.lock().unwrap().execute(&txn, state_context);
+                        let result =
+                            block_context.execute_checkpointed(&**app.lock().unwrap(), &txn);
+                        block_context.put_receipt_with_events(
+                            each,
+                            &txn.app_name,
+                            &result,
+                            block_height,
+                            index as u64,
+                        );
                     }
                     None => {
                         warn!("unknown app transaction bypassed in update_transaction process");
@@ -251,6 +1134,21 @@ lazy_static! {
     pub static ref POOL: Pool = Pool::new();
 }
 
+/// this function will spawn a thread which periodically sweeps expired txns out of POOL
+pub fn start_expiry_sweeper(sweep_interval: u64, delay_limit: u128) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_millis(sweep_interval));
+        let current_timestamp: TxnPoolKeyType = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        let expired_count: usize = POOL.expire_txns(current_timestamp, delay_limit);
+        if expired_count > 0 {
+            info!("{} expired txns swept from txn pool", expired_count);
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests_transaction_pool {
 
@@ -292,11 +1190,7 @@ mod tests_transaction_pool {
         for fxn in TXN_FXN_ARR.iter() {
             let signed_txn = prepare_transaction(fxn.to_string());
             stxn_arr.push(signed_txn.clone());
-            if let Some(string) = signed_txn.header.get(&String::from("timestamp")) {
-                if let Ok(timestamp) = string.parse::<TxnPoolKeyType>() {
-                    temp_pool.insert_op(&timestamp, &signed_txn);
-                }
-            }
+            assert_eq!(temp_pool.insert_op(&signed_txn), Ok(()));
         }
         assert_eq!(
             temp_pool.length_order_pool(),
@@ -318,6 +1212,331 @@ mod tests_transaction_pool {
         assert_eq!(temp_pool.length_hash_pool(), 0, "Issue with sync_pool");
     }
 
+    #[test]
+    pub fn test_transaction_pool_order_key() {
+        let temp_pool: Pool = Pool::new();
+        let first_txn = prepare_transaction(String::from("transfer_sc"));
+        let mut second_txn = first_txn.clone();
+        second_txn.txn = vec![1];
+        // same timestamp header shouldn't overwrite other txn
+        assert_eq!(temp_pool.insert_op(&first_txn), Ok(()));
+        assert_eq!(temp_pool.insert_op(&second_txn), Ok(()));
+        // duplicate txn shouldn't be added twice
+        assert_eq!(temp_pool.insert_op(&second_txn), Ok(()));
+        assert_eq!(temp_pool.length_order_pool(), 2);
+        assert_eq!(temp_pool.length_hash_pool(), 2);
+        {
+            let txn_pool = temp_pool.pool.lock().unwrap();
+            let order: Vec<Hash> = txn_pool.order_pool.keys().map(|key| key.hash).collect();
+            assert_eq!(
+                order,
+                vec![first_txn.object_hash(), second_txn.object_hash()]
+            );
+        }
+
+        let order_key: TxnOrderKey = {
+            let txn_pool = temp_pool.pool.lock().unwrap();
+            txn_pool
+                .hash_pool
+                .get(&first_txn.object_hash())
+                .unwrap()
+                .clone()
+        };
+        temp_pool.delete_txn_order(&order_key);
+        assert_eq!(temp_pool.get(&first_txn.object_hash()), None);
+        assert_eq!(temp_pool.length_order_pool(), 1);
+        assert_eq!(temp_pool.length_hash_pool(), 1);
+
+        // txn without timestamp header should be rejected
+        let mut no_timestamp_txn = first_txn.clone();
+        no_timestamp_txn.header.clear();
+        assert!(temp_pool.insert_op(&no_timestamp_txn).is_err());
+        assert_eq!(temp_pool.length_order_pool(), 1);
+
+        // txns are keyed by receive time of the pool, not by the client timestamp header
+        let received_after: TxnPoolKeyType = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        let mut old_header_txn = first_txn.clone();
+        old_header_txn
+            .header
+            .insert(String::from("timestamp"), String::from("1"));
+        assert_eq!(temp_pool.insert_op(&old_header_txn), Ok(()));
+        let txn_pool = temp_pool.pool.lock().unwrap();
+        let order_key: &TxnOrderKey = &txn_pool.hash_pool[&old_header_txn.object_hash()];
+        assert!(order_key.timestamp >= received_after);
+        assert_eq!(
+            txn_pool.order_pool.keys().last().unwrap().hash,
+            old_header_txn.object_hash()
+        );
+    }
+
+    #[test]
+    pub fn test_evicted_transactions() {
+        let temp_pool: Pool = Pool::new();
+        let signed_txn = prepare_transaction(String::from("transfer_sc"));
+        let txn_hash: Hash = signed_txn.object_hash();
+        temp_pool.insert_op(&signed_txn).unwrap();
+        assert_eq!(temp_pool.get_evicted_status(&txn_hash), None);
+        temp_pool.evict_txns(&vec![txn_hash]);
+        assert_eq!(temp_pool.length_order_pool(), 0, "Issue with evict_txns");
+        assert_eq!(temp_pool.length_hash_pool(), 0, "Issue with evict_txns");
+        assert_eq!(
+            temp_pool.get_evicted_status(&txn_hash),
+            Some(TransactionStatus::Dropped(String::from(
+                "unknown app Mockcurrency"
+            )))
+        );
+
+        let mut evicted_txns: EvictedTxns = EvictedTxns::new();
+        for index in 0..(EVICTED_TXN_LIMIT + 1) {
+            let hash: Hash = exonum_crypto::hash(&index.to_be_bytes());
+            evicted_txns.insert(hash, TransactionStatus::Expired);
+        }
+        assert_eq!(evicted_txns.len(), EVICTED_TXN_LIMIT);
+        assert_eq!(
+            evicted_txns.get(&exonum_crypto::hash(&0_usize.to_be_bytes())),
+            None
+        );
+    }
+
+    #[test]
+    pub fn test_transaction_pool_limits() {
+        let mut temp_pool: TransactionPool = TransactionPool::new();
+        let mut config: PoolConfig = PoolConfig::default();
+        config.max_pool_size = 2;
+        config.max_txns_per_sender = 1;
+        temp_pool.set_config(config.clone());
+        let mut txns: Vec<SignedTransaction> = Vec::new();
+        for index in 0..3 {
+            let mut signed_txn = prepare_transaction(String::from("transfer_sc"));
+            signed_txn.txn = vec![index];
+            txns.push(signed_txn);
+        }
+        let meta = |sender: &str, priority: u64| TxnMeta {
+            sender: Some(String::from(sender)),
+            nonce: None,
+            priority,
+            size: 10,
+        };
+
+        // per sender limit
+        assert_eq!(temp_pool.insert_txn(&txns[0], meta("alice", 1)), Ok(()));
+        assert!(temp_pool.insert_txn(&txns[1], meta("alice", 1)).is_err());
+        assert_eq!(temp_pool.insert_txn(&txns[1], meta("bob", 2)), Ok(()));
+        assert_eq!(temp_pool.pool_bytes(), 20);
+
+        // oldest txn evicted when pool is full
+        assert_eq!(temp_pool.insert_txn(&txns[2], meta("carol", 0)), Ok(()));
+        assert_eq!(temp_pool.length_order_pool(), 2);
+        assert_eq!(temp_pool.get(&txns[0].object_hash()), None);
+        assert_eq!(
+            temp_pool.get_evicted_status(&txns[0].object_hash()),
+            Some(TransactionStatus::Dropped(String::from(
+                "evicted from full txn pool"
+            )))
+        );
+        // evicted sender is allowed again
+        assert_eq!(temp_pool.sender_count.get("alice"), None);
+
+        // lowest priority txn evicted, lower priority newcomer rejected
+        config.eviction_policy = EVICTIONPOLICY::LowestPriority;
+        temp_pool.set_config(config.clone());
+        assert!(temp_pool.insert_txn(&txns[0], meta("alice", 0)).is_err());
+        assert_eq!(temp_pool.insert_txn(&txns[0], meta("alice", 5)), Ok(()));
+        assert_eq!(temp_pool.get(&txns[2].object_hash()), None);
+        assert!(temp_pool.get(&txns[1].object_hash()).is_some());
+        // higher fee txn comes first for block building
+        let first_hash: Hash = temp_pool.priority_pool.iter().next().unwrap().1.hash;
+        assert_eq!(first_hash, txns[0].object_hash());
+
+        // bytes limit
+        config.max_pool_bytes = 5;
+        temp_pool.set_config(config.clone());
+        assert!(temp_pool.insert_txn(&txns[2], meta("dave", 9)).is_err());
+
+        // txn timestamped further ahead than allowed drift can't squat the pool
+        config.max_pool_bytes = PoolConfig::default().max_pool_bytes;
+        config.max_timestamp_drift = 1000;
+        temp_pool.set_config(config);
+        let mut future_txn = txns[2].clone();
+        let future_timestamp: TxnPoolKeyType = txn_timestamp(&future_txn).unwrap() + 60_000_000;
+        future_txn
+            .header
+            .insert(String::from("timestamp"), future_timestamp.to_string());
+        assert_eq!(
+            temp_pool.insert_txn(&future_txn, meta("erin", 9)),
+            Err(String::from("txn timestamp header too far ahead"))
+        );
+
+        // expiry sweep
+        let expired_count: usize = temp_pool.expire_txns(TxnPoolKeyType::max_value(), 0);
+        assert_eq!(expired_count, 2);
+        assert_eq!(temp_pool.length_hash_pool(), 0);
+        assert_eq!(temp_pool.pool_bytes(), 0);
+        assert_eq!(
+            temp_pool.get_evicted_status(&txns[1].object_hash()),
+            Some(TransactionStatus::Expired)
+        );
+    }
+
+    #[test]
+    pub fn test_transaction_pool_nonce_queue() {
+        let mut temp_pool: TransactionPool = TransactionPool::new();
+        let mut txns: Vec<SignedTransaction> = Vec::new();
+        for index in 0..3 {
+            let mut signed_txn = prepare_transaction(String::from("transfer"));
+            signed_txn.txn = vec![index];
+            txns.push(signed_txn);
+        }
+        let meta = |nonce: u64, priority: u64| TxnMeta {
+            sender: Some(String::from("alice")),
+            nonce: Some(nonce),
+            priority,
+            size: 10,
+        };
+        let queue_key: NonceQueueKey = (String::from("Mockcurrency"), String::from("alice"));
+
+        // future nonce is kept in the queue sorted by nonce
+        assert_eq!(temp_pool.insert_txn(&txns[0], meta(3, 1)), Ok(()));
+        assert_eq!(temp_pool.insert_txn(&txns[1], meta(2, 1)), Ok(()));
+        let queue: Vec<u64> = temp_pool.nonce_pool[&queue_key].keys().cloned().collect();
+        assert_eq!(queue, vec![2, 3]);
+
+        // same nonce with lower priority is rejected, otherwise replaced
+        assert!(temp_pool.insert_txn(&txns[2], meta(2, 0)).is_err());
+        assert_eq!(temp_pool.insert_txn(&txns[2], meta(2, 1)), Ok(()));
+        assert_eq!(temp_pool.length_order_pool(), 2);
+        assert_eq!(temp_pool.get(&txns[1].object_hash()), None);
+        assert_eq!(
+            temp_pool.get_evicted_status(&txns[1].object_hash()),
+            Some(TransactionStatus::Dropped(format!(
+                "replaced by txn {}",
+                txns[2].object_hash().to_hex()
+            )))
+        );
+        assert_eq!(temp_pool.nonce_pool[&queue_key][&2], txns[2].object_hash());
+        assert_eq!(temp_pool.sender_count["alice"], 2);
+
+        temp_pool.sync_pool(&vec![txns[0].object_hash(), txns[2].object_hash()]);
+        assert_eq!(temp_pool.nonce_pool.get(&queue_key), None);
+        assert_eq!(temp_pool.sender_count.get("alice"), None);
+    }
+
+    #[test]
+    pub fn test_transaction_pool_rejected_replacement() {
+        let mut temp_pool: TransactionPool = TransactionPool::new();
+        let mut config: PoolConfig = PoolConfig::default();
+        config.max_pool_size = 2;
+        config.max_pool_bytes = 55;
+        config.eviction_policy = EVICTIONPOLICY::LowestPriority;
+        temp_pool.set_config(config);
+        let mut txns: Vec<SignedTransaction> = Vec::new();
+        for index in 0..3 {
+            let mut signed_txn = prepare_transaction(String::from("transfer"));
+            signed_txn.txn = vec![index];
+            txns.push(signed_txn);
+        }
+        let meta = |sender: &str, priority: u64, size: usize| TxnMeta {
+            sender: Some(String::from(sender)),
+            nonce: Some(1),
+            priority,
+            size,
+        };
+        assert_eq!(temp_pool.insert_txn(&txns[0], meta("alice", 1, 10)), Ok(()));
+        assert_eq!(temp_pool.insert_txn(&txns[1], meta("bob", 9, 10)), Ok(()));
+
+        // replacement needs bob's higher priority txn evicted, it is rejected and the
+        // pending txn of alice is kept
+        assert_eq!(
+            temp_pool.insert_txn(&txns[2], meta("alice", 2, 50)),
+            Err(String::from("txn pool is full"))
+        );
+        assert!(temp_pool.get(&txns[0].object_hash()).is_some());
+        assert!(temp_pool.get(&txns[1].object_hash()).is_some());
+        assert_eq!(temp_pool.get_evicted_status(&txns[0].object_hash()), None);
+        assert_eq!(temp_pool.pool_bytes(), 20);
+
+        // replacement which fits in the room it frees goes through
+        assert_eq!(temp_pool.insert_txn(&txns[2], meta("alice", 2, 40)), Ok(()));
+        assert_eq!(temp_pool.get(&txns[0].object_hash()), None);
+        assert!(temp_pool.get(&txns[1].object_hash()).is_some());
+        assert_eq!(temp_pool.pool_bytes(), 50);
+    }
+
+    #[test]
+    pub fn test_transaction_pool_senderless_limit() {
+        let mut temp_pool: TransactionPool = TransactionPool::new();
+        let mut config: PoolConfig = PoolConfig::default();
+        config.max_txns_per_app = 1;
+        temp_pool.set_config(config);
+        let mut txns: Vec<SignedTransaction> = Vec::new();
+        for index in 0..2 {
+            let mut signed_txn = prepare_transaction(String::from("execute"));
+            signed_txn.txn = vec![index];
+            txns.push(signed_txn);
+        }
+        let meta = || TxnMeta {
+            sender: None,
+            nonce: None,
+            priority: 0,
+            size: 10,
+        };
+
+        // txns of an app reporting no sender share one pending limit
+        assert_eq!(temp_pool.insert_txn(&txns[0], meta()), Ok(()));
+        assert_eq!(
+            temp_pool.insert_txn(&txns[1], meta()),
+            Err(String::from(
+                "app Mockcurrency crossed pending limit of txns without sender"
+            ))
+        );
+        temp_pool.sync_pool(&vec![txns[0].object_hash()]);
+        assert_eq!(temp_pool.app_count.get("Mockcurrency"), None);
+        assert_eq!(temp_pool.insert_txn(&txns[1], meta()), Ok(()));
+        assert_eq!(temp_pool.app_count["Mockcurrency"], 1);
+    }
+
+    #[test]
+    pub fn test_transaction_pool_committed_txns() {
+        let mut temp_pool: TransactionPool = TransactionPool::new();
+        let mut config: PoolConfig = PoolConfig::default();
+        config.max_pool_size = 1;
+        config.max_txns_per_sender = 1;
+        config.eviction_policy = EVICTIONPOLICY::LowestPriority;
+        temp_pool.set_config(config);
+        let mut txns: Vec<SignedTransaction> = Vec::new();
+        for index in 0..3 {
+            let mut signed_txn = prepare_transaction(String::from("transfer"));
+            signed_txn.txn = vec![index];
+            txns.push(signed_txn);
+        }
+        let meta = |sender: &str, nonce: u64, priority: u64| TxnMeta {
+            sender: Some(String::from(sender)),
+            nonce: Some(nonce),
+            priority,
+            size: 10,
+        };
+        assert_eq!(temp_pool.insert_txn(&txns[0], meta("alice", 1, 5)), Ok(()));
+        assert!(temp_pool.insert_txn(&txns[2], meta("bob", 1, 0)).is_err());
+
+        // committed txns get in whatever the limits, pending txn of same nonce is replaced
+        temp_pool.insert_committed_txn(&txns[1], meta("alice", 1, 0));
+        temp_pool.insert_committed_txn(&txns[2], meta("bob", 1, 0));
+        assert_eq!(temp_pool.length_order_pool(), 2);
+        assert_eq!(temp_pool.get(&txns[0].object_hash()), None);
+        assert!(temp_pool.get(&txns[1].object_hash()).is_some());
+        assert!(temp_pool.get(&txns[2].object_hash()).is_some());
+        assert_eq!(temp_pool.sender_count["alice"], 1);
+
+        // expiry deadline saturates instead of overflowing
+        assert_eq!(
+            temp_pool.expire_txns(TxnPoolKeyType::max_value(), u128::max_value()),
+            0
+        );
+    }
+
     pub fn prepare_transaction(_txn_fxn: String) -> SignedTransaction {
         let mock_app = "Mockcurrency";
         let serialized_txn = vec![];
@@ -0,0 +1,13 @@
+use std::env;
+use std::process::Command;
+
+/// exports rustc version the sdk is compiled with, it is part of app ABI manifest
+fn main() {
+    let rustc: String = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let rustc_version: String = match Command::new(rustc).arg("--version").output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        Err(_) => String::from("unknown"),
+    };
+    println!("cargo:rustc-env=SDK_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
@@ -0,0 +1,183 @@
+use std::ffi::CString;
+
+/// version of the app loading contract between node & app libraries, it changes
+/// whenever AppHandler or the types crossing the library boundary change
+pub const ABI_VERSION: u32 = 1;
+/// version of this sdk
+pub const SDK_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// rustc this sdk is compiled with, Rust types have no stable layout across compilers
+pub const RUSTC_VERSION: &str = env!("SDK_RUSTC_VERSION");
+/// symbol of `extern "C" fn() -> *const c_char` returning the encoded manifest
+pub const MANIFEST_SYMBOL: &[u8] = b"app_manifest";
+/// symbol of `fn() -> Box<dyn AppHandler + Send>` creating the app
+pub const REGISTER_SYMBOL: &[u8] = b"register_app";
+
+/// AppManifest describes what an app library is built against. It crosses the library
+/// boundary as a C string, so the node can read it before trusting any Rust type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppManifest {
+    pub abi_version: u32,
+    pub sdk_version: String,
+    pub rustc_version: String,
+    pub app_name: String,
+    pub app_version: String,
+}
+
+impl AppManifest {
+    /// manifest of an app compiled against this sdk
+    pub fn new(app_name: &str, app_version: &str) -> AppManifest {
+        AppManifest {
+            abi_version: ABI_VERSION,
+            sdk_version: String::from(SDK_VERSION),
+            rustc_version: String::from(RUSTC_VERSION),
+            app_name: String::from(app_name),
+            app_version: String::from(app_version),
+        }
+    }
+
+    /// one "key=value" per line
+    pub fn encode(&self) -> String {
+        format!(
+            "abi_version={}\nsdk_version={}\nrustc_version={}\napp_name={}\napp_version={}",
+            self.abi_version, self.sdk_version, self.rustc_version, self.app_name, self.app_version
+        )
+    }
+
+    pub fn decode(encoded: &str) -> Result<AppManifest, String> {
+        let mut abi_version: Option<u32> = None;
+        let mut sdk_version: Option<String> = None;
+        let mut rustc_version: Option<String> = None;
+        let mut app_name: Option<String> = None;
+        let mut app_version: Option<String> = None;
+        for line in encoded.lines() {
+            let mut fields = line.splitn(2, '=');
+            let (key, value) = match (fields.next(), fields.next()) {
+                (Some(key), Some(value)) => (key, String::from(value)),
+                _ => return Err(format!("manifest line {:?} malformed", line)),
+            };
+            match key {
+                "abi_version" => match value.parse::<u32>() {
+                    Ok(version) => abi_version = Some(version),
+                    Err(_) => return Err(format!("manifest abi_version {} invalid", value)),
+                },
+                "sdk_version" => sdk_version = Some(value),
+                "rustc_version" => rustc_version = Some(value),
+                "app_name" => app_name = Some(value),
+                "app_version" => app_version = Some(value),
+                // unknown keys are left for newer sdk versions
+                _ => {}
+            }
+        }
+        match (
+            abi_version,
+            sdk_version,
+            rustc_version,
+            app_name,
+            app_version,
+        ) {
+            (
+                Some(abi_version),
+                Some(sdk_version),
+                Some(rustc_version),
+                Some(app_name),
+                Some(app_version),
+            ) => Ok(AppManifest {
+                abi_version,
+                sdk_version,
+                rustc_version,
+                app_name,
+                app_version,
+            }),
+            _ => Err(String::from("manifest incomplete")),
+        }
+    }
+
+    pub fn to_c_string(&self) -> CString {
+        // manifest values never contain NUL, drop any to keep the string valid
+        CString::new(self.encode().replace('\0', "")).unwrap_or_default()
+    }
+
+    /// this function will check manifest of a library against the sdk this node is built
+    /// with, Err carries the reason the app is refused
+    pub fn check_compatible(&self) -> Result<(), String> {
+        if self.abi_version != ABI_VERSION {
+            return Err(format!(
+                "app {} {} uses app ABI {}, node supports {}",
+                self.app_name, self.app_version, self.abi_version, ABI_VERSION
+            ));
+        }
+        if self.sdk_version != SDK_VERSION {
+            return Err(format!(
+                "app {} {} is built against sdk {}, node runs sdk {}",
+                self.app_name, self.app_version, self.sdk_version, SDK_VERSION
+            ));
+        }
+        if self.rustc_version != RUSTC_VERSION {
+            return Err(format!(
+                "app {} {} is built with {}, node is built with {}",
+                self.app_name, self.app_version, self.rustc_version, RUSTC_VERSION
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// export_app! exports the ABI manifest and `register_app` entry point of an app library,
+/// every app has to be exported through it to be loaded by the node.
+///
+/// ```ignore
+/// sdk::export_app!("Cryptocurrency", "0.1.0", CryptoApp::new(&String::from(APPNAME)));
+/// ```
+#[macro_export]
+macro_rules! export_app {
+    ($app_name:expr, $app_version:expr, $app:expr) => {
+        #[no_mangle]
+        pub extern "C" fn app_manifest() -> *const ::std::os::raw::c_char {
+            static INIT: ::std::sync::Once = ::std::sync::Once::new();
+            static mut MANIFEST: *const ::std::os::raw::c_char = ::std::ptr::null();
+            unsafe {
+                INIT.call_once(|| {
+                    MANIFEST = $crate::abi::AppManifest::new($app_name, $app_version)
+                        .to_c_string()
+                        .into_raw();
+                });
+                MANIFEST
+            }
+        }
+
+        #[no_mangle]
+        pub fn register_app() -> Box<dyn $crate::traits::AppHandler + Send> {
+            Box::new($app)
+        }
+    };
+}
+
+#[cfg(test)]
+mod test_abi {
+    use super::*;
+
+    #[test]
+    fn test_manifest_encoding_and_check() {
+        let manifest: AppManifest = AppManifest::new("app_name", "1.2.0");
+        let decoded: AppManifest = AppManifest::decode(&manifest.encode()).unwrap();
+        assert_eq!(decoded, manifest);
+        assert_eq!(decoded.check_compatible(), Ok(()));
+        assert_eq!(
+            manifest.to_c_string().into_string().unwrap(),
+            manifest.encode()
+        );
+
+        let mut other_sdk: AppManifest = manifest.clone();
+        other_sdk.sdk_version = String::from("0.0.1");
+        assert!(other_sdk.check_compatible().is_err());
+        let mut other_rustc: AppManifest = manifest.clone();
+        other_rustc.rustc_version = String::from("rustc 1.0.0");
+        assert!(other_rustc.check_compatible().is_err());
+        let mut other_abi: AppManifest = manifest.clone();
+        other_abi.abi_version = ABI_VERSION + 1;
+        assert!(other_abi.check_compatible().is_err());
+
+        assert!(AppManifest::decode("abi_version=1\nsdk_version=0.1.0").is_err());
+        assert!(AppManifest::decode("abi_version=one").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+/// payload type for apps whose calls carry no positional payload, their arguments
+/// are plain fields of the app txn
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoPayload {}
+
+/// app_calls declares the typed calls of an app. For every call it generates an enum
+/// variant, a method of the handler trait, decoding of `fxn_call` & positional payload
+/// into the variant (argument count & types are validated here) and dispatch of the
+/// decoded call into the handler. `$payload` is the app payload enum and every
+/// argument names the payload variant carrying it. Handler methods return Err with the
+/// reason of a failed call, it ends up in the txn receipt.
+///
+/// ```ignore
+/// sdk::app_calls! {
+///     pub enum DocCall: DataTypes => pub trait ModuleTraits {
+///         SetHash => fn set_hash(token_id: Hash = HashVal, file_hash: Hash = HashVal);
+///         PublishDocs => fn publish_docs(pkg_no: String = StringVal);
+///     }
+/// }
+/// ```
+///
+/// `fxn_call` of a call is its handler method name, e.g. "set_hash".
+#[macro_export]
+macro_rules! app_calls {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $call:ident : $payload:ident => $handler_vis:vis trait $handler:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident => fn $method:ident ( $( $arg:ident : $arg_ty:ty = $data_type:ident ),* $(,)? );
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        $vis enum $call {
+            $(
+                $(#[$variant_meta])*
+                $variant { $( $arg: $arg_ty ),* },
+            )*
+        }
+
+        $handler_vis trait $handler {
+            $(
+                fn $method(
+                    &self,
+                    state_context: &mut dyn $crate::traits::StateContext
+                    $(, $arg: $arg_ty)*
+                ) -> Result<(), String>;
+            )*
+        }
+
+        impl $call {
+            /// fxn_call names of all calls
+            pub const FXN_CALLS: &'static [&'static str] = &[$(stringify!($method)),*];
+
+            /// fxn_call name of this call
+            pub fn fxn_call(&self) -> &'static str {
+                match self {
+                    $( $call::$variant { .. } => stringify!($method), )*
+                }
+            }
+
+            /// decode fxn_call & payload into typed call, Err carries the reason
+            pub fn decode(fxn_call: &str, payload: &[$payload]) -> Result<$call, String> {
+                $(
+                    if fxn_call == stringify!($method) {
+                        #[allow(unused_mut)]
+                        let mut args = payload.iter();
+                        $(
+                            let $arg: $arg_ty = match args.next() {
+                                Some($payload::$data_type(value)) => value.clone(),
+                                Some(_) => {
+                                    return Err(format!(
+                                        "{} argument {} must be {}",
+                                        fxn_call,
+                                        stringify!($arg),
+                                        stringify!($data_type)
+                                    ))
+                                }
+                                None => {
+                                    return Err(format!(
+                                        "{} argument {} missing",
+                                        fxn_call,
+                                        stringify!($arg)
+                                    ))
+                                }
+                            };
+                        )*
+                        if args.next().is_some() {
+                            return Err(format!("{} called with extra arguments", fxn_call));
+                        }
+                        return Ok($call::$variant { $($arg),* });
+                    }
+                )*
+                Err(format!("unknown fxn_call {}", fxn_call))
+            }
+
+            /// call the handler method of this call, Err carries the reason of failure
+            pub fn dispatch<H: $handler + ?Sized>(
+                self,
+                handler: &H,
+                state_context: &mut dyn $crate::traits::StateContext,
+            ) -> Result<(), String> {
+                match self {
+                    $(
+                        $call::$variant { $($arg),* } => handler.$method(state_context $(, $arg)*),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test_dispatch {
+    use crate::test_utils::MemoryStateContext;
+    use crate::traits::StateContext;
+    use std::cell::RefCell;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Payload {
+        IntVal(i32),
+        StringVal(String),
+    }
+
+    app_calls! {
+        enum TestCall: Payload => trait TestHandler {
+            Rename => fn rename(name: String = StringVal);
+            Resize => fn resize(width: i32 = IntVal, height: i32 = IntVal);
+            Reset => fn reset();
+        }
+    }
+
+    struct Recorder {
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl TestHandler for Recorder {
+        fn rename(
+            &self,
+            _state_context: &mut dyn StateContext,
+            name: String,
+        ) -> Result<(), String> {
+            self.calls.borrow_mut().push(name);
+            Ok(())
+        }
+        fn resize(
+            &self,
+            _state_context: &mut dyn StateContext,
+            width: i32,
+            height: i32,
+        ) -> Result<(), String> {
+            self.calls
+                .borrow_mut()
+                .push(format!("{}x{}", width, height));
+            if width > 0 && height > 0 {
+                Ok(())
+            } else {
+                Err(String::from("size must be positive"))
+            }
+        }
+        fn reset(&self, _state_context: &mut dyn StateContext) -> Result<(), String> {
+            self.calls.borrow_mut().clear();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_app_calls_decode_and_dispatch() {
+        assert_eq!(TestCall::FXN_CALLS, &["rename", "resize", "reset"]);
+        let call: TestCall =
+            TestCall::decode("resize", &[Payload::IntVal(2), Payload::IntVal(3)]).unwrap();
+        assert_eq!(
+            call,
+            TestCall::Resize {
+                width: 2,
+                height: 3
+            }
+        );
+        assert_eq!(call.fxn_call(), "resize");
+        assert_eq!(
+            TestCall::decode("rename", &[Payload::IntVal(2)]),
+            Err(String::from("rename argument name must be StringVal"))
+        );
+        assert_eq!(
+            TestCall::decode("resize", &[Payload::IntVal(2)]),
+            Err(String::from("resize argument height missing"))
+        );
+        assert_eq!(
+            TestCall::decode("reset", &[Payload::IntVal(2)]),
+            Err(String::from("reset called with extra arguments"))
+        );
+        assert_eq!(
+            TestCall::decode("remove", &[]),
+            Err(String::from("unknown fxn_call remove"))
+        );
+
+        let recorder = Recorder {
+            calls: RefCell::new(Vec::new()),
+        };
+        let mut state_context = MemoryStateContext::new();
+        let call: TestCall =
+            TestCall::decode("rename", &[Payload::StringVal(String::from("doc"))]).unwrap();
+        assert_eq!(call.dispatch(&recorder, &mut state_context), Ok(()));
+        assert_eq!(
+            TestCall::Resize {
+                width: 0,
+                height: 1
+            }
+            .dispatch(&recorder, &mut state_context),
+            Err(String::from("size must be positive"))
+        );
+        assert_eq!(*recorder.calls.borrow(), vec!["doc", "0x1"]);
+    }
+}
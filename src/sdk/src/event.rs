@@ -0,0 +1,123 @@
+extern crate utils;
+use exonum_crypto::Hash;
+use std::convert::AsRef;
+
+use utils::serializer::{Deserialize, Serialize};
+
+/// Event is a structured log emitted by an app while executing a transaction,
+/// e.g. topic "transfer" with the serialized transfer as data.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, BinaryValue, ObjectHash)]
+#[binary_value(codec = "bincode")]
+pub struct Event {
+    app_name: String,
+    topic: String,
+    data: Vec<u8>,
+    txn_hash: Hash,
+    block_height: u64,
+    index: u64,
+}
+
+impl Event {
+    pub fn new(
+        app_name: String,
+        topic: String,
+        data: Vec<u8>,
+        txn_hash: Hash,
+        block_height: u64,
+        index: u64,
+    ) -> Event {
+        Event {
+            app_name,
+            topic,
+            data,
+            txn_hash,
+            block_height,
+            index,
+        }
+    }
+
+    pub fn get_app_name(&self) -> &String {
+        &self.app_name
+    }
+
+    pub fn get_topic(&self) -> &String {
+        &self.topic
+    }
+
+    pub fn get_data(&self) -> &Vec<u8> {
+        &self.data
+    }
+
+    pub fn get_txn_hash(&self) -> Hash {
+        self.txn_hash
+    }
+
+    pub fn get_block_height(&self) -> u64 {
+        self.block_height
+    }
+
+    /// position of the event among events of its transaction
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+}
+
+/// EventFilter selects events of an app by topic & block range, both bounds are inclusive
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct EventFilter {
+    pub topic: Option<String>,
+    pub from_height: u64,
+    pub to_height: Option<u64>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        if event.block_height < self.from_height {
+            return false;
+        }
+        if let Some(to_height) = self.to_height {
+            if event.block_height > to_height {
+                return false;
+            }
+        }
+        match &self.topic {
+            Some(topic) => topic == &event.topic,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_sdk_event {
+    use super::*;
+
+    #[test]
+    fn test_event_filter() {
+        let txn_hash: Hash = exonum_crypto::hash(&[1, 2, 3]);
+        let event: Event = Event::new(
+            String::from("app_name"),
+            String::from("transfer"),
+            vec![1],
+            txn_hash,
+            4,
+            0,
+        );
+        assert_eq!(event.get_txn_hash(), txn_hash);
+        assert!(EventFilter::default().matches(&event));
+        let mut filter = EventFilter {
+            topic: Some(String::from("transfer")),
+            from_height: 4,
+            to_height: Some(4),
+        };
+        assert!(filter.matches(&event));
+        filter.topic = Some(String::from("mint"));
+        assert!(!filter.matches(&event));
+        filter.topic = None;
+        filter.from_height = 5;
+        filter.to_height = None;
+        assert!(!filter.matches(&event));
+        filter.from_height = 0;
+        filter.to_height = Some(3);
+        assert!(!filter.matches(&event));
+    }
+}
@@ -1,6 +1,15 @@
 #[macro_use]
 extern crate exonum_derive;
 
+pub mod abi;
+pub mod dispatch;
+pub mod event;
+pub mod metering;
+pub mod namespace;
+pub mod query;
+pub mod receipt;
 pub mod signed_transaction;
 pub mod state;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod traits;
@@ -0,0 +1,451 @@
+use super::receipt::Receipt;
+use super::signed_transaction::SignedTransaction;
+use super::state::State;
+use super::traits::StateContext;
+use exonum_crypto::Hash;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
+
+/// state key of the chain limits, it holds no namespace separator so no app can write it
+pub const CHAIN_LIMITS_KEY: &str = "chain_limits";
+
+/// ExecutionLimits bounds the state work done by a txn or by all txns of a block
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionLimits {
+    pub max_reads: u64,
+    pub max_writes: u64,
+    pub max_bytes: u64,
+}
+
+impl ExecutionLimits {
+    pub fn new(max_reads: u64, max_writes: u64, max_bytes: u64) -> ExecutionLimits {
+        ExecutionLimits {
+            max_reads,
+            max_writes,
+            max_bytes,
+        }
+    }
+
+    /// tighter of both limits
+    pub fn min(&self, other: &ExecutionLimits) -> ExecutionLimits {
+        ExecutionLimits {
+            max_reads: self.max_reads.min(other.max_reads),
+            max_writes: self.max_writes.min(other.max_writes),
+            max_bytes: self.max_bytes.min(other.max_bytes),
+        }
+    }
+
+    /// true if nothing can be executed within these limits
+    pub fn is_exhausted(&self) -> bool {
+        self.max_reads == 0 || self.max_writes == 0 || self.max_bytes == 0
+    }
+}
+
+/// ChainLimits are the execution limits of a txn & of a block. They are kept in state from
+/// genesis on, so every node meters txns alike whatever its local config.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChainLimits {
+    pub txn_limits: ExecutionLimits,
+    pub block_limits: ExecutionLimits,
+}
+
+impl ChainLimits {
+    /// limits kept in given state, None if there are none
+    pub fn load<S: StateContext + ?Sized>(state_context: &S) -> Option<ChainLimits> {
+        let state: State = state_context.get(&String::from(CHAIN_LIMITS_KEY))?;
+        deserialize(state.get_data()).ok()
+    }
+
+    /// this function will keep limits in given state, the node calls it at genesis
+    pub fn store(&self, state_context: &mut dyn StateContext) {
+        if let Ok(data) = serialize(self) {
+            let mut state: State = State::new();
+            state.set_data(&data);
+            state_context.put(&String::from(CHAIN_LIMITS_KEY), state);
+        }
+    }
+}
+
+/// ExecutionMeter counts state reads, writes & bytes moved through StateContext
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ExecutionMeter {
+    pub reads: u64,
+    pub writes: u64,
+    pub bytes: u64,
+}
+
+impl ExecutionMeter {
+    pub fn add(&mut self, other: &ExecutionMeter) {
+        self.reads = self.reads.saturating_add(other.reads);
+        self.writes = self.writes.saturating_add(other.writes);
+        self.bytes = self.bytes.saturating_add(other.bytes);
+    }
+
+    /// what is left of given limits after this usage
+    pub fn remaining(&self, limits: &ExecutionLimits) -> ExecutionLimits {
+        ExecutionLimits {
+            max_reads: limits.max_reads.saturating_sub(self.reads),
+            max_writes: limits.max_writes.saturating_sub(self.writes),
+            max_bytes: limits.max_bytes.saturating_sub(self.bytes),
+        }
+    }
+
+    /// reason of the first limit this usage crosses, if any
+    pub fn exceeded(&self, limits: &ExecutionLimits) -> Option<String> {
+        if self.reads > limits.max_reads {
+            Some(format!("state reads exceed limit {}", limits.max_reads))
+        } else if self.writes > limits.max_writes {
+            Some(format!("state writes exceed limit {}", limits.max_writes))
+        } else if self.bytes > limits.max_bytes {
+            Some(format!("state bytes exceed limit {}", limits.max_bytes))
+        } else {
+            None
+        }
+    }
+}
+
+/// MeteredStateContext is handed to an app for one txn execution. It charges every state
+/// operation to its meter and buffers state, storage & event writes, they reach the wrapped
+/// context only through commit, so it acts as a checkpoint of the wrapped context which
+/// rollback returns to. Once a limit is crossed all further operations are refused,
+/// reads return nothing and writes are dropped.
+pub struct MeteredStateContext<'a> {
+    state_context: &'a mut dyn StateContext,
+    limits: ExecutionLimits,
+    meter: Cell<ExecutionMeter>,
+    exceeded: RefCell<Option<String>>,
+    states: BTreeMap<String, Option<State>>,
+    storage: BTreeMap<(String, String), Option<Vec<u8>>>,
+    events: Vec<(String, Vec<u8>)>,
+}
+
+impl<'a> MeteredStateContext<'a> {
+    pub fn new(state_context: &'a mut dyn StateContext, limits: ExecutionLimits) -> Self {
+        MeteredStateContext {
+            state_context,
+            limits,
+            meter: Cell::new(ExecutionMeter::default()),
+            exceeded: RefCell::new(None),
+            states: BTreeMap::new(),
+            storage: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// usage charged so far
+    pub fn meter(&self) -> ExecutionMeter {
+        self.meter.get()
+    }
+
+    /// reason of the crossed limit, None while execution is within limits
+    pub fn exceeded(&self) -> Option<String> {
+        self.exceeded.borrow().clone()
+    }
+
+    /// this function will apply buffered writes to the wrapped context, writes of an
+    /// execution which crossed its limits are dropped. It returns the charged usage.
+    pub fn commit(self) -> ExecutionMeter {
+        if self.exceeded.borrow().is_some() {
+            return self.meter.get();
+        }
+        for (key, state) in self.states {
+            match state {
+                Some(state) => self.state_context.put(&key, state),
+                None => self.state_context.delete(&key),
+            }
+        }
+        for ((account, key), value) in self.storage {
+            match value {
+                Some(value) => self.state_context.put_storage(&account, &key, value),
+                None => self.state_context.delete_storage(&account, &key),
+            }
+        }
+        for (topic, data) in self.events {
+            self.state_context.emit_event(&topic, data);
+        }
+        self.meter.get()
+    }
+
+    /// this function will discard buffered writes, the wrapped context is left as it was
+    /// before execution. It returns the charged usage.
+    pub fn rollback(self) -> ExecutionMeter {
+        self.meter.get()
+    }
+
+    /// charge bytes handed to the execution outside state operations e.g. txn payload,
+    /// false if it crosses the limits
+    pub fn charge_bytes(&self, bytes: usize) -> bool {
+        self.charge(0, 0, bytes)
+    }
+
+    /// charge given usage, false if it crosses the limits
+    fn charge(&self, reads: u64, writes: u64, bytes: usize) -> bool {
+        if self.exceeded.borrow().is_some() {
+            return false;
+        }
+        let mut meter: ExecutionMeter = self.meter.get();
+        meter.add(&ExecutionMeter {
+            reads,
+            writes,
+            bytes: bytes as u64,
+        });
+        self.meter.set(meter);
+        match meter.exceeded(&self.limits) {
+            Some(reason) => {
+                *self.exceeded.borrow_mut() = Some(reason);
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// most entries a scan needs from the wrapped context, one more than the reads left
+    /// along with buffered deletes in bounds. A scan reaching it crosses the read limit.
+    fn scan_cap(&self, in_bounds: &impl Fn(&String) -> bool) -> usize {
+        let reads_left: u64 = self.meter.get().remaining(&self.limits).max_reads;
+        let deleted: usize = self
+            .states
+            .iter()
+            .filter(|(key, state)| state.is_none() && in_bounds(key))
+            .count();
+        (reads_left as usize)
+            .saturating_add(deleted)
+            .saturating_add(1)
+    }
+
+    /// entries of the wrapped context overlaid with buffered writes, for keys in given
+    /// bounds. Every entry is charged as one read, nothing is returned past the limits.
+    fn overlay(
+        &self,
+        entries: Vec<(String, State)>,
+        in_bounds: impl Fn(&String) -> bool,
+    ) -> Vec<(String, State)> {
+        let mut merged: BTreeMap<String, State> = entries.into_iter().collect();
+        for (key, state) in self.states.iter().filter(|(key, _)| in_bounds(key)) {
+            match state {
+                Some(state) => merged.insert(key.clone(), state.clone()),
+                None => merged.remove(key),
+            };
+        }
+        let mut scanned: Vec<(String, State)> = Vec::with_capacity(merged.len());
+        for (key, state) in merged {
+            if !self.charge(1, 0, key.len() + state.get_data().len()) {
+                return Vec::new();
+            }
+            scanned.push((key, state));
+        }
+        scanned
+    }
+}
+
+impl<'a> StateContext for MeteredStateContext<'a> {
+    fn put(&mut self, key: &String, state: State) {
+        if self.charge(0, 1, key.len() + state.get_data().len()) {
+            self.states.insert(key.clone(), Some(state));
+        }
+    }
+    fn get(&self, key: &String) -> Option<State> {
+        if !self.charge(1, 0, key.len()) {
+            return None;
+        }
+        let state: Option<State> = match self.states.get(key) {
+            Some(state) => state.clone(),
+            None => self.state_context.get(key),
+        };
+        match state {
+            Some(state) if self.charge(0, 0, state.get_data().len()) => Some(state),
+            _ => None,
+        }
+    }
+    fn contains(&self, key: &String) -> bool {
+        if !self.charge(1, 0, key.len()) {
+            return false;
+        }
+        match self.states.get(key) {
+            Some(state) => state.is_some(),
+            None => self.state_context.contains(key),
+        }
+    }
+    fn delete(&mut self, key: &String) {
+        if self.charge(0, 1, key.len()) {
+            self.states.insert(key.clone(), None);
+        }
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        let in_bounds = |key: &String| key.starts_with(prefix.as_str());
+        let entries = self
+            .state_context
+            .iter_prefix_bounded(prefix, self.scan_cap(&in_bounds));
+        self.overlay(entries, in_bounds)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        let in_bounds = |key: &String| key >= from && key < to;
+        let entries = self
+            .state_context
+            .range_bounded(from, to, self.scan_cap(&in_bounds));
+        self.overlay(entries, in_bounds)
+    }
+    fn put_storage(&mut self, account: &String, key: &String, value: Vec<u8>) {
+        if self.charge(0, 1, account.len() + key.len() + value.len()) {
+            self.storage
+                .insert((account.clone(), key.clone()), Some(value));
+        }
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        if !self.charge(1, 0, account.len() + key.len()) {
+            return None;
+        }
+        let value: Option<Vec<u8>> = match self.storage.get(&(account.clone(), key.clone())) {
+            Some(value) => value.clone(),
+            None => self.state_context.get_storage(account, key),
+        };
+        match value {
+            Some(value) if self.charge(0, 0, value.len()) => Some(value),
+            _ => None,
+        }
+    }
+    fn delete_storage(&mut self, account: &String, key: &String) {
+        if self.charge(0, 1, account.len() + key.len()) {
+            self.storage.insert((account.clone(), key.clone()), None);
+        }
+    }
+    fn get_txn(&self, key: &Hash) -> Option<SignedTransaction> {
+        if !self.charge(1, 0, 0) {
+            return None;
+        }
+        match self.state_context.get_txn(key) {
+            Some(txn) if self.charge(0, 0, txn.txn.len()) => Some(txn),
+            _ => None,
+        }
+    }
+    fn contains_txn(&self, key: &Hash) -> bool {
+        self.charge(1, 0, 0) && self.state_context.contains_txn(key)
+    }
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt> {
+        if !self.charge(1, 0, 0) {
+            return None;
+        }
+        self.state_context.get_receipt(key)
+    }
+    fn emit_event(&mut self, topic: &String, data: Vec<u8>) {
+        if self.charge(0, 1, topic.len() + data.len()) {
+            self.events.push((topic.clone(), data));
+        }
+    }
+    fn get_block_producer(&self) -> Option<String> {
+        self.state_context.get_block_producer()
+    }
+}
+
+#[cfg(test)]
+mod test_metering {
+    use super::*;
+    use crate::test_utils::MemoryStateContext;
+
+    fn state(data: &[u8]) -> State {
+        let mut state = State::new();
+        state.set_data(&data.to_vec());
+        state
+    }
+
+    #[test]
+    fn test_metered_state_context() {
+        let mut state_context = MemoryStateContext::new();
+        state_context.put(&String::from("doc_1"), state(&[1]));
+        state_context.put(&String::from("doc_2"), state(&[2]));
+        let limits = ExecutionLimits::new(10, 10, 100);
+        let meter: ExecutionMeter = {
+            let mut metered = MeteredStateContext::new(&mut state_context, limits);
+            metered.put(&String::from("doc_3"), state(&[3, 3]));
+            metered.delete(&String::from("doc_1"));
+            metered.emit_event(&String::from("created"), vec![]);
+            assert!(!metered.contains(&String::from("doc_1")));
+            assert_eq!(metered.get(&String::from("doc_3")), Some(state(&[3, 3])));
+            let docs: Vec<String> = metered
+                .iter_prefix(&String::from("doc_"))
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect();
+            assert_eq!(docs, vec!["doc_2", "doc_3"]);
+            // nothing reaches wrapped context before commit
+            assert!(metered.state_context.contains(&String::from("doc_1")));
+            assert_eq!(metered.exceeded(), None);
+            metered.commit()
+        };
+        assert_eq!(
+            meter,
+            ExecutionMeter {
+                reads: 4,
+                writes: 3,
+                bytes: 44,
+            }
+        );
+        assert!(!state_context.contains(&String::from("doc_1")));
+        assert_eq!(state_context.states.len(), 2);
+        assert_eq!(state_context.event_topics(), vec!["created"]);
+        assert_eq!(meter.remaining(&limits), ExecutionLimits::new(6, 7, 56));
+    }
+
+    #[test]
+    fn test_metered_state_context_limits() {
+        let mut state_context = MemoryStateContext::new();
+        let limits = ExecutionLimits::new(10, 2, 100);
+        let meter: ExecutionMeter = {
+            let mut metered = MeteredStateContext::new(&mut state_context, limits);
+            metered.put(&String::from("doc_1"), state(&[1]));
+            metered.put(&String::from("doc_2"), state(&[2; 200]));
+            assert_eq!(
+                metered.exceeded(),
+                Some(String::from("state bytes exceed limit 100"))
+            );
+            // refused once a limit is crossed
+            assert_eq!(metered.get(&String::from("doc_1")), None);
+            metered.delete(&String::from("doc_1"));
+            metered.commit()
+        };
+        assert_eq!(meter.writes, 2);
+        assert!(state_context.states.is_empty());
+        assert!(meter.remaining(&limits).is_exhausted());
+        assert_eq!(
+            limits.min(&ExecutionLimits::new(20, 1, 50)),
+            ExecutionLimits::new(10, 1, 50)
+        );
+    }
+
+    #[test]
+    fn test_metered_state_context_scan_limits() {
+        let mut state_context = MemoryStateContext::new();
+        for index in 0..5 {
+            state_context.put(&format!("doc_{}", index), state(&[index]));
+        }
+        let limits = ExecutionLimits::new(3, 10, 100);
+        {
+            let metered = MeteredStateContext::new(&mut state_context, limits);
+            assert!(metered.iter_prefix(&String::from("doc_")).is_empty());
+            assert_eq!(
+                metered.exceeded(),
+                Some(String::from("state reads exceed limit 3"))
+            );
+            // entries are charged one by one, the scan stops at the first crossing one
+            assert_eq!(metered.meter().reads, 4);
+        }
+        {
+            let mut metered = MeteredStateContext::new(&mut state_context, limits);
+            metered.delete(&String::from("doc_0"));
+            metered.delete(&String::from("doc_1"));
+            let ranged = metered.range(&String::from("doc_"), &String::from("doc_9"));
+            assert_eq!(ranged.len(), 3);
+            assert_eq!(metered.exceeded(), None);
+            assert!(!metered.charge_bytes(100));
+        }
+
+        let chain_limits = ChainLimits {
+            txn_limits: limits,
+            block_limits: ExecutionLimits::new(30, 100, 1000),
+        };
+        assert_eq!(ChainLimits::load(&state_context), None);
+        chain_limits.store(&mut state_context);
+        assert_eq!(ChainLimits::load(&state_context), Some(chain_limits));
+    }
+}
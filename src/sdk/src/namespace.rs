@@ -0,0 +1,239 @@
+use super::receipt::Receipt;
+use super::signed_transaction::SignedTransaction;
+use super::state::State;
+use super::traits::{ReadStateContext, StateContext};
+use exonum_crypto::Hash;
+
+/// separator between app name and app key in state_trie keys
+pub const NAMESPACE_SEPARATOR: &str = "/";
+
+/// state_trie key under which key of given app is stored
+pub fn namespaced_key(app_name: &str, key: &str) -> String {
+    format!("{}{}{}", app_name, NAMESPACE_SEPARATOR, key)
+}
+
+/// app names may hold ascii letters, digits, '_' & '-' only. A separator in the name
+/// would let app "a" reach keys of app "a/b" through its own namespace.
+pub fn check_app_name(app_name: &str) -> Result<(), String> {
+    if app_name.is_empty() {
+        return Err(String::from("app name must be non empty"));
+    }
+    match app_name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+    {
+        Some(c) => Err(format!(
+            "app name {} contains invalid character {:?}",
+            app_name, c
+        )),
+        None => Ok(()),
+    }
+}
+
+fn strip_namespace(prefix: &String, entries: Vec<(String, State)>) -> Vec<(String, State)> {
+    entries
+        .into_iter()
+        .filter_map(|(key, state)| {
+            if key.starts_with(prefix.as_str()) {
+                Some((key[prefix.len()..].to_string(), state))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// NamespacedStateContext is handed to an app while its txns execute, every state key
+/// is prefixed by app name so apps can't read or overwrite each other's keys.
+/// Txns, receipts & block producer are passed through as it is.
+pub struct NamespacedStateContext<'a> {
+    state_context: &'a mut dyn StateContext,
+    prefix: String,
+}
+
+impl<'a> NamespacedStateContext<'a> {
+    pub fn new(state_context: &'a mut dyn StateContext, app_name: &str) -> Self {
+        NamespacedStateContext {
+            state_context,
+            prefix: namespaced_key(app_name, ""),
+        }
+    }
+
+    fn key(&self, key: &String) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+impl<'a> StateContext for NamespacedStateContext<'a> {
+    fn put(&mut self, key: &String, state: State) {
+        let key: String = self.key(key);
+        self.state_context.put(&key, state);
+    }
+    fn get(&self, key: &String) -> Option<State> {
+        self.state_context.get(&self.key(key))
+    }
+    fn contains(&self, key: &String) -> bool {
+        self.state_context.contains(&self.key(key))
+    }
+    fn delete(&mut self, key: &String) {
+        let key: String = self.key(key);
+        self.state_context.delete(&key);
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        let entries = self.state_context.iter_prefix(&self.key(prefix));
+        strip_namespace(&self.prefix, entries)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        let entries = self.state_context.range(&self.key(from), &self.key(to));
+        strip_namespace(&self.prefix, entries)
+    }
+    fn iter_prefix_bounded(&self, prefix: &String, max_entries: usize) -> Vec<(String, State)> {
+        let entries = self
+            .state_context
+            .iter_prefix_bounded(&self.key(prefix), max_entries);
+        strip_namespace(&self.prefix, entries)
+    }
+    fn range_bounded(
+        &self,
+        from: &String,
+        to: &String,
+        max_entries: usize,
+    ) -> Vec<(String, State)> {
+        let entries = self
+            .state_context
+            .range_bounded(&self.key(from), &self.key(to), max_entries);
+        strip_namespace(&self.prefix, entries)
+    }
+    fn put_storage(&mut self, account: &String, key: &String, value: Vec<u8>) {
+        let account: String = self.key(account);
+        self.state_context.put_storage(&account, key, value);
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.state_context.get_storage(&self.key(account), key)
+    }
+    fn delete_storage(&mut self, account: &String, key: &String) {
+        let account: String = self.key(account);
+        self.state_context.delete_storage(&account, key);
+    }
+    fn get_txn(&self, key: &Hash) -> Option<SignedTransaction> {
+        self.state_context.get_txn(key)
+    }
+    fn contains_txn(&self, key: &Hash) -> bool {
+        self.state_context.contains_txn(key)
+    }
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt> {
+        self.state_context.get_receipt(key)
+    }
+    fn emit_event(&mut self, topic: &String, data: Vec<u8>) {
+        self.state_context.emit_event(topic, data);
+    }
+    fn get_block_producer(&self) -> Option<String> {
+        self.state_context.get_block_producer()
+    }
+}
+
+/// NamespacedReadContext is the read-only counterpart used for app queries
+pub struct NamespacedReadContext<'a> {
+    state_context: &'a dyn ReadStateContext,
+    prefix: String,
+}
+
+impl<'a> NamespacedReadContext<'a> {
+    pub fn new(state_context: &'a dyn ReadStateContext, app_name: &str) -> Self {
+        NamespacedReadContext {
+            state_context,
+            prefix: namespaced_key(app_name, ""),
+        }
+    }
+
+    fn key(&self, key: &String) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+impl<'a> ReadStateContext for NamespacedReadContext<'a> {
+    fn get(&self, key: &String) -> Option<State> {
+        self.state_context.get(&self.key(key))
+    }
+    fn contains(&self, key: &String) -> bool {
+        self.state_context.contains(&self.key(key))
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        let entries = self.state_context.iter_prefix(&self.key(prefix));
+        strip_namespace(&self.prefix, entries)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        let entries = self.state_context.range(&self.key(from), &self.key(to));
+        strip_namespace(&self.prefix, entries)
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.state_context.get_storage(&self.key(account), key)
+    }
+    fn get_txn(&self, key: &Hash) -> Option<SignedTransaction> {
+        self.state_context.get_txn(key)
+    }
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt> {
+        self.state_context.get_receipt(key)
+    }
+}
+
+#[cfg(test)]
+mod test_namespace {
+    use super::*;
+    use crate::test_utils::MemoryStateContext;
+
+    #[test]
+    fn test_check_app_name() {
+        assert_eq!(check_app_name("Document_Review"), Ok(()));
+        assert_eq!(check_app_name("validator-set"), Ok(()));
+        assert_eq!(
+            check_app_name(""),
+            Err(String::from("app name must be non empty"))
+        );
+        assert_eq!(
+            check_app_name("a/b"),
+            Err(String::from("app name a/b contains invalid character '/'"))
+        );
+        assert!(check_app_name("a b").is_err());
+        assert!(check_app_name("a:b").is_err());
+    }
+
+    #[test]
+    fn test_namespaced_state_context() {
+        let mut state_context = MemoryStateContext::new();
+        let key: String = String::from("doc_1");
+        {
+            let mut first_app = NamespacedStateContext::new(&mut state_context, "first_app");
+            first_app.put(&key, State::new());
+            first_app.put(&String::from("doc_2"), State::new());
+            first_app.put(&String::from("pkg_1"), State::new());
+        }
+        {
+            let mut second_app = NamespacedStateContext::new(&mut state_context, "second_app");
+            assert!(!second_app.contains(&key));
+            second_app.put(&key, State::new());
+            second_app.delete(&key);
+            assert!(!second_app.contains(&key));
+            second_app.put_storage(&key, &key, vec![1]);
+            assert_eq!(second_app.get_storage(&key, &key), Some(vec![1]));
+        }
+        assert!(state_context
+            .storage
+            .contains_key(&(namespaced_key("second_app", &key), key.clone())));
+        assert!(state_context.contains(&namespaced_key("first_app", &key)));
+        assert_eq!(state_context.states.len(), 3);
+
+        let mut first_app = NamespacedStateContext::new(&mut state_context, "first_app");
+        let docs: Vec<String> = first_app
+            .iter_prefix(&String::from("doc_"))
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(docs, vec!["doc_1", "doc_2"]);
+        let ranged: Vec<(String, State)> =
+            first_app.range(&String::from("doc_2"), &String::from("pkg_2"));
+        assert_eq!(ranged.len(), 2);
+        first_app.delete(&key);
+        assert_eq!(first_app.iter_prefix(&String::new()).len(), 2);
+    }
+}
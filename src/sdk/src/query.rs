@@ -0,0 +1,10 @@
+use std::collections::HashMap;
+use utils::serializer::{Deserialize, Serialize};
+
+/// AppQuery is the request body of `/client/query/{app}`, path selects the query
+/// inside the app and params carry its named arguments
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppQuery {
+    pub path: String,
+    pub params: HashMap<String, String>,
+}
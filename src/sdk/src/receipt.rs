@@ -0,0 +1,133 @@
+extern crate utils;
+use super::event::Event;
+use exonum_crypto::Hash;
+use std::convert::AsRef;
+
+use utils::serializer::{Deserialize, Serialize};
+
+/// Receipt keeps the execution outcome of a transaction included in a block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default, BinaryValue, ObjectHash)]
+#[binary_value(codec = "bincode")]
+pub struct Receipt {
+    txn_hash: Hash,
+    status: bool,
+    error: String,
+    block_height: u64,
+    index: u64,
+    // events emitted by a successful execution
+    events: Vec<Event>,
+}
+
+impl Receipt {
+    pub fn new(
+        txn_hash: Hash,
+        result: &Result<(), String>,
+        block_height: u64,
+        index: u64,
+    ) -> Receipt {
+        let (status, error) = match result {
+            Ok(()) => (true, String::new()),
+            Err(reason) => (false, reason.clone()),
+        };
+        Receipt {
+            txn_hash,
+            status,
+            error,
+            block_height,
+            index,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn get_txn_hash(&self) -> Hash {
+        self.txn_hash
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status
+    }
+
+    pub fn get_error(&self) -> &String {
+        &self.error
+    }
+
+    pub fn get_block_height(&self) -> u64 {
+        self.block_height
+    }
+
+    pub fn get_index(&self) -> u64 {
+        self.index
+    }
+
+    pub fn get_events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    pub fn set_events(&mut self, events: Vec<Event>) {
+        self.events = events;
+    }
+}
+
+/// TransactionStatus reports where a transaction is in its lifecycle.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    // waiting in the transaction pool
+    Pending,
+    // included in a block and executed successfully
+    Included(Receipt),
+    // included in a block but execution failed
+    Failed(Receipt),
+    // removed from the pool after transaction_execution_delay_limit
+    Expired,
+    // removed from the pool for the given reason
+    Dropped(String),
+    // never seen by this node or evicted record already rotated out
+    Unknown,
+}
+
+impl TransactionStatus {
+    pub fn from_receipt(receipt: Receipt) -> TransactionStatus {
+        if receipt.is_success() {
+            TransactionStatus::Included(receipt)
+        } else {
+            TransactionStatus::Failed(receipt)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_sdk_receipt {
+    use super::*;
+
+    #[test]
+    fn test_receipt_operations() {
+        let txn_hash: Hash = exonum_crypto::hash(&[1, 2, 3]);
+        let receipt: Receipt = Receipt::new(txn_hash, &Ok(()), 4, 2);
+        assert_eq!(receipt.get_txn_hash(), txn_hash);
+        assert_eq!(receipt.is_success(), true);
+        assert_eq!(receipt.get_error(), &String::new());
+        assert_eq!(receipt.get_block_height(), 4);
+        assert_eq!(receipt.get_index(), 2);
+
+        let result: Result<(), String> = Err(String::from("insufficient balance"));
+        let receipt: Receipt = Receipt::new(txn_hash, &result, 4, 3);
+        assert_eq!(receipt.is_success(), false);
+        assert_eq!(receipt.get_error(), &String::from("insufficient balance"));
+    }
+
+    #[test]
+    fn test_transaction_status_from_receipt() {
+        let txn_hash: Hash = exonum_crypto::hash(&[1, 2, 3]);
+        let receipt: Receipt = Receipt::new(txn_hash, &Ok(()), 4, 2);
+        assert_eq!(
+            TransactionStatus::from_receipt(receipt.clone()),
+            TransactionStatus::Included(receipt)
+        );
+        let result: Result<(), String> = Err(String::from("insufficient balance"));
+        let receipt: Receipt = Receipt::new(txn_hash, &result, 4, 2);
+        assert_eq!(
+            TransactionStatus::from_receipt(receipt.clone()),
+            TransactionStatus::Failed(receipt)
+        );
+    }
+}
@@ -1,7 +1,12 @@
+use exonum_crypto::Hash;
+use exonum_merkledb::ObjectHash;
 use std::convert::AsRef;
 use utils::serializer::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BinaryValue, ObjectHash)]
+/// SignedTransaction is the envelope of every app txn. header is a HashMap, so its
+/// bincode encoding depends on iteration order; hashing & signing must go through the
+/// canonical encoding below where headers are sorted by key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, BinaryValue)]
 #[binary_value(codec = "bincode")]
 pub struct SignedTransaction {
     pub txn: ::std::vec::Vec<u8>,
@@ -9,3 +14,73 @@ pub struct SignedTransaction {
     pub header: ::std::collections::HashMap<std::string::String, std::string::String>,
     pub signature: std::vec::Vec<u8>,
 }
+
+impl SignedTransaction {
+    /// headers sorted by key
+    pub fn sorted_header(&self) -> Vec<(&String, &String)> {
+        let mut header: Vec<(&String, &String)> = self.header.iter().collect();
+        header.sort();
+        header
+    }
+
+    /// canonical bytes covered by the signature, i.e. txn, app_name & sorted headers
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&(&self.txn, &self.app_name, self.sorted_header()))
+            .expect("signed transaction encoding failed")
+    }
+
+    /// canonical bytes of the whole txn including signature, used for txn hash
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&(
+            &self.txn,
+            &self.app_name,
+            self.sorted_header(),
+            &self.signature,
+        ))
+        .expect("signed transaction encoding failed")
+    }
+}
+
+impl ObjectHash for SignedTransaction {
+    fn object_hash(&self) -> Hash {
+        exonum_crypto::hash(self.canonical_bytes().as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test_signed_transaction {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_canonical_encoding() {
+        let keys: Vec<String> = (0..32).map(|index| format!("header_{}", index)).collect();
+        let mut forward: HashMap<String, String> = HashMap::new();
+        for key in keys.iter() {
+            forward.insert(key.clone(), key.to_uppercase());
+        }
+        let mut reverse: HashMap<String, String> = HashMap::with_capacity(64);
+        for key in keys.iter().rev() {
+            reverse.insert(key.clone(), key.to_uppercase());
+        }
+        let first = SignedTransaction {
+            txn: vec![1, 2, 3],
+            app_name: String::from("app_name"),
+            header: forward,
+            signature: vec![4, 5],
+        };
+        let mut second: SignedTransaction = first.clone();
+        second.header = reverse;
+        assert_eq!(first.canonical_bytes(), second.canonical_bytes());
+        assert_eq!(first.object_hash(), second.object_hash());
+        assert_eq!(first.signing_bytes(), second.signing_bytes());
+
+        second.signature = vec![6];
+        assert_eq!(first.signing_bytes(), second.signing_bytes());
+        assert_ne!(first.object_hash(), second.object_hash());
+        second
+            .header
+            .insert(String::from("timestamp"), String::from("1"));
+        assert_ne!(first.signing_bytes(), second.signing_bytes());
+    }
+}
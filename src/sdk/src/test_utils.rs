@@ -0,0 +1,223 @@
+//! in-memory state context & app shared by unit tests of sdk, apps and node crates.
+//! Other crates enable it through the test-utils feature in their dev-dependencies.
+
+use super::receipt::Receipt;
+use super::signed_transaction::SignedTransaction;
+use super::state::State;
+use super::traits::{AppHandler, BlockContext, ReadStateContext, StateContext};
+use exonum_crypto::Hash;
+use std::collections::{BTreeMap, HashMap};
+use utils::serializer::serialize;
+
+/// MemoryStateContext keeps states, account storage & emitted events in memory.
+/// Txns & receipts are not kept, they are written by the node only.
+#[derive(Debug, Default)]
+pub struct MemoryStateContext {
+    pub states: BTreeMap<String, State>,
+    pub storage: BTreeMap<(String, String), Vec<u8>>,
+    pub events: Vec<(String, Vec<u8>)>,
+    pub block_producer: Option<String>,
+}
+
+impl MemoryStateContext {
+    pub fn new() -> MemoryStateContext {
+        MemoryStateContext::default()
+    }
+
+    /// context of a block produced by given account, apps charging fees credit it
+    pub fn with_block_producer(block_producer: &str) -> MemoryStateContext {
+        MemoryStateContext {
+            block_producer: Some(String::from(block_producer)),
+            ..MemoryStateContext::default()
+        }
+    }
+
+    /// read-only view of this context, as app queries get it
+    pub fn read_only(&self) -> ReadOnlyContext<'_> {
+        ReadOnlyContext(self)
+    }
+
+    /// topics of emitted events in emit order
+    pub fn event_topics(&self) -> Vec<String> {
+        self.events.iter().map(|(topic, _)| topic.clone()).collect()
+    }
+
+    fn prefixed(&self, prefix: &str) -> Vec<(String, State)> {
+        self.states
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, state)| (key.clone(), state.clone()))
+            .collect()
+    }
+
+    fn ranged(&self, from: &str, to: &str) -> Vec<(String, State)> {
+        if from >= to {
+            return Vec::new();
+        }
+        self.states
+            .range(from.to_string()..to.to_string())
+            .map(|(key, state)| (key.clone(), state.clone()))
+            .collect()
+    }
+}
+
+impl StateContext for MemoryStateContext {
+    fn put(&mut self, key: &String, state: State) {
+        self.states.insert(key.clone(), state);
+    }
+    fn get(&self, key: &String) -> Option<State> {
+        self.states.get(key).cloned()
+    }
+    fn contains(&self, key: &String) -> bool {
+        self.states.contains_key(key)
+    }
+    fn delete(&mut self, key: &String) {
+        self.states.remove(key);
+        self.storage.retain(|(account, _), _| account != key);
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        self.prefixed(prefix)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        self.ranged(from, to)
+    }
+    fn put_storage(&mut self, account: &String, key: &String, value: Vec<u8>) {
+        self.storage.insert((account.clone(), key.clone()), value);
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.storage.get(&(account.clone(), key.clone())).cloned()
+    }
+    fn delete_storage(&mut self, account: &String, key: &String) {
+        self.storage.remove(&(account.clone(), key.clone()));
+    }
+    fn get_txn(&self, _key: &Hash) -> Option<SignedTransaction> {
+        None
+    }
+    fn contains_txn(&self, _key: &Hash) -> bool {
+        false
+    }
+    fn get_receipt(&self, _key: &Hash) -> Option<Receipt> {
+        None
+    }
+    fn emit_event(&mut self, topic: &String, data: Vec<u8>) {
+        self.events.push((topic.clone(), data));
+    }
+    fn get_block_producer(&self) -> Option<String> {
+        self.block_producer.clone()
+    }
+}
+
+/// read-only view of a MemoryStateContext, it is handed to app queries
+pub struct ReadOnlyContext<'a>(&'a MemoryStateContext);
+
+impl<'a> ReadStateContext for ReadOnlyContext<'a> {
+    fn get(&self, key: &String) -> Option<State> {
+        self.0.states.get(key).cloned()
+    }
+    fn contains(&self, key: &String) -> bool {
+        self.0.states.contains_key(key)
+    }
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)> {
+        self.0.prefixed(prefix)
+    }
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)> {
+        self.0.ranged(from, to)
+    }
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>> {
+        self.0.storage.get(&(account.clone(), key.clone())).cloned()
+    }
+    fn get_txn(&self, _key: &Hash) -> Option<SignedTransaction> {
+        None
+    }
+    fn get_receipt(&self, _key: &Hash) -> Option<Receipt> {
+        None
+    }
+}
+
+/// MockApp accepts every signed txn and executes it without touching state. Its only
+/// query path is "contains", it reports whether key given in "address" param exists.
+pub struct MockApp {
+    name: String,
+    genesis_error: Option<String>,
+}
+
+impl MockApp {
+    pub fn new(name: &str) -> MockApp {
+        MockApp {
+            name: String::from(name),
+            genesis_error: None,
+        }
+    }
+
+    /// MockApp whose init_genesis hook fails with given reason
+    pub fn with_genesis_error(name: &str, reason: &str) -> MockApp {
+        MockApp {
+            name: String::from(name),
+            genesis_error: Some(String::from(reason)),
+        }
+    }
+}
+
+impl AppHandler for MockApp {
+    fn execute(
+        &self,
+        _txn: &SignedTransaction,
+        _state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String> {
+        if txn.signature.is_empty() {
+            return Err(String::from("signature missing"));
+        }
+        Ok(())
+    }
+
+    fn sender(&self, _txn: &SignedTransaction) -> Option<String> {
+        None
+    }
+
+    fn nonce(&self, _txn: &SignedTransaction) -> Option<u64> {
+        None
+    }
+
+    fn next_nonce(&self, _sender: &String, _state_context: &dyn StateContext) -> Option<u64> {
+        None
+    }
+
+    fn fee(&self, _txn: &SignedTransaction) -> u64 {
+        0
+    }
+
+    fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String> {
+        if path != "contains" {
+            return Err(format!("unknown query path {}", path));
+        }
+        match params.get("address") {
+            Some(address) => serialize(&state_context.contains(address))
+                .map_err(|_| String::from("response couldn't serialize")),
+            None => Err(String::from("address param missing")),
+        }
+    }
+
+    fn init_genesis(
+        &self,
+        _block: &BlockContext,
+        _state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        match &self.genesis_error {
+            Some(reason) => Err(reason.clone()),
+            None => Ok(()),
+        }
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
@@ -1,27 +1,148 @@
+use super::receipt::Receipt;
 use super::signed_transaction::SignedTransaction;
 use super::state::State;
 use exonum_crypto::Hash;
 use exonum_merkledb::access::Access;
+use std::collections::HashMap;
 
 pub trait StateContext {
+    /// write state of key, its storage_root is kept by the node & the given one is ignored
     fn put(&mut self, key: &String, state: State);
     fn get(&self, key: &String) -> Option<State>;
     fn contains(&self, key: &String) -> bool;
-    fn put_txn(&mut self, key: &Hash, txn: SignedTransaction);
+    /// remove state of key, nothing happens if key is absent
+    fn delete(&mut self, key: &String);
+    /// states whose key starts with prefix, in key order
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)>;
+    /// states with from <= key < to, in key order
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)>;
+    /// at most max_entries first states of iter_prefix, contexts backed by the db stop
+    /// their scan there
+    fn iter_prefix_bounded(&self, prefix: &String, max_entries: usize) -> Vec<(String, State)> {
+        let mut entries: Vec<(String, State)> = self.iter_prefix(prefix);
+        entries.truncate(max_entries);
+        entries
+    }
+    /// at most max_entries first states of range, contexts backed by the db stop their
+    /// scan there
+    fn range_bounded(
+        &self,
+        from: &String,
+        to: &String,
+        max_entries: usize,
+    ) -> Vec<(String, State)> {
+        let mut entries: Vec<(String, State)> = self.range(from, to);
+        entries.truncate(max_entries);
+        entries
+    }
+    /// write key of account's contract storage, root of the account storage is
+    /// updated in State::storage_root of the account
+    fn put_storage(&mut self, account: &String, key: &String, value: Vec<u8>);
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>>;
+    fn delete_storage(&mut self, account: &String, key: &String);
     fn get_txn(&self, key: &Hash) -> Option<SignedTransaction>;
     fn contains_txn(&self, key: &Hash) -> bool;
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt>;
+    /// emit event of the transaction under execution, it is stored with the transaction
+    /// receipt. Events of a failed execution are discarded.
+    fn emit_event(&mut self, topic: &String, data: Vec<u8>);
+    /// account of the node producing the block under execution, None outside block execution
+    fn get_block_producer(&self) -> Option<String>;
+}
+
+/// node side of StateContext, txns & receipts are written only by the node while it
+/// executes a block. Apps are handed a plain StateContext so they can't forge or
+/// overwrite txns & receipts of other apps.
+pub trait ChainStateContext: StateContext {
+    fn put_txn(&mut self, key: &Hash, txn: SignedTransaction);
+    fn put_receipt(&mut self, key: &Hash, receipt: Receipt);
+}
+
+/// read-only view of committed state, app queries can't modify anything
+pub trait ReadStateContext {
+    fn get(&self, key: &String) -> Option<State>;
+    fn contains(&self, key: &String) -> bool;
+    fn iter_prefix(&self, prefix: &String) -> Vec<(String, State)>;
+    fn range(&self, from: &String, to: &String) -> Vec<(String, State)>;
+    fn get_storage(&self, account: &String, key: &String) -> Option<Vec<u8>>;
+    fn get_txn(&self, key: &Hash) -> Option<SignedTransaction>;
+    fn get_receipt(&self, key: &Hash) -> Option<Receipt>;
+}
+
+/// details of the block under execution handed to app lifecycle hooks
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockContext {
+    pub height: u64,
+    // block timestamp in micro seconds
+    pub timestamp: u128,
+    pub proposer: String,
 }
 
 pub trait AppHandler {
-    fn execute(&self, txn: &SignedTransaction, state_context: &mut dyn StateContext) -> bool;
+    /// Err carries the failure reason which ends up in the transaction receipt
+    fn execute(
+        &self,
+        txn: &SignedTransaction,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String>;
+    /// admission check run before the transaction enters the pool or is gossiped,
+    /// Err carries the rejection reason returned to the submitter
+    fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String>;
+    /// account which signed the transaction, used for per-sender pool limits
+    fn sender(&self, txn: &SignedTransaction) -> Option<String>;
+    /// account nonce of the transaction, None if app doesn't order txns by nonce
+    fn nonce(&self, txn: &SignedTransaction) -> Option<u64>;
+    /// nonce the next executable transaction of sender must carry as per given state
+    fn next_nonce(&self, sender: &String, state_context: &dyn StateContext) -> Option<u64>;
+    /// fee offered by the transaction, higher fee is executed first and evicted last from the pool
+    fn fee(&self, txn: &SignedTransaction) -> u64;
+    /// read-only query on committed state e.g. balance of an account, Ok carries the
+    /// serialized response and Err the reason returned to the client
+    fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String>;
+    /// called once while the genesis block is computed e.g. for initial allocations
+    fn init_genesis(
+        &self,
+        _block: &BlockContext,
+        _state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+    /// called before the txns of every block are executed
+    fn begin_block(
+        &self,
+        _block: &BlockContext,
+        _state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+    /// called after the txns of every block are executed, before block header roots are computed
+    fn end_block(
+        &self,
+        _block: &BlockContext,
+        _state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        Ok(())
+    }
     fn name(&self) -> String;
 }
 
 pub trait PoolTrait<T: Access, StateObj, TransactionObj> {
-    fn execute_transactions(&self, state_context: &mut dyn StateContext) -> (Vec<Hash>, Vec<Hash>);
+    fn execute_transactions(
+        &self,
+        state_context: &mut dyn ChainStateContext,
+        block_height: u64,
+        block_producer: &String,
+    ) -> (Vec<Hash>, Vec<Hash>);
     fn update_transactions(
         &self,
-        state_context: &mut dyn StateContext,
+        state_context: &mut dyn ChainStateContext,
         hash_vec: &Vec<Hash>,
+        block_height: u64,
+        block_producer: &String,
     ) -> bool;
 }
@@ -1,12 +1,19 @@
-use actix_web::{web, HttpResponse};
-use db_service::db_layer::snapshot_db;
+use actix_web::{rt::time::delay_for, web, Error, HttpResponse};
+use db_service::db_layer::DbInstance;
 use db_service::db_snapshot_ref::SchemaSnap;
 use exonum_crypto::Hash;
 use futures::channel::mpsc::*;
+use futures::stream;
 use message_handler::message_sender::MessageSender;
-use message_handler::messages::MessageTypes;
+use message_handler::messages::{ConsensusRequest, MessageTypes, MSG_DISPATCHER};
+use schema::appdata::{AppRef, APPDATA};
+use schema::event::{Event, EventFilter};
+use schema::receipt::TransactionStatus;
 use schema::signed_transaction::SignedTransaction;
-use schema::transaction_pool::{TxnPool, TxnPoolKeyType, POOL};
+use schema::transaction_pool::{TxnPool, POOL};
+use sdk::query::AppQuery;
+use std::time::Duration;
+use utils::configreader;
 use utils::serializer::{deserialize, serialize};
 
 pub struct ClientServices {}
@@ -18,17 +25,20 @@ impl ClientServices {
     ) -> HttpResponse {
         if let Ok(txn) = deserialize::<SignedTransaction>(&transaction) {
             debug!("submit_transaction {:?}", txn);
-            if let Some(string) = txn.header.get(&String::from("timestamp")) {
-                if let Ok(timestamp) = string.parse::<TxnPoolKeyType>() {
-                    POOL.insert_op(&timestamp, &txn);
-                    MessageSender::send_transaction_msg(sender, txn);
-                    if let Ok(serialized_string) = serialize(&String::from("txn added in the pool"))
-                    {
-                        return HttpResponse::Ok().body(serialized_string);
-                    };
-                    // return HttpResponse::Ok().body("txn added in the pool".as_bytes());
-                }
+            let app_result: Result<AppRef, String> = APPDATA.lock().unwrap().get_app(&txn.app_name);
+            let check_result: Result<(), String> =
+                app_result.and_then(|app| app.check_transaction(&txn));
+            if let Err(reason) = check_result {
+                return HttpResponse::BadRequest().body(reason);
             }
+            if let Err(reason) = POOL.insert_op(&txn) {
+                return HttpResponse::BadRequest().body(reason);
+            }
+            MessageSender::send_transaction_msg(sender, txn);
+            if let Ok(serialized_string) = serialize(&String::from("txn added in the pool")) {
+                return HttpResponse::Ok().body(serialized_string);
+            };
+            // return HttpResponse::Ok().body("txn added in the pool".as_bytes());
         }
         HttpResponse::BadRequest().body("txn couldn't deserialize")
     }
@@ -46,10 +56,13 @@ impl ClientServices {
         HttpResponse::BadRequest().body("txn_hash couldn't deserialize")
     }
 
-    pub fn fetch_confirm_transaction_service(transaction_hash: web::Bytes) -> HttpResponse {
+    pub fn fetch_confirm_transaction_service(
+        db: &DbInstance,
+        transaction_hash: web::Bytes,
+    ) -> HttpResponse {
         if let Ok(txn_hash) = deserialize::<Hash>(&transaction_hash) {
             debug!("fetch_confirm_transaction {:?}", txn_hash);
-            let snapshot = snapshot_db();
+            let snapshot = db.snapshot();
             let schema = SchemaSnap::new(&snapshot);
             if let Some(transaction) = schema.get_transaction(txn_hash) {
                 if let Ok(serialized_transaction) = serialize(&transaction) {
@@ -61,10 +74,55 @@ impl ClientServices {
         HttpResponse::BadRequest().body("txn_hash couldn't deserialize")
     }
 
-    pub fn fetch_state_service(address: web::Bytes) -> HttpResponse {
+    pub fn fetch_transaction_receipt_service(
+        db: &DbInstance,
+        transaction_hash: web::Bytes,
+    ) -> HttpResponse {
+        if let Ok(txn_hash) = deserialize::<Hash>(&transaction_hash) {
+            debug!("fetch_transaction_receipt {:?}", txn_hash);
+            let snapshot = db.snapshot();
+            let schema = SchemaSnap::new(&snapshot);
+            if let Some(receipt) = schema.get_receipt(txn_hash) {
+                if let Ok(serialized_receipt) = serialize(&receipt) {
+                    return HttpResponse::Ok().body(serialized_receipt);
+                };
+            }
+            return HttpResponse::BadRequest().body("BadRequest");
+        }
+        HttpResponse::BadRequest().body("txn_hash couldn't deserialize")
+    }
+
+    pub fn fetch_transaction_status_service(
+        db: &DbInstance,
+        transaction_hash: web::Bytes,
+    ) -> HttpResponse {
+        if let Ok(txn_hash) = deserialize::<Hash>(&transaction_hash) {
+            debug!("fetch_transaction_status {:?}", txn_hash);
+            let status: TransactionStatus = {
+                let snapshot = db.snapshot();
+                let schema = SchemaSnap::new(&snapshot);
+                if let Some(receipt) = schema.get_receipt(txn_hash) {
+                    TransactionStatus::from_receipt(receipt)
+                } else if let Some(_) = POOL.get(&txn_hash) {
+                    TransactionStatus::Pending
+                } else if let Some(evicted_status) = POOL.get_evicted_status(&txn_hash) {
+                    evicted_status
+                } else {
+                    TransactionStatus::Unknown
+                }
+            };
+            if let Ok(serialized_status) = serialize(&status) {
+                return HttpResponse::Ok().body(serialized_status);
+            };
+            return HttpResponse::BadRequest().body("BadRequest");
+        }
+        HttpResponse::BadRequest().body("txn_hash couldn't deserialize")
+    }
+
+    pub fn fetch_state_service(db: &DbInstance, address: web::Bytes) -> HttpResponse {
         if let Ok(public_address) = deserialize::<String>(&address) {
             debug!("fetch_state {:?}", public_address);
-            let snapshot = snapshot_db();
+            let snapshot = db.snapshot();
             let schema = SchemaSnap::new(&snapshot);
             if let Some(state) = schema.get_state(public_address) {
                 if let Ok(serialized_state) = serialize(&state) {
@@ -76,10 +134,115 @@ impl ClientServices {
         HttpResponse::BadRequest().body("string couldn't deserialize")
     }
 
-    pub fn fetch_block_peer_service(address: web::Bytes) -> HttpResponse {
+    pub fn query_service(db: &DbInstance, app_name: String, query: web::Bytes) -> HttpResponse {
+        if let Ok(app_query) = deserialize::<AppQuery>(&query) {
+            debug!("query {} {:?}", app_name, app_query);
+            let snapshot = db.snapshot();
+            let schema = SchemaSnap::new(&snapshot);
+            let app_result: Result<AppRef, String> = APPDATA.lock().unwrap().get_app(&app_name);
+            let query_result: Result<Vec<u8>, String> =
+                app_result.and_then(|app| app.query(&app_query.path, &app_query.params, &schema));
+            return match query_result {
+                Ok(response) => HttpResponse::Ok().body(response),
+                Err(reason) => HttpResponse::BadRequest().body(reason),
+            };
+        }
+        HttpResponse::BadRequest().body("query couldn't deserialize")
+    }
+
+    /// this function will return committed events of given app matching the filter along
+    /// with the blockchain length, events of all blocks below it are final
+    pub fn fetch_events(
+        db: &DbInstance,
+        app_name: &String,
+        filter: &EventFilter,
+    ) -> (Vec<Event>, u64) {
+        let snapshot = db.snapshot();
+        let schema = SchemaSnap::new(&snapshot);
+        let blockchain_length: u64 = schema.get_blockchain_length();
+        let events: Vec<Event> = schema
+            .get_events(app_name, filter)
+            .into_iter()
+            .filter(|event| event.get_block_height() < blockchain_length)
+            .collect();
+        (events, blockchain_length)
+    }
+
+    pub fn events_service(db: &DbInstance, app_name: String, filter: web::Bytes) -> HttpResponse {
+        if let Ok(event_filter) = deserialize::<EventFilter>(&filter) {
+            debug!("events {} {:?}", app_name, event_filter);
+            let (events, _) = ClientServices::fetch_events(db, &app_name, &event_filter);
+            if let Ok(serialized_events) = serialize(&events) {
+                return HttpResponse::Ok().body(serialized_events);
+            }
+            return HttpResponse::BadRequest().body("BadRequest");
+        }
+        HttpResponse::BadRequest().body("event filter couldn't deserialize")
+    }
+
+    /// this function will frame serialized events for stream_events_service, the frame is
+    /// 4 byte big-endian length of the serialized Vec<Event> followed by it
+    pub fn event_frame(events: &[Event]) -> Result<Vec<u8>, String> {
+        let serialized_events: Vec<u8> = serialize(&events)?;
+        let mut frame: Vec<u8> = (serialized_events.len() as u32).to_be_bytes().to_vec();
+        frame.extend(serialized_events);
+        Ok(frame)
+    }
+
+    /// this function will stream events of given app as blocks get committed. Every chunk
+    /// is an event_frame of newly committed blocks, frames of no event keep the
+    /// connection alive. Stream ends once to_height of filter is committed.
+    pub fn stream_events_service(
+        db: &DbInstance,
+        app_name: String,
+        filter: web::Bytes,
+    ) -> HttpResponse {
+        if let Ok(event_filter) = deserialize::<EventFilter>(&filter) {
+            debug!("stream events {} {:?}", app_name, event_filter);
+            let poll_interval: Duration = Duration::from_micros(
+                configreader::GLOBAL_CONFIG
+                    .block_config
+                    .block_creation_time_limit as u64,
+            );
+            let db: DbInstance = db.clone();
+            let event_stream = stream::unfold(
+                (app_name, event_filter, true),
+                move |(app_name, mut event_filter, first_poll)| {
+                    let db: DbInstance = db.clone();
+                    async move {
+                        if let Some(to_height) = event_filter.to_height {
+                            if event_filter.from_height > to_height {
+                                return None;
+                            }
+                        }
+                        if !first_poll {
+                            delay_for(poll_interval).await;
+                        }
+                        let (events, blockchain_length) =
+                            ClientServices::fetch_events(&db, &app_name, &event_filter);
+                        if blockchain_length > event_filter.from_height {
+                            event_filter.from_height = blockchain_length;
+                        }
+                        let chunk: Result<web::Bytes, Error> =
+                            match ClientServices::event_frame(&events) {
+                                Ok(frame) => Ok(web::Bytes::from(frame)),
+                                Err(reason) => {
+                                    Err(actix_web::error::ErrorInternalServerError(reason))
+                                }
+                            };
+                        Some((chunk, (app_name, event_filter, false)))
+                    }
+                },
+            );
+            return HttpResponse::Ok().streaming(Box::pin(event_stream));
+        }
+        HttpResponse::BadRequest().body("event filter couldn't deserialize")
+    }
+
+    pub fn fetch_block_peer_service(db: &DbInstance, address: web::Bytes) -> HttpResponse {
         if let Ok(block_index) = deserialize::<u64>(&address) {
             debug!("fetch_block {:?}", block_index);
-            let snapshot = snapshot_db();
+            let snapshot = db.snapshot();
             let schema = SchemaSnap::new(&snapshot);
             if let Some(block) = schema.get_block(block_index) {
                 if let Ok(serialized_block) = serialize(&block) {
@@ -91,8 +254,8 @@ impl ClientServices {
         HttpResponse::BadRequest().body("block index couldn't deserialize")
     }
 
-    pub fn fetch_latest_block_peer_service() -> HttpResponse {
-        let snapshot = snapshot_db();
+    pub fn fetch_latest_block_peer_service(db: &DbInstance) -> HttpResponse {
+        let snapshot = db.snapshot();
         let schema = SchemaSnap::new(&snapshot);
         if let Some(block) = schema.get_root_block() {
             if let Ok(serialized_block) = serialize(&block) {
@@ -102,10 +265,10 @@ impl ClientServices {
         return HttpResponse::BadRequest().body("BadRequest");
     }
 
-    pub fn fetch_block_service(address: web::Bytes) -> HttpResponse {
+    pub fn fetch_block_service(db: &DbInstance, address: web::Bytes) -> HttpResponse {
         if let Ok(block_index) = deserialize::<u64>(&address) {
             debug!("fetch_block {:?}", block_index);
-            let snapshot = snapshot_db();
+            let snapshot = db.snapshot();
             let schema = SchemaSnap::new(&snapshot);
             if let Some(block) = schema.get_block(block_index) {
                 let block_string: String = block.to_string_format();
@@ -118,8 +281,8 @@ impl ClientServices {
         HttpResponse::BadRequest().body("block index couldn't deserialize")
     }
 
-    pub fn fetch_latest_block_service() -> HttpResponse {
-        let snapshot = snapshot_db();
+    pub fn fetch_latest_block_service(db: &DbInstance) -> HttpResponse {
+        let snapshot = db.snapshot();
         let schema = SchemaSnap::new(&snapshot);
         if let Some(block) = schema.get_root_block() {
             let block_string: String = block.to_string_format();
@@ -130,8 +293,8 @@ impl ClientServices {
         return HttpResponse::BadRequest().body("BadRequest");
     }
 
-    pub fn fetch_blockchain_length_peer_service() -> HttpResponse {
-        let snapshot = snapshot_db();
+    pub fn fetch_blockchain_length_peer_service(db: &DbInstance) -> HttpResponse {
+        let snapshot = db.snapshot();
         let schema = SchemaSnap::new(&snapshot);
         if let Ok(serialized_length) = serialize(&schema.get_blockchain_length()) {
             return HttpResponse::Ok().body(serialized_length);
@@ -139,8 +302,8 @@ impl ClientServices {
         HttpResponse::BadRequest().body("BadRequest")
     }
 
-    pub fn fetch_blockchain_length_service() -> HttpResponse {
-        let snapshot = snapshot_db();
+    pub fn fetch_blockchain_length_service(db: &DbInstance) -> HttpResponse {
+        let snapshot = db.snapshot();
         let schema = SchemaSnap::new(&snapshot);
         if let Ok(serialized_length) = serialize(&schema.get_blockchain_length()) {
             return HttpResponse::Ok().body(serialized_length);
@@ -148,7 +311,10 @@ impl ClientServices {
         HttpResponse::BadRequest().body("BadRequest")
     }
 
-    pub fn fetch_transaction_peer_service(transaction_hash: web::Bytes) -> HttpResponse {
+    pub fn fetch_transaction_peer_service(
+        db: &DbInstance,
+        transaction_hash: web::Bytes,
+    ) -> HttpResponse {
         if let Ok(txn_hash) = deserialize::<Hash>(&transaction_hash) {
             debug!("fetching transaction for peer {:?}", txn_hash);
             if let Some(transaction) = POOL.get(&txn_hash) {
@@ -156,7 +322,7 @@ impl ClientServices {
                     return HttpResponse::Ok().body(serialized_transaction);
                 };
             } else {
-                let snapshot = snapshot_db();
+                let snapshot = db.snapshot();
                 let schema = SchemaSnap::new(&snapshot);
                 if let Some(transaction) = schema.get_transaction(txn_hash) {
                     if let Ok(serialized_transaction) = serialize(&transaction) {
@@ -168,4 +334,54 @@ impl ClientServices {
         }
         HttpResponse::BadRequest().body("txn_hash couldn't deserialize")
     }
+
+    /// this function will check whether admin api is served, it needs dev consensus and a
+    /// configured admin_token
+    pub fn admin_api_enabled() -> bool {
+        let node = &configreader::GLOBAL_CONFIG.node;
+        node.consensus_name == "dev" && !node.admin_token.is_empty()
+    }
+
+    /// this function will check the authorization header of an admin request against given
+    /// token, an empty token authorizes nothing
+    pub fn admin_authorized(admin_token: &str, authorization: Option<&str>) -> bool {
+        if admin_token.is_empty() {
+            return false;
+        }
+        let token: &[u8] = match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(token) => token.as_bytes(),
+            None => return false,
+        };
+        if token.len() != admin_token.len() {
+            return false;
+        }
+        // compare every byte, time taken doesn't reveal the matching prefix
+        token
+            .iter()
+            .zip(admin_token.as_bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+
+    /// this function will ask dev consensus to seal a block now, other engines seal blocks
+    /// on their own schedule. The request has to carry admin_token as bearer token
+    pub fn seal_block_service(authorization: Option<&str>) -> HttpResponse {
+        if configreader::GLOBAL_CONFIG.node.consensus_name != "dev" {
+            return HttpResponse::BadRequest()
+                .body("blocks are sealed on demand only by dev consensus");
+        }
+        if !ClientServices::admin_authorized(
+            &configreader::GLOBAL_CONFIG.node.admin_token,
+            authorization,
+        ) {
+            return HttpResponse::Unauthorized().body("admin token missing or invalid");
+        }
+        if let Err(reason) = MSG_DISPATCHER.request_consensus(ConsensusRequest::SealBlock) {
+            return HttpResponse::InternalServerError().body(reason);
+        }
+        if let Ok(serialized_string) = serialize(&String::from("block seal requested")) {
+            return HttpResponse::Ok().body(serialized_string);
+        }
+        HttpResponse::BadRequest().body("BadRequest")
+    }
 }
@@ -5,25 +5,38 @@ mod test_controller_services {
         dev::{Body, ResponseBody},
         web, HttpResponse,
     };
-    use db_service::{
-        db_fork_ref::SchemaFork,
-        db_layer::{fork_db, patch_db},
-    };
+    use db_service::{db_fork_ref::SchemaFork, db_layer::DbInstance};
     use exonum_crypto::Hash;
     use exonum_merkledb::ObjectHash;
     use futures::channel::mpsc::*;
     use message_handler::messages::MessageTypes;
+    use schema::appdata::APPDATA;
     use schema::block::SignedBlock;
+    use schema::event::{Event, EventFilter};
+    use schema::receipt::{Receipt, TransactionStatus};
     use schema::signed_transaction::SignedTransaction;
     use schema::state::State;
-    use schema::transaction_pool::TxnPoolKeyType;
-    use sdk::traits::StateContext;
+    use schema::transaction_pool::{TxnPool, TxnPoolKeyType, POOL};
+    use sdk::namespace::namespaced_key;
+    use sdk::query::AppQuery;
+    use sdk::test_utils::MockApp;
+    use sdk::traits::{AppHandler, ChainStateContext, StateContext};
     use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
     use std::time::SystemTime;
     use std::{thread, time::Duration};
     use utils::configreader::initialize_config;
     use utils::serializer::{deserialize, serialize};
 
+    fn register_mock_app() {
+        let app: Box<dyn AppHandler + Send> = Box::new(MockApp::new("app_name"));
+        APPDATA
+            .lock()
+            .unwrap()
+            .appdata
+            .insert(app.name(), Arc::new(Mutex::new(app)));
+    }
+
     fn test_submit_transaction_service() {
         let (mut sender, mut receiver) = channel::<Option<MessageTypes>>(4194304);
         let mut header = HashMap::default();
@@ -60,7 +73,46 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_pending_transaction_service() {
+    fn test_submit_transaction_without_timestamp() {
+        let (mut sender, _receiver) = channel::<Option<MessageTypes>>(4194304);
+        let signed_transaction: SignedTransaction = SignedTransaction {
+            txn: vec![1],
+            app_name: String::from("app_name"),
+            header: HashMap::default(),
+            signature: vec![0],
+        };
+        let transaction: web::Bytes = web::Bytes::from(serialize(&signed_transaction).unwrap());
+        let http_response: HttpResponse =
+            ClientServices::submit_transaction_service(transaction, &mut sender);
+        assert_eq!(http_response.status(), 400);
+    }
+
+    fn test_submit_transaction_admission() {
+        let (mut sender, _receiver) = channel::<Option<MessageTypes>>(4194304);
+        let time_stamp: TxnPoolKeyType = 6565656565;
+        let mut header = HashMap::default();
+        header.insert("timestamp".to_string(), time_stamp.to_string());
+        let mut signed_transaction: SignedTransaction = SignedTransaction {
+            txn: vec![2],
+            app_name: String::from("unknown_app"),
+            header,
+            signature: vec![0],
+        };
+        let transaction: web::Bytes = web::Bytes::from(serialize(&signed_transaction).unwrap());
+        let http_response: HttpResponse =
+            ClientServices::submit_transaction_service(transaction, &mut sender);
+        assert_eq!(http_response.status(), 400);
+
+        signed_transaction.app_name = String::from("app_name");
+        signed_transaction.signature = vec![];
+        let transaction: web::Bytes = web::Bytes::from(serialize(&signed_transaction).unwrap());
+        let http_response: HttpResponse =
+            ClientServices::submit_transaction_service(transaction, &mut sender);
+        assert_eq!(http_response.status(), 400);
+        assert_eq!(POOL.get(&signed_transaction.object_hash()), None);
+    }
+
+    fn test_fetch_pending_transaction_service(db: &DbInstance) {
         let time_stamp: TxnPoolKeyType = 6565656565;
         let mut header = HashMap::default();
         header.insert("timestamp".to_string(), time_stamp.to_string());
@@ -90,7 +142,7 @@ mod test_controller_services {
         }
 
         let mut http_response: HttpResponse =
-            ClientServices::fetch_transaction_peer_service(transaction_hash);
+            ClientServices::fetch_transaction_peer_service(db, transaction_hash);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -107,7 +159,7 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_confirm_transaction_service() {
+    fn test_fetch_confirm_transaction_service(db: &DbInstance) {
         let mut header = HashMap::default();
         let time_stamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -121,15 +173,15 @@ mod test_controller_services {
             signature: vec![0],
         };
         let txn_hash: Hash = signed_transaction.object_hash();
-        let fork = fork_db();
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             schema.put_txn(&txn_hash, signed_transaction.clone());
         }
-        patch_db(fork);
+        db.patch(fork);
         let transaction_hash: web::Bytes = web::Bytes::from(serialize(&txn_hash).unwrap());
         let mut http_response: HttpResponse =
-            ClientServices::fetch_confirm_transaction_service(transaction_hash.clone());
+            ClientServices::fetch_confirm_transaction_service(db, transaction_hash.clone());
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -146,7 +198,7 @@ mod test_controller_services {
         }
 
         let mut http_response: HttpResponse =
-            ClientServices::fetch_transaction_peer_service(transaction_hash);
+            ClientServices::fetch_transaction_peer_service(db, transaction_hash);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -163,17 +215,103 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_state_service() {
+    fn test_fetch_transaction_receipt_service(db: &DbInstance) {
+        let txn_hash: Hash = Hash::zero();
+        let result: Result<(), String> = Err(String::from("transfer call failed"));
+        let receipt: Receipt = Receipt::new(txn_hash, &result, 1, 0);
+        let fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            schema.put_receipt(&txn_hash, receipt.clone());
+        }
+        db.patch(fork);
+        let transaction_hash: web::Bytes = web::Bytes::from(serialize(&txn_hash).unwrap());
+        let mut http_response: HttpResponse =
+            ClientServices::fetch_transaction_receipt_service(db, transaction_hash);
+        if http_response.status() == 200 {
+            let response_body: ResponseBody<Body> = http_response.take_body();
+            let body_ref = response_body.as_ref().unwrap();
+            let body_vec: Vec<u8> = match body_ref {
+                Body::None => panic!("invalid response body type"),
+                Body::Empty => panic!("invalid response body type"),
+                Body::Bytes(ref b) => b.to_vec(),
+                Body::Message(_) => panic!("invalid response body type"),
+            };
+            let output: Receipt = deserialize(&body_vec).unwrap();
+            assert_eq!(output, receipt);
+            assert_eq!(output.is_success(), false);
+        } else {
+            panic!("http_response not equal to 200");
+        }
+    }
+
+    fn fetch_transaction_status(db: &DbInstance, txn_hash: &Hash) -> TransactionStatus {
+        let transaction_hash: web::Bytes = web::Bytes::from(serialize(txn_hash).unwrap());
+        let mut http_response: HttpResponse =
+            ClientServices::fetch_transaction_status_service(db, transaction_hash);
+        if http_response.status() == 200 {
+            let response_body: ResponseBody<Body> = http_response.take_body();
+            let body_ref = response_body.as_ref().unwrap();
+            let body_vec: Vec<u8> = match body_ref {
+                Body::None => panic!("invalid response body type"),
+                Body::Empty => panic!("invalid response body type"),
+                Body::Bytes(ref b) => b.to_vec(),
+                Body::Message(_) => panic!("invalid response body type"),
+            };
+            deserialize(&body_vec).unwrap()
+        } else {
+            panic!("http_response not equal to 200");
+        }
+    }
+
+    fn test_fetch_transaction_status_service(db: &DbInstance) {
+        // receipt stored by test_fetch_transaction_receipt_service
+        match fetch_transaction_status(db, &Hash::zero()) {
+            TransactionStatus::Failed(receipt) => assert_eq!(receipt.get_block_height(), 1),
+            _ => panic!("transaction status should be failed"),
+        }
+
+        // transaction submitted by test_submit_transaction_service
+        let time_stamp: TxnPoolKeyType = 6565656565;
+        let mut header = HashMap::default();
+        header.insert("timestamp".to_string(), time_stamp.to_string());
+        let signed_transaction: SignedTransaction = SignedTransaction {
+            txn: vec![0],
+            app_name: String::from("app_name"),
+            header,
+            signature: vec![0],
+        };
+        let txn_hash: Hash = signed_transaction.object_hash();
+        assert_eq!(
+            fetch_transaction_status(db, &txn_hash),
+            TransactionStatus::Pending
+        );
+        POOL.evict_txns(&vec![txn_hash]);
+        assert_eq!(
+            fetch_transaction_status(db, &txn_hash),
+            TransactionStatus::Expired
+        );
+        POOL.insert_op(&signed_transaction).unwrap();
+
+        let unknown_hash: Hash = exonum_crypto::hash(&[1, 2, 3]);
+        assert_eq!(
+            fetch_transaction_status(db, &unknown_hash),
+            TransactionStatus::Unknown
+        );
+    }
+
+    fn test_fetch_state_service(db: &DbInstance) {
         let state_key: String = String::from("dkcnjsdcnosdvnvsfv");
         let state: State = State::new();
-        let fork = fork_db();
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             schema.put(&state_key, state.clone());
         }
-        patch_db(fork);
+        db.patch(fork);
         let state_key_bytes: web::Bytes = web::Bytes::from(serialize(&state_key).unwrap());
-        let mut http_response: HttpResponse = ClientServices::fetch_state_service(state_key_bytes);
+        let mut http_response: HttpResponse =
+            ClientServices::fetch_state_service(db, state_key_bytes);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -190,15 +328,59 @@ mod test_controller_services {
         }
     }
 
-    fn fetch_block_peer_service() {
-        let fork = fork_db();
+    fn test_query_service(db: &DbInstance) {
+        let state_key: String = String::from("query_state_key");
+        let fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            // app sees its own namespace only
+            schema.put(&namespaced_key("app_name", &state_key), State::new());
+        }
+        db.patch(fork);
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert(String::from("address"), state_key);
+        let mut app_query = AppQuery {
+            path: String::from("contains"),
+            params,
+        };
+        let query_bytes: web::Bytes = web::Bytes::from(serialize(&app_query).unwrap());
+        let mut http_response: HttpResponse =
+            ClientServices::query_service(db, String::from("app_name"), query_bytes);
+        if http_response.status() == 200 {
+            let response_body: ResponseBody<Body> = http_response.take_body();
+            let body_ref = response_body.as_ref().unwrap();
+            let body_vec: Vec<u8> = match body_ref {
+                Body::None => panic!("invalid response body type"),
+                Body::Empty => panic!("invalid response body type"),
+                Body::Bytes(ref b) => b.to_vec(),
+                Body::Message(_) => panic!("invalid response body type"),
+            };
+            let output: bool = deserialize(&body_vec).unwrap();
+            assert_eq!(output, true);
+        } else {
+            panic!("http_response not equal to 200");
+        }
+        let query_bytes: web::Bytes = web::Bytes::from(serialize(&app_query).unwrap());
+        let http_response: HttpResponse =
+            ClientServices::query_service(db, String::from("unknown_app"), query_bytes);
+        assert_eq!(http_response.status(), 400);
+        app_query.path = String::from("balance");
+        let query_bytes: web::Bytes = web::Bytes::from(serialize(&app_query).unwrap());
+        let http_response: HttpResponse =
+            ClientServices::query_service(db, String::from("app_name"), query_bytes);
+        assert_eq!(http_response.status(), 400);
+    }
+
+    fn fetch_block_peer_service(db: &DbInstance) {
+        let fork = db.fork();
         {
             let mut schema = SchemaFork::new(&fork);
             schema.initialize_db(Vec::new(), 0);
         }
-        patch_db(fork);
+        db.patch(fork);
         let index_byes: web::Bytes = web::Bytes::from(serialize(&0).unwrap());
-        let mut http_response: HttpResponse = ClientServices::fetch_block_peer_service(index_byes);
+        let mut http_response: HttpResponse =
+            ClientServices::fetch_block_peer_service(db, index_byes);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -216,8 +398,77 @@ mod test_controller_services {
         }
     }
 
-    fn fetch_latest_block_peer_service() {
-        let mut http_response: HttpResponse = ClientServices::fetch_latest_block_peer_service();
+    fn test_events_service(db: &DbInstance) {
+        let txn_hash: Hash = Hash::zero();
+        let event_of = |topic: &str, block_height: u64, index: u64| {
+            Event::new(
+                String::from("app_name"),
+                String::from(topic),
+                vec![1],
+                txn_hash,
+                block_height,
+                index,
+            )
+        };
+        let committed: Event = event_of("approved", 0, 0);
+        let mut receipt: Receipt = Receipt::new(txn_hash, &Ok(()), 0, 0);
+        receipt.set_events(vec![committed.clone(), event_of("rejected", 0, 1)]);
+        // events above blockchain length aren't final yet
+        let mut pending_receipt: Receipt = Receipt::new(Hash::default(), &Ok(()), 5, 0);
+        pending_receipt.set_events(vec![event_of("approved", 5, 0)]);
+        let fork = db.fork();
+        {
+            let mut schema = SchemaFork::new(&fork);
+            schema.put_receipt(&txn_hash, receipt);
+            schema.put_receipt(&exonum_crypto::hash(&[5]), pending_receipt);
+        }
+        db.patch(fork);
+        let event_filter = EventFilter {
+            topic: Some(String::from("approved")),
+            from_height: 0,
+            to_height: None,
+        };
+        let filter_bytes: web::Bytes = web::Bytes::from(serialize(&event_filter).unwrap());
+        let mut http_response: HttpResponse =
+            ClientServices::events_service(db, String::from("app_name"), filter_bytes);
+        if http_response.status() == 200 {
+            let response_body: ResponseBody<Body> = http_response.take_body();
+            let body_ref = response_body.as_ref().unwrap();
+            let body_vec: Vec<u8> = match body_ref {
+                Body::None => panic!("invalid response body type"),
+                Body::Empty => panic!("invalid response body type"),
+                Body::Bytes(ref b) => b.to_vec(),
+                Body::Message(_) => panic!("invalid response body type"),
+            };
+            let output: Vec<Event> = deserialize(&body_vec).unwrap();
+            assert_eq!(output, vec![committed.clone()]);
+        } else {
+            panic!("http_response not equal to 200");
+        }
+        let filter_bytes: web::Bytes = web::Bytes::from(serialize(&event_filter).unwrap());
+        let (events, _) =
+            ClientServices::fetch_events(db, &String::from("unknown_app"), &event_filter);
+        assert!(events.is_empty());
+        // stream chunks are length prefixed so a client can split them
+        let frame: Vec<u8> = ClientServices::event_frame(&vec![committed.clone()]).unwrap();
+        let mut frame_length: [u8; 4] = [0; 4];
+        frame_length.copy_from_slice(&frame[..4]);
+        assert_eq!(u32::from_be_bytes(frame_length) as usize, frame.len() - 4);
+        let framed_events: Vec<Event> = deserialize(&frame[4..]).unwrap();
+        assert_eq!(framed_events, vec![committed.clone()]);
+        let http_response: HttpResponse =
+            ClientServices::stream_events_service(db, String::from("app_name"), filter_bytes);
+        assert_eq!(http_response.status(), 200);
+        let http_response: HttpResponse = ClientServices::events_service(
+            db,
+            String::from("app_name"),
+            web::Bytes::from(vec![0xff]),
+        );
+        assert_eq!(http_response.status(), 400);
+    }
+
+    fn fetch_latest_block_peer_service(db: &DbInstance) {
+        let mut http_response: HttpResponse = ClientServices::fetch_latest_block_peer_service(db);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -238,9 +489,9 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_block_service() {
+    fn test_fetch_block_service(db: &DbInstance) {
         let index_byes: web::Bytes = web::Bytes::from(serialize(&0).unwrap());
-        let mut http_response: HttpResponse = ClientServices::fetch_block_service(index_byes);
+        let mut http_response: HttpResponse = ClientServices::fetch_block_service(db, index_byes);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -256,8 +507,8 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_latest_block_servic() {
-        let mut http_response: HttpResponse = ClientServices::fetch_latest_block_service();
+    fn test_fetch_latest_block_servic(db: &DbInstance) {
+        let mut http_response: HttpResponse = ClientServices::fetch_latest_block_service(db);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -273,9 +524,9 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_blockchain_length_peer_service() {
+    fn test_fetch_blockchain_length_peer_service(db: &DbInstance) {
         let mut http_response: HttpResponse =
-            ClientServices::fetch_blockchain_length_peer_service();
+            ClientServices::fetch_blockchain_length_peer_service(db);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -291,8 +542,8 @@ mod test_controller_services {
         }
     }
 
-    fn test_fetch_blockchain_length_service() {
-        let mut http_response: HttpResponse = ClientServices::fetch_blockchain_length_service();
+    fn test_fetch_blockchain_length_service(db: &DbInstance) {
+        let mut http_response: HttpResponse = ClientServices::fetch_blockchain_length_service(db);
         if http_response.status() == 200 {
             let response_body: ResponseBody<Body> = http_response.take_body();
             let body_ref = response_body.as_ref().unwrap();
@@ -308,18 +559,64 @@ mod test_controller_services {
         }
     }
 
+    fn test_seal_block_service() {
+        // node config runs poa consensus, it seals blocks on its own schedule
+        let mut http_response: HttpResponse =
+            ClientServices::seal_block_service(Some("Bearer secret"));
+        assert_eq!(http_response.status(), 400);
+        let response_body: ResponseBody<Body> = http_response.take_body();
+        match response_body.as_ref().unwrap() {
+            Body::Bytes(ref b) => assert_eq!(
+                b.to_vec(),
+                b"blocks are sealed on demand only by dev consensus".to_vec()
+            ),
+            _ => panic!("invalid response body type"),
+        };
+        assert!(!ClientServices::admin_api_enabled());
+    }
+
+    #[test]
+    fn test_admin_authorized() {
+        assert!(ClientServices::admin_authorized(
+            "secret",
+            Some("Bearer secret")
+        ));
+        assert!(!ClientServices::admin_authorized(
+            "secret",
+            Some("Bearer secreT")
+        ));
+        assert!(!ClientServices::admin_authorized(
+            "secret",
+            Some("Bearer secret1")
+        ));
+        assert!(!ClientServices::admin_authorized("secret", Some("secret")));
+        assert!(!ClientServices::admin_authorized("secret", None));
+        // admin api is disabled without a token
+        assert!(!ClientServices::admin_authorized("", Some("Bearer ")));
+        assert!(!ClientServices::admin_authorized("", None));
+    }
+
     #[test]
     fn test_controller_services() {
         initialize_config("../../config.toml");
+        let db: DbInstance = DbInstance::temporary();
+        register_mock_app();
         test_submit_transaction_service();
-        test_fetch_pending_transaction_service();
-        test_fetch_confirm_transaction_service();
-        test_fetch_state_service();
-        fetch_block_peer_service();
-        fetch_latest_block_peer_service();
-        test_fetch_block_service();
-        test_fetch_latest_block_servic();
-        test_fetch_blockchain_length_peer_service();
-        test_fetch_blockchain_length_service();
+        test_submit_transaction_without_timestamp();
+        test_submit_transaction_admission();
+        test_fetch_pending_transaction_service(&db);
+        test_fetch_confirm_transaction_service(&db);
+        test_fetch_transaction_receipt_service(&db);
+        test_fetch_transaction_status_service(&db);
+        test_fetch_state_service(&db);
+        test_query_service(&db);
+        fetch_block_peer_service(&db);
+        test_events_service(&db);
+        fetch_latest_block_peer_service(&db);
+        test_fetch_block_service(&db);
+        test_fetch_latest_block_servic(&db);
+        test_fetch_blockchain_length_peer_service(&db);
+        test_fetch_blockchain_length_service(&db);
+        test_seal_block_service();
     }
 }
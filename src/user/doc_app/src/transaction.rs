@@ -5,7 +5,7 @@ use exonum_crypto::Hash;
 use exonum_merkledb::ObjectHash;
 pub use sdk::signed_transaction::SignedTransaction;
 use sdk::state::State;
-use sdk::traits::{AppHandler, StateContext};
+use sdk::traits::{AppHandler, ReadStateContext, StateContext};
 use std::collections::HashMap;
 use std::convert::AsRef;
 use std::time::SystemTime;
@@ -18,7 +18,8 @@ const APPNAME: &str = "Document_Review";
 pub const STATE_KEY: &str = "34132aec80149c4538bad4a15995ddf6a89d4ed5e39f0060e8466f6ba4dc9ceb";
 
 trait StateTraits {
-    fn execute(&self, state_context: &mut dyn StateContext) -> bool;
+    fn execute(&self, state_context: &mut dyn StateContext) -> Result<(), String>;
+    fn check(&self) -> Result<(), String>;
 }
 
 pub trait TransactionTrait<T> {
@@ -69,11 +70,15 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => return false,
         };
-        PublicKey::verify_from_encoded_pk(&txn.from, &self.txn, &self.signature.as_ref())
+        PublicKey::verify_from_encoded_pk(
+            &txn.from,
+            &self.signing_bytes(),
+            &self.signature.as_ref(),
+        )
     }
 
     fn sign(&self, kp: &KeypairType) -> Vec<u8> {
-        Keypair::sign(&kp, &self.txn)
+        Keypair::sign(&kp, &self.signing_bytes())
     }
 
     fn generate(kp: &KeypairType) -> SignedTransaction {
@@ -88,7 +93,6 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             fxn_call: String::from("set_hash"),
             payload,
         };
-        let txn_sign = txn.sign(&kp);
         let mut header = HashMap::default();
         let time_stamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -99,12 +103,14 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => vec![0],
         };
-        SignedTransaction {
+        let mut signed_txn = SignedTransaction {
             txn: serialized_txn,
             app_name: String::from(APPNAME),
-            signature: txn_sign,
+            signature: Vec::new(),
             header,
-        }
+        };
+        signed_txn.signature = signed_txn.sign(&kp);
+        signed_txn
     }
 
     fn get_hash(&self) -> Hash {
@@ -113,71 +119,68 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
 }
 
 impl StateTraits for SignedTransaction {
-    fn execute(&self, state_context: &mut dyn StateContext) -> bool {
-        let mut flag: bool = false;
+    fn execute(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        let mut result: Result<(), String> =
+            Err(String::from("transaction signature couldn't verified"));
         if self.validate() {
             let txn: CryptoTransaction = match deserialize(&self.txn) {
                 Result::Ok(value) => value,
-                Result::Err(_) => return false,
+                Result::Err(_) => return Err(String::from("transaction couldn't deserialize")),
+            };
+            result = match DocCall::decode(&txn.fxn_call, &txn.payload) {
+                Ok(call) => call.dispatch(&txn, state_context),
+                Err(reason) => Err(reason),
             };
-            let crypto_txn = &txn.clone() as &dyn ModuleTraits;
-            if txn.fxn_call == String::from("set_hash") {
-                flag = crypto_txn.set_hash(state_context);
-            } else if txn.fxn_call == String::from("add_doc") {
-                flag = crypto_txn.add_doc(state_context);
-            } else if txn.fxn_call == String::from("transfer_sc") {
-                flag = crypto_txn.transfer_sc(state_context);
-            } else if txn.fxn_call == String::from("set_pkg_no") {
-                flag = crypto_txn.set_pkg_no(state_context);
-            } else if txn.fxn_call == String::from("transfer_for_review") {
-                flag = crypto_txn.transfer_for_review(state_context);
-            } else if txn.fxn_call == String::from("review_docs") {
-                flag = crypto_txn.review_docs(state_context);
-            } else if txn.fxn_call == String::from("publish_docs") {
-                flag = crypto_txn.publish_docs(state_context);
-            } else {
-            }
         }
-        state_context.put_txn(&self.get_hash(), self.clone());
-        flag
+        result
+    }
+    fn check(&self) -> Result<(), String> {
+        if self.app_name != APPNAME {
+            return Err(format!("transaction addressed to app {}", self.app_name));
+        }
+        let txn: CryptoTransaction = match deserialize(&self.txn) {
+            Result::Ok(value) => value,
+            Result::Err(_) => return Err(String::from("transaction couldn't deserialize")),
+        };
+        if !self.validate() {
+            return Err(String::from("transaction signature couldn't verified"));
+        }
+        DocCall::decode(&txn.fxn_call, &txn.payload).map(|_| ())
     }
 }
 
-pub trait ModuleTraits {
-    fn set_hash(&self, state_context: &mut dyn StateContext) -> bool;
-    fn add_doc(&self, state_context: &mut dyn StateContext) -> bool;
-    fn transfer_sc(&self, state_context: &mut dyn StateContext) -> bool;
-    fn set_pkg_no(&self, state_context: &mut dyn StateContext) -> bool;
-    fn transfer_for_review(&self, state_context: &mut dyn StateContext) -> bool;
-    fn review_docs(&self, state_context: &mut dyn StateContext) -> bool;
-    fn publish_docs(&self, state_context: &mut dyn StateContext) -> bool;
+sdk::app_calls! {
+    /// typed calls of document review app, fxn_call is the handler method name
+    pub enum DocCall: DataTypes => pub trait ModuleTraits {
+        SetHash => fn set_hash(token_id: Hash = HashVal, file_hash: Hash = HashVal);
+        AddDoc => fn add_doc(token_ids: Vec<Hash> = VecHashVal);
+        TransferSc => fn transfer_sc(
+            token_ids: Vec<Hash> = VecHashVal,
+            to_address: String = StringVal
+        );
+        SetPkgNo => fn set_pkg_no(
+            token_ids: Vec<Hash> = VecHashVal,
+            pkg_no: String = StringVal
+        );
+        TransferForReview => fn transfer_for_review(
+            pkg_no: String = StringVal,
+            reviewer_address: String = StringVal
+        );
+        ReviewDocs => fn review_docs(
+            pkg_no: String = StringVal,
+            response_bool: bool = BoolVal
+        );
+        PublishDocs => fn publish_docs(pkg_no: String = StringVal);
+    }
 }
 
 impl ModuleTraits for CryptoTransaction {
-    fn set_hash(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 2;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::HashVal(Hash::zero()));
-        expected_payload.push(DataTypes::HashVal(Hash::zero()));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::HashVal(_a), DataTypes::HashVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let token_id: Hash = match self.payload.get(0).unwrap() {
-            DataTypes::HashVal(value) => value.clone(),
-            _ => return false,
-        };
-        let file_hash: Hash = match self.payload.get(1).unwrap() {
-            DataTypes::HashVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn set_hash(
+        &self,
+        state_context: &mut dyn StateContext,
+        token_id: Hash,
+        file_hash: Hash,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
             None => {
@@ -185,7 +188,7 @@ impl ModuleTraits for CryptoTransaction {
                 let mut state: State = State::new();
                 let serialized_doc_state: Vec<u8> = match serialize(&doc_state) {
                     Result::Ok(value) => value,
-                    Result::Err(_) => return false,
+                    Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
                 };
                 state.set_data(&serialized_doc_state);
                 state
@@ -193,46 +196,31 @@ impl ModuleTraits for CryptoTransaction {
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         let flag: bool = state.set_hash(token_id, file_hash);
         if !flag {
             info!("operation set_hash failed");
-            return false;
+            return Err(format!(
+                "file hash of token {} already set",
+                token_id.to_hex()
+            ));
         }
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
         info!("operation set_hash done");
-        true
+        Ok(())
     }
 
-    fn add_doc(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 1;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::VecHashVal(vec![]));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::IntVal(_a), DataTypes::IntVal(_d)) => {}
-                (DataTypes::HashVal(_a), DataTypes::HashVal(_d)) => {}
-                (DataTypes::StringVal(_a), DataTypes::StringVal(_d)) => {}
-                (DataTypes::VecHashVal(_a), DataTypes::VecHashVal(_d)) => {}
-                (DataTypes::VecStringVal(_a), DataTypes::VecStringVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let token_ids: Vec<Hash> = match self.payload.get(0).unwrap() {
-            DataTypes::VecHashVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn add_doc(
+        &self,
+        state_context: &mut dyn StateContext,
+        token_ids: Vec<Hash>,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
             None => {
@@ -240,7 +228,7 @@ impl ModuleTraits for CryptoTransaction {
                 let mut state: State = State::new();
                 let serialized_doc_state: Vec<u8> = match serialize(&doc_state) {
                     Result::Ok(value) => value,
-                    Result::Err(_) => return false,
+                    Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
                 };
                 state.set_data(&serialized_doc_state);
                 state
@@ -248,7 +236,7 @@ impl ModuleTraits for CryptoTransaction {
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         for each in token_ids.iter() {
             let token: NFTToken = NFTToken {
@@ -259,121 +247,86 @@ impl ModuleTraits for CryptoTransaction {
             };
             let flag: bool = state.add_nft_token(each.clone(), token);
             if !flag {
-                return false;
+                return Err(format!("token {} already exists", each.to_hex()));
             }
         }
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
-        true
+        Ok(())
     }
 
-    fn transfer_sc(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 2;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::VecHashVal(vec![]));
-        expected_payload.push(DataTypes::StringVal(String::default()));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::StringVal(_a), DataTypes::StringVal(_d)) => {}
-                (DataTypes::VecHashVal(_a), DataTypes::VecHashVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let token_ids: Vec<Hash> = match self.payload.get(0).unwrap() {
-            DataTypes::VecHashVal(value) => value.clone(),
-            _ => return false,
-        };
-        let to_address: String = match self.payload.get(1).unwrap() {
-            DataTypes::StringVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn transfer_sc(
+        &self,
+        state_context: &mut dyn StateContext,
+        token_ids: Vec<Hash>,
+        to_address: String,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
-            None => return false,
+            None => return Err(String::from("doc state not found")),
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         for each in token_ids.iter() {
             match state.get_nft_token(each.clone()) {
                 Some(token) => {
                     if token.owner != self.from {
-                        return false;
+                        return Err(format!("token {} isn't owned by sender", each.to_hex()));
                     }
                 }
-                None => return false,
+                None => return Err(format!("token {} not found", each.to_hex())),
             }
         }
         state.add_into_confirmation_list(&to_address, &token_ids);
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
-        true
+        Ok(())
     }
 
-    fn set_pkg_no(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 2;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::VecHashVal(vec![]));
-        expected_payload.push(DataTypes::StringVal(String::default()));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::StringVal(_a), DataTypes::StringVal(_d)) => {}
-                (DataTypes::VecHashVal(_a), DataTypes::VecHashVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let token_ids: Vec<Hash> = match self.payload.get(0).unwrap() {
-            DataTypes::VecHashVal(value) => value.clone(),
-            _ => return false,
-        };
-        let pkg_no: String = match self.payload.get(1).unwrap() {
-            DataTypes::StringVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn set_pkg_no(
+        &self,
+        state_context: &mut dyn StateContext,
+        token_ids: Vec<Hash>,
+        pkg_no: String,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
-            None => return false,
+            None => return Err(String::from("doc state not found")),
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         let mut waiting_list: Vec<Hash> = match state.get_confirmation_waiting_list(&self.from) {
             Some(list) => list.clone(),
-            None => return false,
+            None => return Err(String::from("no tokens waiting for confirmation of sender")),
         };
         let mut token_map: HashMap<Hash, NFTToken> = HashMap::new();
         for each in token_ids.iter() {
             if !waiting_list.contains(each) {
-                return false;
+                return Err(format!(
+                    "token {} isn't waiting for confirmation of sender",
+                    each.to_hex()
+                ));
             }
             let token: NFTToken = match state.get_nft_token(each.clone()) {
                 Some(token) => {
                     if token.status != DocStatus::Created {
-                        return false;
+                        return Err(format!("token {} isn't in created status", each.to_hex()));
                     }
                     token.clone()
                 }
-                None => return false,
+                None => return Err(format!("token {} not found", each.to_hex())),
             };
             token_map.insert(each.clone(), token);
         }
@@ -389,125 +342,88 @@ impl ModuleTraits for CryptoTransaction {
         state.update_confirmation_list(&self.from, &waiting_list);
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
-        true
+        Ok(())
     }
 
-    fn transfer_for_review(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 2;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::StringVal(String::default()));
-        expected_payload.push(DataTypes::StringVal(String::default()));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::StringVal(_a), DataTypes::StringVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let pkg_no: String = match self.payload.get(0).unwrap() {
-            DataTypes::StringVal(value) => value.clone(),
-            _ => return false,
-        };
-        let reviewer_address: String = match self.payload.get(1).unwrap() {
-            DataTypes::StringVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn transfer_for_review(
+        &self,
+        state_context: &mut dyn StateContext,
+        pkg_no: String,
+        reviewer_address: String,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
-            None => return false,
+            None => return Err(String::from("doc state not found")),
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         let pkg_doc_list: Vec<Hash> = match state.get_pkg_list(&pkg_no) {
             Some(list) => list.clone(),
-            None => return false,
+            None => return Err(format!("pkg {} not found", pkg_no)),
         };
         for each in pkg_doc_list.iter() {
             match state.get_nft_token(each.clone()) {
                 Some(token) => {
                     if token.status != DocStatus::Submitted {
-                        return false;
+                        return Err(format!("token {} isn't in submitted status", each.to_hex()));
                     }
                     if token.owner != self.from {
-                        return false;
+                        return Err(format!("token {} isn't owned by sender", each.to_hex()));
                     }
                 }
-                None => return false,
+                None => return Err(format!("token {} not found", each.to_hex())),
             };
         }
         state.add_pkg_no_for_review(&reviewer_address, &pkg_no);
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
-        true
+        Ok(())
     }
 
-    fn review_docs(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 2;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::StringVal(String::default()));
-        expected_payload.push(DataTypes::BoolVal(false));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::StringVal(_a), DataTypes::StringVal(_d)) => {}
-                (DataTypes::BoolVal(_a), DataTypes::BoolVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let pkg_no: String = match self.payload.get(0).unwrap() {
-            DataTypes::StringVal(value) => value.clone(),
-            _ => return false,
-        };
-        let response_bool: bool = match self.payload.get(1).unwrap() {
-            DataTypes::BoolVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn review_docs(
+        &self,
+        state_context: &mut dyn StateContext,
+        pkg_no: String,
+        response_bool: bool,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
-            None => return false,
+            None => return Err(String::from("doc state not found")),
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         match state.get_pkg_review_pending_list(&self.from) {
             Some(list) => {
                 if !list.contains(&pkg_no) {
-                    return false;
+                    return Err(format!("pkg {} isn't pending review of sender", pkg_no));
                 }
             }
-            None => return false,
+            None => return Err(String::from("no pkg pending review of sender")),
         }
         let pkg_doc_list: Vec<Hash> = match state.get_pkg_list(&pkg_no) {
             Some(list) => list.clone(),
-            None => return false,
+            None => return Err(format!("pkg {} not found", pkg_no)),
         };
         for each in pkg_doc_list.iter() {
             match state.get_nft_token(each.clone()) {
                 Some(token) => {
                     if token.status != DocStatus::Submitted {
-                        return false;
+                        return Err(format!("token {} isn't in submitted status", each.to_hex()));
                     }
                 }
-                None => return false,
+                None => return Err(format!("token {} not found", each.to_hex())),
             };
         }
         if response_bool {
@@ -526,55 +442,47 @@ impl ModuleTraits for CryptoTransaction {
         state.remove_pkg_no_from_review_list(&self.from, &pkg_no);
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
-        true
+        let topic: &str = if response_bool {
+            "approved"
+        } else {
+            "rejected"
+        };
+        emit_pkg_event(state_context, topic, &pkg_no);
+        Ok(())
     }
 
-    fn publish_docs(&self, state_context: &mut dyn StateContext) -> bool {
-        let expected_payload_size: usize = 1;
-        let mut expected_payload: Vec<DataTypes> = Vec::with_capacity(expected_payload_size);
-        expected_payload.push(DataTypes::StringVal(String::default()));
-        if self.payload.len() != expected_payload_size {
-            return false;
-        }
-        for index in 0..expected_payload_size {
-            let required_type = expected_payload.get(index).unwrap();
-            let given_type = self.payload.get(index).unwrap();
-            match (required_type, given_type) {
-                (DataTypes::StringVal(_a), DataTypes::StringVal(_d)) => {}
-                _ => return false,
-            };
-        }
-        let pkg_no: String = match self.payload.get(0).unwrap() {
-            DataTypes::StringVal(value) => value.clone(),
-            _ => return false,
-        };
+    fn publish_docs(
+        &self,
+        state_context: &mut dyn StateContext,
+        pkg_no: String,
+    ) -> Result<(), String> {
         let mut app_state: State = match state_context.get(&STATE_KEY.to_string()) {
             Some(state) => state,
-            None => return false,
+            None => return Err(String::from("doc state not found")),
         };
         let mut state: DocState = match deserialize(app_state.get_data().as_slice()) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
         };
         let pkg_doc_list: Vec<Hash> = match state.get_pkg_list(&pkg_no) {
             Some(list) => list.clone(),
-            None => return false,
+            None => return Err(format!("pkg {} not found", pkg_no)),
         };
         for each in pkg_doc_list.iter() {
             match state.get_nft_token(each.clone()) {
                 Some(token) => {
                     if token.status != DocStatus::Approved {
-                        return false;
+                        return Err(format!("token {} isn't in approved status", each.to_hex()));
                     }
                     if token.owner != self.from {
-                        return false;
+                        return Err(format!("token {} isn't owned by sender", each.to_hex()));
                     }
                 }
-                None => return false,
+                None => return Err(format!("token {} not found", each.to_hex())),
             };
         }
         for each in pkg_doc_list.iter() {
@@ -584,11 +492,26 @@ impl ModuleTraits for CryptoTransaction {
         }
         let serialized_state: Vec<u8> = match serialize(&state) {
             Result::Ok(value) => value,
-            Result::Err(_) => return false,
+            Result::Err(_) => return Err(String::from("doc state couldn't serialize")),
         };
         app_state.set_data(&serialized_state);
         state_context.put(&STATE_KEY.to_string(), app_state);
-        true
+        emit_pkg_event(state_context, "published", &pkg_no);
+        Ok(())
+    }
+}
+
+/// emit event of given topic carrying the serialized pkg_no
+fn emit_pkg_event(state_context: &mut dyn StateContext, topic: &str, pkg_no: &String) {
+    if let Ok(data) = serialize(pkg_no) {
+        state_context.emit_event(&String::from(topic), data);
+    }
+}
+
+fn query_param<'a>(params: &'a HashMap<String, String>, name: &str) -> Result<&'a String, String> {
+    match params.get(name) {
+        Some(value) => Ok(value),
+        None => Err(format!("{} param missing", name)),
     }
 }
 
@@ -604,20 +527,97 @@ impl CryptoApp {
 }
 
 impl AppHandler for CryptoApp {
-    fn execute(&self, txn: &SignedTransaction, state_context: &mut dyn StateContext) -> bool {
+    fn execute(
+        &self,
+        txn: &SignedTransaction,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
         let st = txn as &dyn StateTraits;
         st.execute(state_context)
     }
 
+    fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String> {
+        let st = txn as &dyn StateTraits;
+        st.check()
+    }
+
+    fn sender(&self, txn: &SignedTransaction) -> Option<String> {
+        match deserialize::<CryptoTransaction>(&txn.txn) {
+            Result::Ok(value) => Some(value.from),
+            Result::Err(_) => None,
+        }
+    }
+
+    fn nonce(&self, _txn: &SignedTransaction) -> Option<u64> {
+        None
+    }
+
+    fn next_nonce(&self, _sender: &String, _state_context: &dyn StateContext) -> Option<u64> {
+        None
+    }
+
+    fn fee(&self, _txn: &SignedTransaction) -> u64 {
+        0
+    }
+
+    /// supported paths are "token" (token_id param, hex), "pkg_docs" (pkg_no param)
+    /// and "pending_review" (reviewer param), response is serialized NFTToken,
+    /// Vec<Hash> & Vec<String> respectively
+    fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String> {
+        let state: DocState = match state_context.get(&STATE_KEY.to_string()) {
+            Some(app_state) => match deserialize(app_state.get_data().as_slice()) {
+                Result::Ok(value) => value,
+                Result::Err(_) => return Err(String::from("doc state couldn't deserialize")),
+            },
+            None => DocState::new(),
+        };
+        let response: Result<Vec<u8>, _> = match path.as_str() {
+            "token" => {
+                let token_id: Hash = match hex::decode(query_param(params, "token_id")?) {
+                    Result::Ok(bytes) => match Hash::from_slice(&bytes) {
+                        Some(hash) => hash,
+                        None => return Err(String::from("invalid token_id")),
+                    },
+                    Result::Err(_) => return Err(String::from("invalid token_id")),
+                };
+                match state.get_nft_token(token_id) {
+                    Some(token) => serialize(token),
+                    None => return Err(format!("token {} not found", token_id.to_hex())),
+                }
+            }
+            "pkg_docs" => match state.get_pkg_list(query_param(params, "pkg_no")?) {
+                Some(list) => serialize(list),
+                None => serialize(&Vec::<Hash>::new()),
+            },
+            "pending_review" => {
+                match state.get_pkg_review_pending_list(query_param(params, "reviewer")?) {
+                    Some(list) => serialize(list),
+                    None => serialize(&Vec::<String>::new()),
+                }
+            }
+            _ => return Err(format!("unknown query path {}", path)),
+        };
+        match response {
+            Result::Ok(value) => Ok(value),
+            Result::Err(_) => Err(String::from("query response couldn't serialize")),
+        }
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
 }
 
-#[no_mangle]
-pub fn register_app() -> Box<dyn AppHandler + Send> {
-    Box::new(CryptoApp::new(&String::from(APPNAME)))
-}
+sdk::export_app!(
+    APPNAME,
+    env!("CARGO_PKG_VERSION"),
+    CryptoApp::new(&String::from(APPNAME))
+);
 
 #[cfg(test)]
 mod test_state {
@@ -632,4 +632,22 @@ mod test_state {
         info!("{:?}", signed_transaction);
         info!("{:?}", signed_transaction.validate());
     }
+
+    #[test]
+    fn check_txn_admission() {
+        use super::*;
+        let kp = Keypair::generate();
+        let mut signed_transaction: SignedTransaction = SignedTransaction::generate(&kp);
+        let app = CryptoApp {
+            name: String::from(APPNAME),
+        };
+        assert_eq!(app.check_transaction(&signed_transaction), Ok(()));
+        signed_transaction.signature = vec![0];
+        assert_eq!(
+            app.check_transaction(&signed_transaction),
+            Err(String::from("transaction signature couldn't verified"))
+        );
+        signed_transaction.app_name = String::from("Cryptocurrency");
+        assert!(app.check_transaction(&signed_transaction).is_err());
+    }
 }
@@ -2,10 +2,11 @@ extern crate futures;
 
 use crate::cli_config::Configuration;
 use crate::doc_app_types::SignedTransaction;
-use crate::doc_app_types::{CryptoTransaction, DocState};
+use crate::doc_app_types::{CryptoTransaction, DocState, APPNAME};
 use awc::Client;
 use bytes::Bytes;
 use exonum_crypto::Hash;
+use sdk::namespace::namespaced_key;
 use sdk::state::State;
 use utils::crypto::keypair::{CryptoKeypair, Keypair, KeypairType};
 use utils::serializer::{deserialize, serialize};
@@ -100,7 +101,7 @@ impl ClientObj {
     pub async fn fetch_state(&self, public_address: &String) {
         let mut url: String = self.url.clone();
         url.extend("client/fetch_state".chars());
-        let serialized_body: Vec<u8> = match serialize(public_address) {
+        let serialized_body: Vec<u8> = match serialize(&namespaced_key(APPNAME, public_address)) {
             Result::Ok(value) => value,
             Result::Err(_) => vec![0],
         };
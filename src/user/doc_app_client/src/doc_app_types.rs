@@ -76,11 +76,15 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => return false,
         };
-        PublicKey::verify_from_encoded_pk(&txn.from, &self.txn, &self.signature.as_ref())
+        PublicKey::verify_from_encoded_pk(
+            &txn.from,
+            &self.signing_bytes(),
+            &self.signature.as_ref(),
+        )
     }
 
     fn sign(&self, kp: &KeypairType) -> Vec<u8> {
-        Keypair::sign(&kp, &self.txn)
+        Keypair::sign(&kp, &self.signing_bytes())
     }
 
     fn generate(kp: &KeypairType) -> SignedTransaction {
@@ -95,7 +99,6 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             fxn_call: String::from("set_hash"),
             payload,
         };
-        let txn_sign = txn.sign(&kp);
         let mut header = HashMap::default();
         let time_stamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -106,12 +109,14 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => vec![0],
         };
-        SignedTransaction {
+        let mut signed_txn = SignedTransaction {
             txn: serialized_txn,
             app_name: String::from(APPNAME),
-            signature: txn_sign,
+            signature: Vec::new(),
             header,
-        }
+        };
+        signed_txn.signature = signed_txn.sign(&kp);
+        signed_txn
     }
 
     fn get_hash(&self) -> Hash {
@@ -3,18 +3,23 @@ use super::state::CryptoState;
 pub use crate::user_messages::CryptoTransaction;
 use exonum_crypto::Hash;
 use exonum_merkledb::ObjectHash;
+use sdk::dispatch::NoPayload;
 pub use sdk::signed_transaction::SignedTransaction;
 use sdk::state::State;
-use sdk::traits::{AppHandler, StateContext};
+use sdk::traits::{AppHandler, BlockContext, ReadStateContext, StateContext};
+use std::collections::HashMap;
 use std::convert::AsRef;
 use utils::keypair::{CryptoKeypair, Keypair, KeypairType, PublicKey, Verify};
 use utils::logger::logger_init_from_yml;
 use utils::serializer::{deserialize, serialize};
 
 const APPNAME: &str = "Cryptocurrency";
+/// file in node directory listing initial wallet balances, one "<address> <amount>" per line
+const GENESIS_FILE: &str = "wallet_genesis.txt";
 
 trait StateTraits {
-    fn execute(&self, state_context: &mut dyn StateContext) -> bool;
+    fn execute(&self, state_context: &mut dyn StateContext) -> Result<(), String>;
+    fn check(&self) -> Result<(), String>;
 }
 
 pub trait TransactionTrait<T> {
@@ -49,11 +54,15 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => return false,
         };
-        PublicKey::verify_from_encoded_pk(&txn.from, &self.txn, &self.signature.as_ref())
+        PublicKey::verify_from_encoded_pk(
+            &txn.from,
+            &self.signing_bytes(),
+            &self.signature.as_ref(),
+        )
     }
 
     fn sign(&self, kp: &KeypairType) -> Vec<u8> {
-        Keypair::sign(&kp, &self.txn)
+        Keypair::sign(&kp, &self.signing_bytes())
     }
 
     fn get_hash(&self) -> Hash {
@@ -62,163 +71,534 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
 }
 
 impl StateTraits for SignedTransaction {
-    fn execute(&self, state_context: &mut dyn StateContext) -> bool {
-        let mut flag: bool = false;
+    fn execute(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        let mut result: Result<(), String> =
+            Err(String::from("transaction signature couldn't verified"));
         if self.validate() {
             let txn: CryptoTransaction = match deserialize(&self.txn) {
                 Result::Ok(value) => value,
-                Result::Err(_) => return false,
+                Result::Err(_) => return Err(String::from("transaction couldn't deserialize")),
             };
-            let crypto_txn = &txn.clone() as &dyn ModuleTraits;
-            if txn.fxn_call == String::from("transfer") {
-                flag = crypto_txn.transfer(state_context);
-            } else if txn.fxn_call == String::from("mint") {
-                flag = crypto_txn.mint(state_context);
-            } else {
+            result = match WalletCall::decode(&txn.fxn_call, &[]) {
+                Ok(call) => call.dispatch(&txn, state_context),
+                Err(reason) => Err(reason),
+            };
+        }
+        result
+    }
+    fn check(&self) -> Result<(), String> {
+        if self.app_name != APPNAME {
+            return Err(format!("transaction addressed to app {}", self.app_name));
+        }
+        let txn: CryptoTransaction = match deserialize(&self.txn) {
+            Result::Ok(value) => value,
+            Result::Err(_) => return Err(String::from("transaction couldn't deserialize")),
+        };
+        if !self.validate() {
+            return Err(String::from("transaction signature couldn't verified"));
+        }
+        match WalletCall::decode(&txn.fxn_call, &[])? {
+            WalletCall::Transfer {} if txn.from == txn.to => {
+                Err(String::from("self transfer transaction not allowed"))
             }
+            _ => Ok(()),
+        }
+    }
+}
+
+sdk::app_calls! {
+    /// typed calls of wallet app, arguments are plain fields of CryptoTransaction
+    pub enum WalletCall: NoPayload => pub trait ModuleTraits {
+        Transfer => fn transfer();
+        Mint => fn mint();
+    }
+}
+
+pub trait FeeTraits {
+    fn pay_fee(&self, state_context: &mut dyn StateContext) -> Result<(), String>;
+}
+
+impl CryptoTransaction {
+    /// emit event of given topic carrying the serialized txn
+    fn emit(&self, state_context: &mut dyn StateContext, topic: &str) {
+        if let Ok(data) = serialize(self) {
+            state_context.emit_event(&String::from(topic), data);
         }
-        state_context.put_txn(&self.get_hash(), self.clone());
-        flag
     }
 }
 
-pub trait ModuleTraits {
-    fn transfer(&self, state_context: &mut dyn StateContext) -> bool;
-    fn mint(&self, state_context: &mut dyn StateContext) -> bool;
+/// this function will read wallet of given address, None if the address has no wallet yet
+fn get_wallet(
+    state_context: &dyn StateContext,
+    address: &String,
+) -> Result<Option<(State, CryptoState)>, String> {
+    match state_context.get(address) {
+        Some(state) => match deserialize(state.get_data().as_slice()) {
+            Result::Ok(wallet) => Ok(Some((state, wallet))),
+            Result::Err(_) => Err(format!("wallet of {} couldn't deserialize", address)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// this function will write given wallet in its state under address
+fn put_wallet(
+    state_context: &mut dyn StateContext,
+    address: &String,
+    mut state: State,
+    wallet: &CryptoState,
+) -> Result<(), String> {
+    match serialize(wallet) {
+        Result::Ok(value) => state.set_data(&value),
+        Result::Err(_) => return Err(format!("wallet of {} couldn't serialize", address)),
+    };
+    state_context.put(address, state);
+    Ok(())
 }
 
 impl ModuleTraits for CryptoTransaction {
-    fn transfer(&self, state_context: &mut dyn StateContext) -> bool {
-        if self.validate() {
-            if self.from == self.to {
-                info!("self transfer transaction not allowed");
-                return false;
-            }
-            let mut from_state: State = match state_context.get(&self.from) {
-                Some(state) => state,
-                None => return false,
-            };
-            let mut from_wallet: CryptoState = match deserialize(from_state.get_data().as_slice()) {
-                Result::Ok(value) => value,
-                Result::Err(_) => return false,
-            };
-            if self.nonce != from_wallet.get_nonce() + 1 {
-                info!(
-                    "transfer txn nonce mismatched {:?} {:?}",
-                    self.nonce,
-                    from_wallet.get_nonce() + 1
-                );
-                return false;
-            }
-            if from_wallet.get_balance() >= self.amount {
-                let mut to_state: State = match state_context.get(&self.to) {
-                    Some(state) => state,
-                    None => {
-                        let crypto_state: CryptoState = CryptoState::new();
-                        let mut state: State = State::new();
-                        let serialized_crypto_state: Vec<u8> = match serialize(&crypto_state) {
-                            Result::Ok(value) => value,
-                            Result::Err(_) => return false,
-                        };
-                        state.set_data(&serialized_crypto_state);
-                        state
-                    }
-                };
-                let mut to_wallet: CryptoState = match deserialize(to_state.get_data().as_slice()) {
-                    Result::Ok(value) => value,
-                    Result::Err(_) => return false,
-                };
-                if !to_wallet.add_balance(self.amount) {
-                    return false;
-                }
-                let serialized_to_wallet: Vec<u8> = match serialize(&to_wallet) {
-                    Result::Ok(value) => value,
-                    Result::Err(_) => return false,
-                };
-                to_state.set_data(&serialized_to_wallet);
-
-                from_wallet.deduct_balance(self.amount);
-                from_wallet.increase_nonce();
-                let serialized_from_wallet: Vec<u8> = match serialize(&from_wallet) {
-                    Result::Ok(value) => value,
-                    Result::Err(_) => return false,
-                };
-                from_state.set_data(&serialized_from_wallet);
-
-                state_context.put(&self.to.clone(), to_state);
-                state_context.put(&self.from.clone(), from_state);
-                return true;
-            }
+    fn transfer(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        if self.from == self.to {
+            info!("self transfer transaction not allowed");
+            return Err(String::from("self transfer transaction not allowed"));
+        }
+        let from_wallet: CryptoState = match get_wallet(state_context, &self.from)? {
+            Some((_, wallet)) => wallet,
+            None => return Err(format!("wallet of {} not found", self.from)),
+        };
+        if self.nonce != from_wallet.get_nonce() + 1 {
+            info!(
+                "transfer txn nonce mismatched {:?} {:?}",
+                self.nonce,
+                from_wallet.get_nonce() + 1
+            );
+            return Err(format!(
+                "nonce {} mismatched, expected {}",
+                self.nonce,
+                from_wallet.get_nonce() + 1
+            ));
+        }
+        // sender must be able to pay the fee on top of the amount
+        let total_amount: u64 = match self.amount.checked_add(self.fee) {
+            Some(total_amount) => total_amount,
+            None => return Err(String::from("amount and fee overflowed")),
+        };
+        if from_wallet.get_balance() < total_amount {
+            return Err(String::from("balance couldn't cover amount and fee"));
+        }
+        // fee is charged before any balance moves, wallets are read again after it
+        // as the block producer may be either side of the transfer
+        self.pay_fee(state_context)?;
+        let (from_state, mut from_wallet) = match get_wallet(state_context, &self.from)? {
+            Some(wallet) => wallet,
+            None => return Err(format!("wallet of {} not found", self.from)),
+        };
+        let (to_state, mut to_wallet) = get_wallet(state_context, &self.to)?
+            .unwrap_or_else(|| (State::new(), CryptoState::new()));
+        if !to_wallet.add_balance(self.amount) {
+            return Err(format!("balance of {} overflowed", self.to));
         }
-        false
+        from_wallet.deduct_balance(self.amount);
+        from_wallet.increase_nonce();
+        put_wallet(state_context, &self.to, to_state, &to_wallet)?;
+        put_wallet(state_context, &self.from, from_state, &from_wallet)?;
+        self.emit(state_context, "transfer");
+        Ok(())
     }
 
-    fn mint(&self, state_context: &mut dyn StateContext) -> bool {
-        if self.validate() {
-            let mut state: State = match state_context.get(&self.from) {
-                Some(state) => state,
-                None => {
-                    let crypto_state: CryptoState = CryptoState::new();
-                    let mut state: State = State::new();
-                    let serialized_crypto_state: Vec<u8> = match serialize(&crypto_state) {
-                        Result::Ok(value) => value,
-                        Result::Err(_) => return false,
-                    };
-                    state.set_data(&serialized_crypto_state);
-                    state
-                }
-            };
-            let mut wallet: CryptoState = match deserialize(state.get_data().as_slice()) {
-                Result::Ok(value) => value,
-                Result::Err(_) => return false,
-            };
-            if self.nonce != wallet.get_nonce() + 1 {
-                info!(
-                    "transfer txn nonce mismatched {:?} {:?}",
-                    self.nonce,
-                    wallet.get_nonce() + 1
-                );
-                return false;
-            }
-            if !wallet.add_balance(self.amount) {
-                return false;
-            }
-            wallet.increase_nonce();
-            let serialized_wallet: Vec<u8> = match serialize(&wallet) {
-                Result::Ok(value) => value,
-                Result::Err(_) => return false,
-            };
-            state.set_data(&serialized_wallet);
-            state_context.put(&self.from.clone(), state);
-            return true;
+    fn mint(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        let (state, mut wallet) = get_wallet(state_context, &self.from)?
+            .unwrap_or_else(|| (State::new(), CryptoState::new()));
+        if self.nonce != wallet.get_nonce() + 1 {
+            info!(
+                "transfer txn nonce mismatched {:?} {:?}",
+                self.nonce,
+                wallet.get_nonce() + 1
+            );
+            return Err(format!(
+                "nonce {} mismatched, expected {}",
+                self.nonce,
+                wallet.get_nonce() + 1
+            ));
+        }
+        if !wallet.add_balance(self.amount) {
+            return Err(format!("balance of {} overflowed", self.from));
+        }
+        if wallet.get_balance() < self.fee {
+            info!("mint txn balance couldn't cover the fee");
+            return Err(String::from("balance couldn't cover the fee"));
+        }
+        wallet.increase_nonce();
+        put_wallet(state_context, &self.from, state, &wallet)?;
+        // fee comes out of the minted amount, it is validated above before any write
+        self.pay_fee(state_context)?;
+        self.emit(state_context, "mint");
+        Ok(())
+    }
+}
+
+impl FeeTraits for CryptoTransaction {
+    fn pay_fee(&self, state_context: &mut dyn StateContext) -> Result<(), String> {
+        // fee is only charged while executing a block, it goes to the block producer
+        let block_producer: String = match state_context.get_block_producer() {
+            Some(block_producer) => block_producer,
+            None => return Ok(()),
+        };
+        if self.fee == 0 {
+            return Ok(());
+        }
+        let (from_state, mut from_wallet) = match get_wallet(state_context, &self.from)? {
+            Some(wallet) => wallet,
+            None => return Err(format!("wallet of {} not found", self.from)),
+        };
+        if from_wallet.get_balance() < self.fee {
+            return Err(String::from("balance couldn't cover the fee"));
+        }
+        from_wallet.deduct_balance(self.fee);
+        put_wallet(state_context, &self.from, from_state, &from_wallet)?;
+
+        let (producer_state, mut producer_wallet) = get_wallet(state_context, &block_producer)?
+            .unwrap_or_else(|| (State::new(), CryptoState::new()));
+        if !producer_wallet.add_balance(self.fee) {
+            return Err(format!("balance of {} overflowed", block_producer));
         }
-        false
+        put_wallet(
+            state_context,
+            &block_producer,
+            producer_state,
+            &producer_wallet,
+        )
     }
 }
 
+/// this function will read genesis allocation from given file, a missing file means no
+/// allocation. Err is returned if the file can't be read or is malformed.
+fn read_genesis_allocation(genesis_file: &str) -> Result<Vec<(String, u64)>, String> {
+    match std::fs::read_to_string(genesis_file) {
+        Ok(content) => parse_genesis_allocation(&content)
+            .map_err(|reason| format!("{} malformed: {}", genesis_file, reason)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => Err(format!("{} couldn't read: {}", genesis_file, error)),
+    }
+}
+
+/// this function will parse genesis allocation, blank lines & lines starting with # are skipped
+fn parse_genesis_allocation(content: &str) -> Result<Vec<(String, u64)>, String> {
+    let mut allocation: Vec<(String, u64)> = Vec::new();
+    for (line_no, line) in content.lines().enumerate() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 2 {
+            return Err(format!("genesis allocation line {} malformed", line_no + 1));
+        }
+        let amount: u64 = match fields[1].parse::<u64>() {
+            Ok(amount) => amount,
+            Err(_) => {
+                return Err(format!(
+                    "genesis allocation line {} amount invalid",
+                    line_no + 1
+                ))
+            }
+        };
+        allocation.push((fields[0].to_string(), amount));
+    }
+    Ok(allocation)
+}
+
 pub struct CryptoApp {
     name: String,
+    genesis_file: String,
 }
 
 impl CryptoApp {
     pub fn new(s: &String) -> CryptoApp {
         logger_init_from_yml("log.yml");
-        CryptoApp { name: s.clone() }
+        CryptoApp {
+            name: s.clone(),
+            genesis_file: String::from(GENESIS_FILE),
+        }
     }
 }
 
 impl AppHandler for CryptoApp {
-    fn execute(&self, txn: &SignedTransaction, state_context: &mut dyn StateContext) -> bool {
+    fn execute(
+        &self,
+        txn: &SignedTransaction,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
         let st = txn as &dyn StateTraits;
         st.execute(state_context)
     }
 
+    fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String> {
+        let st = txn as &dyn StateTraits;
+        st.check()
+    }
+
+    fn sender(&self, txn: &SignedTransaction) -> Option<String> {
+        match deserialize::<CryptoTransaction>(&txn.txn) {
+            Result::Ok(value) => Some(value.from),
+            Result::Err(_) => None,
+        }
+    }
+
+    fn nonce(&self, txn: &SignedTransaction) -> Option<u64> {
+        match deserialize::<CryptoTransaction>(&txn.txn) {
+            Result::Ok(value) => Some(value.nonce),
+            Result::Err(_) => None,
+        }
+    }
+
+    fn next_nonce(&self, sender: &String, state_context: &dyn StateContext) -> Option<u64> {
+        match state_context.get(sender) {
+            Some(state) => match deserialize::<CryptoState>(state.get_data().as_slice()) {
+                Result::Ok(wallet) => Some(wallet.get_nonce() + 1),
+                Result::Err(_) => None,
+            },
+            None => Some(1),
+        }
+    }
+
+    fn fee(&self, txn: &SignedTransaction) -> u64 {
+        match deserialize::<CryptoTransaction>(&txn.txn) {
+            Result::Ok(value) => value.fee,
+            Result::Err(_) => 0,
+        }
+    }
+
+    /// supported paths are "balance" & "nonce" of account given in "address" param,
+    /// response is serialized u64 and unknown accounts are reported as empty
+    fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String> {
+        let address: &String = match params.get("address") {
+            Some(address) => address,
+            None => return Err(String::from("address param missing")),
+        };
+        let wallet: CryptoState = match state_context.get(address) {
+            Some(state) => match deserialize(state.get_data().as_slice()) {
+                Result::Ok(value) => value,
+                Result::Err(_) => return Err(String::from("wallet state couldn't deserialize")),
+            },
+            None => CryptoState::new(),
+        };
+        let response: u64 = match path.as_str() {
+            "balance" => wallet.get_balance(),
+            "nonce" => wallet.get_nonce(),
+            _ => return Err(format!("unknown query path {}", path)),
+        };
+        match serialize(&response) {
+            Result::Ok(value) => Ok(value),
+            Result::Err(_) => Err(String::from("query response couldn't serialize")),
+        }
+    }
+
+    /// credits genesis allocation of the genesis file to the listed wallets, an unreadable
+    /// or malformed file fails the hook
+    fn init_genesis(
+        &self,
+        _block: &BlockContext,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        let genesis_allocation: Vec<(String, u64)> = read_genesis_allocation(&self.genesis_file)?;
+        for (address, amount) in genesis_allocation.iter() {
+            let mut state: State = state_context.get(address).unwrap_or_else(State::new);
+            let mut wallet: CryptoState = match deserialize(state.get_data().as_slice()) {
+                Result::Ok(value) => value,
+                Result::Err(_) => CryptoState::new(),
+            };
+            if !wallet.add_balance(*amount) {
+                return Err(format!("genesis allocation of {} overflowed", address));
+            }
+            match serialize(&wallet) {
+                Result::Ok(value) => state.set_data(&value),
+                Result::Err(_) => return Err(String::from("wallet state couldn't serialize")),
+            };
+            state_context.put(address, state);
+        }
+        Ok(())
+    }
+
     fn name(&self) -> String {
         self.name.clone()
     }
 }
 
-#[no_mangle]
-pub fn register_app() -> Box<dyn AppHandler + Send> {
-    Box::new(CryptoApp::new(&String::from(APPNAME)))
+sdk::export_app!(
+    APPNAME,
+    env!("CARGO_PKG_VERSION"),
+    CryptoApp::new(&String::from(APPNAME))
+);
+
+#[cfg(test)]
+mod test_transaction {
+    use super::*;
+    use sdk::test_utils::MemoryStateContext;
+
+    fn get_balance(state_context: &MemoryStateContext, key: &String) -> u64 {
+        let state: State = state_context.get(key).unwrap();
+        let wallet: CryptoState = deserialize(state.get_data().as_slice()).unwrap();
+        wallet.get_balance()
+    }
+
+    fn sign_transaction(kp: &KeypairType, txn: &CryptoTransaction) -> SignedTransaction {
+        let mut signed_txn = SignedTransaction {
+            txn: serialize(txn).unwrap(),
+            app_name: String::from(APPNAME),
+            header: HashMap::default(),
+            signature: Vec::new(),
+        };
+        signed_txn.signature = signed_txn.sign(kp);
+        signed_txn
+    }
+
+    #[test]
+    fn test_fee_paid_to_block_producer() {
+        let kp = Keypair::generate();
+        let from: String = hex::encode(kp.public().encode());
+        let to: String = String::from("receiver");
+        let block_producer: String = String::from("block_producer");
+        let mut state_context = MemoryStateContext::with_block_producer(&block_producer);
+        let app = CryptoApp {
+            name: String::from(APPNAME),
+            genesis_file: String::from(GENESIS_FILE),
+        };
+        let mut txn: CryptoTransaction = CryptoTransaction {
+            nonce: 1,
+            from: from.clone(),
+            to: String::default(),
+            fxn_call: String::from("mint"),
+            amount: 100,
+            fee: 10,
+        };
+        let signed_txn: SignedTransaction = sign_transaction(&kp, &txn);
+        assert_eq!(app.fee(&signed_txn), 10);
+        assert_eq!(app.execute(&signed_txn, &mut state_context), Ok(()));
+        assert_eq!(get_balance(&state_context, &from), 90);
+        assert_eq!(get_balance(&state_context, &block_producer), 10);
+
+        // amount & fee together couldn't be covered
+        txn.nonce = 2;
+        txn.to = to.clone();
+        txn.fxn_call = String::from("transfer");
+        txn.amount = 85;
+        let signed_txn: SignedTransaction = sign_transaction(&kp, &txn);
+        assert_eq!(
+            app.execute(&signed_txn, &mut state_context),
+            Err(String::from("balance couldn't cover amount and fee"))
+        );
+        assert_eq!(get_balance(&state_context, &from), 90);
+
+        txn.amount = 80;
+        let signed_txn: SignedTransaction = sign_transaction(&kp, &txn);
+        assert_eq!(app.execute(&signed_txn, &mut state_context), Ok(()));
+        assert_eq!(get_balance(&state_context, &from), 0);
+        assert_eq!(get_balance(&state_context, &to), 80);
+        assert_eq!(get_balance(&state_context, &block_producer), 20);
+        // failed transfer emitted nothing
+        assert_eq!(state_context.event_topics(), vec!["mint", "transfer"]);
+        let transfer: CryptoTransaction = deserialize(&state_context.events[1].1).unwrap();
+        assert_eq!(transfer, txn);
+
+        // block producer receiving the transfer gets both the fee and the amount
+        txn.nonce = 3;
+        txn.fxn_call = String::from("mint");
+        txn.amount = 50;
+        let signed_txn: SignedTransaction = sign_transaction(&kp, &txn);
+        assert_eq!(app.execute(&signed_txn, &mut state_context), Ok(()));
+        txn.nonce = 4;
+        txn.to = block_producer.clone();
+        txn.fxn_call = String::from("transfer");
+        txn.amount = 30;
+        let signed_txn: SignedTransaction = sign_transaction(&kp, &txn);
+        assert_eq!(app.execute(&signed_txn, &mut state_context), Ok(()));
+        assert_eq!(get_balance(&state_context, &from), 0);
+        assert_eq!(get_balance(&state_context, &block_producer), 70);
+    }
+
+    #[test]
+    fn test_wallet_query() {
+        let kp = Keypair::generate();
+        let from: String = hex::encode(kp.public().encode());
+        let mut state_context = MemoryStateContext::new();
+        let app = CryptoApp {
+            name: String::from(APPNAME),
+            genesis_file: String::from(GENESIS_FILE),
+        };
+        let txn: CryptoTransaction = CryptoTransaction {
+            nonce: 1,
+            from: from.clone(),
+            to: String::default(),
+            fxn_call: String::from("mint"),
+            amount: 100,
+            fee: 0,
+        };
+        let signed_txn: SignedTransaction = sign_transaction(&kp, &txn);
+        assert_eq!(app.execute(&signed_txn, &mut state_context), Ok(()));
+
+        let mut params: HashMap<String, String> = HashMap::new();
+        params.insert(String::from("address"), from);
+        let read_context = state_context.read_only();
+        let balance: Vec<u8> = app
+            .query(&String::from("balance"), &params, &read_context)
+            .unwrap();
+        assert_eq!(deserialize::<u64>(&balance).unwrap(), 100);
+        assert!(app
+            .query(&String::from("storage"), &params, &read_context)
+            .is_err());
+        params.insert(String::from("address"), String::from("unknown"));
+        let balance: Vec<u8> = app
+            .query(&String::from("balance"), &params, &read_context)
+            .unwrap();
+        assert_eq!(deserialize::<u64>(&balance).unwrap(), 0);
+        assert!(app
+            .query(&String::from("balance"), &HashMap::new(), &read_context)
+            .is_err());
+    }
+
+    #[test]
+    fn test_genesis_allocation() {
+        let content: &str = "# initial balances\nalice 100\n\nbob 50\nalice 5\n";
+        let allocation: Vec<(String, u64)> = parse_genesis_allocation(content).unwrap();
+        assert_eq!(allocation.len(), 3);
+        assert!(parse_genesis_allocation("alice").is_err());
+        assert!(parse_genesis_allocation("alice ten").is_err());
+
+        let genesis_file =
+            std::env::temp_dir().join(format!("wallet_genesis_{}.txt", std::process::id()));
+        std::fs::write(&genesis_file, content).unwrap();
+        let mut state_context = MemoryStateContext::new();
+        let mut app = CryptoApp {
+            name: String::from(APPNAME),
+            genesis_file: genesis_file.to_str().unwrap().to_string(),
+        };
+        let block = BlockContext {
+            height: 0,
+            timestamp: 0,
+            proposer: String::from("genesis_block"),
+        };
+        assert_eq!(app.init_genesis(&block, &mut state_context), Ok(()));
+        assert_eq!(get_balance(&state_context, &String::from("alice")), 105);
+        assert_eq!(get_balance(&state_context, &String::from("bob")), 50);
+
+        // malformed genesis file fails the hook instead of the app registration
+        std::fs::write(&genesis_file, "alice ten\n").unwrap();
+        let result: Result<(), String> = app.init_genesis(&block, &mut MemoryStateContext::new());
+        std::fs::remove_file(&genesis_file).unwrap();
+        assert!(result
+            .unwrap_err()
+            .ends_with("malformed: genesis allocation line 1 amount invalid"));
+
+        // missing genesis file means no allocation
+        app.genesis_file = String::from("missing_wallet_genesis.txt");
+        let mut state_context = MemoryStateContext::new();
+        assert_eq!(app.init_genesis(&block, &mut state_context), Ok(()));
+        assert!(state_context.get(&String::from("alice")).is_none());
+    }
 }
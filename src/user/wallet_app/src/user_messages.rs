@@ -12,6 +12,8 @@ pub struct CryptoTransaction {
     pub fxn_call: std::string::String,
     #[prost(uint64, tag = "5")]
     pub amount: u64,
+    #[prost(uint64, tag = "6")]
+    pub fee: u64,
 }
 //// Auto-generated using build.rs and proto files. Don't edit by hand. ////
 #[derive(Clone, PartialEq, Serialize, Deserialize, ::prost::Message, BinaryValue, ObjectHash)]
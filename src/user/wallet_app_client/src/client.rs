@@ -1,10 +1,12 @@
 extern crate futures;
 
 use crate::cli_config::Configuration;
-use crate::wallet_app_types::{CryptoState, CryptoTransaction, SignedTransaction};
+use crate::wallet_app_types::{CryptoState, CryptoTransaction, SignedTransaction, APPNAME};
 use awc::Client;
 use bytes::Bytes;
 use exonum_crypto::Hash;
+use sdk::namespace::namespaced_key;
+use sdk::receipt::Receipt;
 use sdk::state::State;
 use utils::crypto::keypair::{CryptoKeypair, Keypair, KeypairType};
 use utils::serializer::{deserialize, serialize};
@@ -99,7 +101,7 @@ impl ClientObj {
     pub async fn fetch_state(&self, public_address: &String) {
         let mut url: String = self.url.clone();
         url.extend("client/fetch_state".chars());
-        let serialized_body: Vec<u8> = match serialize(public_address) {
+        let serialized_body: Vec<u8> = match serialize(&namespaced_key(APPNAME, public_address)) {
             Result::Ok(value) => value,
             Result::Err(_) => vec![0],
         };
@@ -220,6 +222,42 @@ impl ClientObj {
         }
     }
 
+    // request to peer to fetch execution receipt of confirmed transaction
+    pub async fn fetch_transaction_receipt(&self, txn_hash: &Hash) {
+        let mut url: String = self.url.clone();
+        url.extend("client/fetch_transaction_receipt".chars());
+        let serialized_body: Vec<u8> = match serialize(txn_hash) {
+            Result::Ok(value) => value,
+            Result::Err(_) => vec![0],
+        };
+        let result = self
+            .client
+            .get(url) // <- Create request builder
+            .header("User-Agent", "Actix-web")
+            .send_body(Bytes::from(serialized_body))
+            .await
+            .map_err(|_| ());
+        match result {
+            Ok(mut response) => {
+                let resp_body = response.body();
+                info!("fetch_transaction_receipt Status: {:?}", response.status());
+                if response.status() == 200 {
+                    match resp_body.await {
+                        Ok(receipt_body) => {
+                            if let Ok(receipt) = deserialize::<Receipt>(&receipt_body) {
+                                info!("{:#?}", receipt);
+                            } else {
+                                info!("receipt couldn't deserialize");
+                            }
+                        }
+                        Err(e) => error!("Error body: {:?}", e),
+                    }
+                }
+            }
+            Err(e) => error!("Error response: {:?}", e),
+        }
+    }
+
     // request for fetching latest block
     pub async fn fetch_latest_block(&self) {
         let mut url: String = self.url.clone();
@@ -257,7 +295,7 @@ impl ClientObj {
         let public_key: &String = &hex::encode(self.keypair.public().encode());
         let mut url: String = self.url.clone();
         url.extend("client/fetch_state".chars());
-        let serialized_body: Vec<u8> = match serialize(public_key) {
+        let serialized_body: Vec<u8> = match serialize(&namespaced_key(APPNAME, public_key)) {
             Result::Ok(value) => value,
             Result::Err(_) => vec![0],
         };
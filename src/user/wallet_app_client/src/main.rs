@@ -161,7 +161,12 @@ pub fn create_transaction(kp: &KeypairType, nonce: u64) -> Option<SignedTransact
         Some(amount) => crypto_transaction.amount = amount,
         None => return None,
     };
-    let txn_sign = crypto_transaction.sign(&kp);
+    info!("Please enter fee:");
+    let is_fee: Option<u64> = get_integer_input(&mut input);
+    match is_fee {
+        Some(fee) => crypto_transaction.fee = fee,
+        None => return None,
+    };
     let mut header = HashMap::default();
     let time_stamp = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -172,12 +177,14 @@ pub fn create_transaction(kp: &KeypairType, nonce: u64) -> Option<SignedTransact
         Result::Ok(value) => value,
         Result::Err(_) => vec![0],
     };
-    Some(SignedTransaction {
+    let mut signed_txn = SignedTransaction {
         txn: serialized_txn,
         app_name: String::from(APPNAME),
-        signature: txn_sign,
+        signature: Vec::new(),
         header,
-    })
+    };
+    signed_txn.signature = signed_txn.sign(&kp);
+    Some(signed_txn)
 }
 
 //this attribute allows main to not need to return anything and still use async calls.
@@ -224,7 +231,8 @@ async fn main() {
         info!("4:) fetch state details");
         info!("5:) fetch block");
         info!("6:) fetch latest block");
-        info!("7:) exit");
+        info!("7:) fetch transaction receipt");
+        info!("8:) exit");
         let mut input = String::new();
         info!("Please select Option:");
         let is_string: bool = get_string_input(&mut input);
@@ -285,6 +293,14 @@ async fn main() {
                 client.fetch_latest_block().await;
                 invalid_opt_count = 0;
             } else if input == String::from("7") {
+                invalid_opt_count = 0;
+                info!("Enter transaction Hash");
+                let is_hash: Option<Hash> = get_hash_input(&mut input);
+                match is_hash {
+                    Some(txn_hash) => client.fetch_transaction_receipt(&txn_hash).await,
+                    None => error!("error: invalid input for transaction hash"),
+                }
+            } else if input == String::from("8") {
                 end_flag = true;
             } else {
                 info!("invalid option");
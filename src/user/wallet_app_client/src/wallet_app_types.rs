@@ -7,7 +7,7 @@ use std::convert::AsRef;
 use std::time::SystemTime;
 use utils::keypair::{CryptoKeypair, Keypair, KeypairType, PublicKey, Verify};
 use utils::serializer::{deserialize, serialize, Deserialize, Serialize};
-const APPNAME: &str = "Cryptocurrency";
+pub const APPNAME: &str = "Cryptocurrency";
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, BinaryValue, ObjectHash)]
 #[binary_value(codec = "bincode")]
@@ -26,6 +26,7 @@ pub struct CryptoTransaction {
     pub to: std::string::String,
     pub fxn_call: std::string::String,
     pub amount: u64,
+    pub fee: u64,
 }
 
 pub trait TransactionTrait<T> {
@@ -61,6 +62,7 @@ impl TransactionTrait<CryptoTransaction> for CryptoTransaction {
             to,
             amount: 32,
             fxn_call: String::from("transfer"),
+            fee: 0,
         }
     }
 
@@ -75,11 +77,15 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => return false,
         };
-        PublicKey::verify_from_encoded_pk(&txn.from, &self.txn, &self.signature.as_ref())
+        PublicKey::verify_from_encoded_pk(
+            &txn.from,
+            &self.signing_bytes(),
+            &self.signature.as_ref(),
+        )
     }
 
     fn sign(&self, kp: &KeypairType) -> Vec<u8> {
-        Keypair::sign(&kp, &self.txn)
+        Keypair::sign(&kp, &self.signing_bytes())
     }
 
     fn generate(kp: &KeypairType) -> SignedTransaction {
@@ -92,8 +98,8 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             to,
             amount: 32,
             fxn_call: String::from("transfer"),
+            fee: 0,
         };
-        let txn_sign = txn.sign(&kp);
         let mut header = HashMap::default();
         let time_stamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -104,12 +110,14 @@ impl TransactionTrait<SignedTransaction> for SignedTransaction {
             Result::Ok(value) => value,
             Result::Err(_) => vec![0],
         };
-        SignedTransaction {
+        let mut signed_txn = SignedTransaction {
             txn: serialized_txn,
             app_name: String::from(APPNAME),
-            signature: txn_sign,
+            signature: Vec::new(),
             header,
-        }
+        };
+        signed_txn.signature = signed_txn.sign(&kp);
+        signed_txn
     }
 
     fn get_hash(&self) -> Hash {
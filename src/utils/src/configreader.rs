@@ -1,6 +1,7 @@
 use super::*;
 use crypto::keypair::{CryptoKeypair, Keypair};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::Read;
@@ -37,6 +38,18 @@ pub enum NODETYPE {
     Validator,
 }
 
+#[derive(Debug)]
+pub enum DBTYPE {
+    RocksDB,
+    TemporaryDB,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EVICTIONPOLICY {
+    Oldest,
+    LowestPriority,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TomlReaderConfig {
     pub public: String,
@@ -47,16 +60,38 @@ pub struct TomlReaderConfig {
     p2p_port: u16,
     //db config
     dbpath: String,
+    db_type: String,
     //client config
     client_port: u32,
     client_host: String,
     client_apps: Vec<String>,
+    // .wasm app path => hex of its expected code hash
+    #[serde(default)]
+    client_app_hashes: HashMap<String, String>,
     //block config
     block_creation_time_limit: u64,
     block_transaction_limit: u64,
     transaction_execution_delay_limit: u64,
+    //execution metering config
+    txn_state_read_limit: u64,
+    txn_state_write_limit: u64,
+    txn_state_bytes_limit: u64,
+    block_state_read_limit: u64,
+    block_state_write_limit: u64,
+    block_state_bytes_limit: u64,
+    //txn pool config
+    pool_max_size: usize,
+    pool_max_txns_per_sender: usize,
+    pool_max_txns_per_app: usize,
+    pool_max_bytes: usize,
+    pool_eviction_policy: String,
+    pool_expiry_sweep_interval: u64,
+    pool_max_timestamp_drift: u64,
     // consensus name
     consensus_name: String,
+    // bearer token of admin api, empty disables it
+    #[serde(default)]
+    admin_token: String,
 }
 
 #[derive(Debug)]
@@ -70,6 +105,9 @@ pub struct Configuration {
 
     // block creation config
     pub block_config: BlockConfig,
+
+    // txn pool limits config
+    pub pool_config: PoolConfig,
 }
 
 impl Configuration {
@@ -96,10 +134,19 @@ impl Configuration {
             client_host: tomlreader.client_host,
             client_port: tomlreader.client_port,
             client_apps: tomlreader.client_apps.to_vec(),
+            client_app_hashes: tomlreader.client_app_hashes,
             consensus_name: tomlreader.consensus_name,
+            admin_token: tomlreader.admin_token,
         };
+        let mut db_type: DBTYPE = DBTYPE::RocksDB;
+        if tomlreader.db_type.to_ascii_lowercase() == "temporarydb" {
+            db_type = DBTYPE::TemporaryDB
+        } else if tomlreader.db_type.to_ascii_lowercase() != "rocksdb" {
+            panic!("db type not defined properly");
+        }
         let db_path: Database = Database {
             dbpath: tomlreader.dbpath,
+            db_type,
         };
         let delay_in_micros: u128 = 1000 * tomlreader.transaction_execution_delay_limit as u128;
         let time_limit_for_block: u128 = 1000 * tomlreader.block_creation_time_limit as u128;
@@ -107,11 +154,33 @@ impl Configuration {
             block_creation_time_limit: time_limit_for_block,
             block_transaction_limit: tomlreader.block_transaction_limit,
             transaction_execution_delay_limit: delay_in_micros,
+            txn_state_read_limit: tomlreader.txn_state_read_limit,
+            txn_state_write_limit: tomlreader.txn_state_write_limit,
+            txn_state_bytes_limit: tomlreader.txn_state_bytes_limit,
+            block_state_read_limit: tomlreader.block_state_read_limit,
+            block_state_write_limit: tomlreader.block_state_write_limit,
+            block_state_bytes_limit: tomlreader.block_state_bytes_limit,
+        };
+        let mut eviction_policy: EVICTIONPOLICY = EVICTIONPOLICY::Oldest;
+        if tomlreader.pool_eviction_policy.to_ascii_lowercase() == "lowest_priority" {
+            eviction_policy = EVICTIONPOLICY::LowestPriority
+        } else if tomlreader.pool_eviction_policy.to_ascii_lowercase() != "oldest" {
+            panic!("pool eviction policy not defined properly");
+        }
+        let pool_config: PoolConfig = PoolConfig {
+            max_pool_size: tomlreader.pool_max_size,
+            max_txns_per_sender: tomlreader.pool_max_txns_per_sender,
+            max_txns_per_app: tomlreader.pool_max_txns_per_app,
+            max_pool_bytes: tomlreader.pool_max_bytes,
+            eviction_policy,
+            expiry_sweep_interval: tomlreader.pool_expiry_sweep_interval,
+            max_timestamp_drift: 1000 * tomlreader.pool_max_timestamp_drift as u128,
         };
         let conf_obj = Configuration {
             node: node_obj,
             db: db_path,
             block_config,
+            pool_config,
         };
         conf_obj
     }
@@ -155,12 +224,15 @@ pub struct Node {
     pub client_host: String,
     pub client_port: u32,
     pub client_apps: Vec<String>,
+    pub client_app_hashes: HashMap<String, String>,
     pub consensus_name: String,
+    pub admin_token: String,
 }
 
 #[derive(Debug)]
 pub struct Database {
     pub dbpath: String,
+    pub db_type: DBTYPE,
 }
 
 #[derive(Debug)]
@@ -168,6 +240,37 @@ pub struct BlockConfig {
     pub block_creation_time_limit: u128,         // in micro seconds
     pub block_transaction_limit: u64,            // max transaction count in a block
     pub transaction_execution_delay_limit: u128, // in micro seconds
+    pub txn_state_read_limit: u64,               // max state reads of a transaction
+    pub txn_state_write_limit: u64,              // max state writes of a transaction
+    pub txn_state_bytes_limit: u64,              // max state bytes read & written by a transaction
+    pub block_state_read_limit: u64,             // max state reads of all transactions in a block
+    pub block_state_write_limit: u64,            // max state writes of all transactions in a block
+    pub block_state_bytes_limit: u64,            // max state bytes of all transactions in a block
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_pool_size: usize,            // max transaction count in the pool
+    pub max_txns_per_sender: usize,      // max pending transaction count of a sender
+    pub max_txns_per_app: usize,         // max pending count of senderless txns of an app
+    pub max_pool_bytes: usize,           // max serialized size of pooled transactions
+    pub eviction_policy: EVICTIONPOLICY, // which transaction leaves first when pool is full
+    pub expiry_sweep_interval: u64,      // in milli seconds
+    pub max_timestamp_drift: u128,       // in micro seconds, max lead of timestamp header
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            max_pool_size: 10000,
+            max_txns_per_sender: 100,
+            max_txns_per_app: 1000,
+            max_pool_bytes: 64 * 1024 * 1024,
+            eviction_policy: EVICTIONPOLICY::Oldest,
+            expiry_sweep_interval: 10000,
+            max_timestamp_drift: 5000000,
+        }
+    }
 }
 
 pub fn initialize_config(file_path: &str) {
@@ -0,0 +1,105 @@
+use super::host::{GAS_FUNCTION, HOST_MODULE};
+use parity_wasm::builder;
+use parity_wasm::elements::{ImportCountType, Instruction, Internal, Module, Section, ValueType};
+
+/// true for instructions after which execution may continue elsewhere than the next
+/// instruction, or which start a new block. They close a metered segment.
+fn ends_segment(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Block(_)
+            | Instruction::Loop(_)
+            | Instruction::If(_)
+            | Instruction::Else
+            | Instruction::End
+            | Instruction::Br(_)
+            | Instruction::BrIf(_)
+            | Instruction::BrTable(_)
+            | Instruction::Return
+    )
+}
+
+/// this function will charge every straight-line segment of a function body up front,
+/// one unit of gas per instruction. A loop body starts a segment, so every iteration pays.
+fn meter_body(instructions: &[Instruction], gas_function: u32) -> Vec<Instruction> {
+    let mut metered: Vec<Instruction> = Vec::with_capacity(instructions.len() * 2);
+    let mut start: usize = 0;
+    while start < instructions.len() {
+        let end: usize = match instructions[start..].iter().position(ends_segment) {
+            Some(position) => start + position + 1,
+            None => instructions.len(),
+        };
+        metered.push(Instruction::I32Const((end - start) as i32));
+        metered.push(Instruction::Call(gas_function));
+        metered.extend_from_slice(&instructions[start..end]);
+        start = end;
+    }
+    metered
+}
+
+/// this function will add the host gas function to imports of the module & charge every
+/// function body through it. Functions defined by the module move one index up, calls,
+/// exports, table elements & start function are updated accordingly.
+pub fn inject_gas(module: Module) -> Result<Module, String> {
+    if let Some(imports) = module.import_section() {
+        if imports
+            .entries()
+            .iter()
+            .any(|entry| entry.module() == HOST_MODULE && entry.field() == GAS_FUNCTION)
+        {
+            return Err(format!(
+                "wasm app must not import host function {}",
+                GAS_FUNCTION
+            ));
+        }
+    }
+    // gas function is the last imported function, defined functions come after it
+    let gas_function: u32 = module.import_count(ImportCountType::Function) as u32;
+    let mut module_builder = builder::from_module(module);
+    let gas_signature: u32 =
+        module_builder.push_signature(builder::signature().with_param(ValueType::I32).build_sig());
+    module_builder.push_import(
+        builder::import()
+            .module(HOST_MODULE)
+            .field(GAS_FUNCTION)
+            .external()
+            .func(gas_signature)
+            .build(),
+    );
+    let mut module: Module = module_builder.build();
+    let shift = |index: &mut u32| {
+        if *index >= gas_function {
+            *index += 1;
+        }
+    };
+    for section in module.sections_mut() {
+        match section {
+            Section::Code(code) => {
+                for body in code.bodies_mut() {
+                    let instructions: &mut Vec<Instruction> = body.code_mut().elements_mut();
+                    for instruction in instructions.iter_mut() {
+                        if let Instruction::Call(index) = instruction {
+                            shift(index);
+                        }
+                    }
+                    *instructions = meter_body(instructions, gas_function);
+                }
+            }
+            Section::Export(exports) => {
+                for entry in exports.entries_mut() {
+                    if let Internal::Function(index) = entry.internal_mut() {
+                        shift(index);
+                    }
+                }
+            }
+            Section::Element(elements) => {
+                for segment in elements.entries_mut() {
+                    segment.members_mut().iter_mut().for_each(shift);
+                }
+            }
+            Section::Start(index) => shift(index),
+            _ => {}
+        }
+    }
+    Ok(module)
+}
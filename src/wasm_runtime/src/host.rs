@@ -0,0 +1,353 @@
+use sdk::state::State;
+use sdk::traits::{ReadStateContext, StateContext};
+use std::fmt;
+use wasmi::{
+    Error, Externals, FuncInstance, FuncRef, HostError, MemoryRef, ModuleImportResolver,
+    RuntimeArgs, RuntimeValue, Signature, Trap, TrapKind, ValueType,
+};
+
+/// module name wasm apps import host functions from
+pub const HOST_MODULE: &str = "env";
+
+/// host function charging gas, its import is injected into every app at load time and
+/// apps can't import it themselves
+pub const GAS_FUNCTION: &str = "gas";
+
+/// upper bound of a single key, value or output handed over by an app, in bytes
+pub const MAX_HOST_DATA: u32 = 1 << 20;
+
+/// StateAccess is what host functions of one call are allowed to touch
+pub enum StateAccess<'a> {
+    /// txn execution, state can be modified
+    Write(&'a mut dyn StateContext),
+    /// app query, state is read-only
+    Read(&'a dyn ReadStateContext),
+    /// calls which must not depend on state e.g. name or admission check
+    Empty,
+}
+
+/// HostTrap aborts the wasm call, it is raised on misuse of host functions
+#[derive(Debug)]
+pub struct HostTrap(pub String);
+
+impl fmt::Display for HostTrap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl HostError for HostTrap {}
+
+fn trap(reason: String) -> Trap {
+    Trap::new(TrapKind::Host(Box::new(HostTrap(reason))))
+}
+
+const STATE_GET: usize = 0;
+const STATE_PUT: usize = 1;
+const STATE_DELETE: usize = 2;
+const STATE_CONTAINS: usize = 3;
+const STORAGE_GET: usize = 4;
+const STORAGE_PUT: usize = 5;
+const STORAGE_DELETE: usize = 6;
+const EMIT_EVENT: usize = 7;
+const BLOCK_PRODUCER: usize = 8;
+const READ_BUFFER: usize = 9;
+const SET_OUTPUT: usize = 10;
+const GAS: usize = 11;
+
+struct HostFunction {
+    name: &'static str,
+    index: usize,
+    params: &'static [ValueType],
+    result: Option<ValueType>,
+}
+
+const I32: ValueType = ValueType::I32;
+
+/// host functions importable by wasm apps. Data is passed as (ptr, len) pairs of app memory,
+/// functions returning data place it in the host buffer and return its length (-1 if
+/// absent), the app copies it into its memory through read_buffer.
+const HOST_FUNCTIONS: &[HostFunction] = &[
+    HostFunction {
+        name: "state_get",
+        index: STATE_GET,
+        params: &[I32, I32],
+        result: Some(I32),
+    },
+    HostFunction {
+        name: "state_put",
+        index: STATE_PUT,
+        params: &[I32, I32, I32, I32],
+        result: None,
+    },
+    HostFunction {
+        name: "state_delete",
+        index: STATE_DELETE,
+        params: &[I32, I32],
+        result: None,
+    },
+    HostFunction {
+        name: "state_contains",
+        index: STATE_CONTAINS,
+        params: &[I32, I32],
+        result: Some(I32),
+    },
+    HostFunction {
+        name: "storage_get",
+        index: STORAGE_GET,
+        params: &[I32, I32, I32, I32],
+        result: Some(I32),
+    },
+    HostFunction {
+        name: "storage_put",
+        index: STORAGE_PUT,
+        params: &[I32, I32, I32, I32, I32, I32],
+        result: None,
+    },
+    HostFunction {
+        name: "storage_delete",
+        index: STORAGE_DELETE,
+        params: &[I32, I32, I32, I32],
+        result: None,
+    },
+    HostFunction {
+        name: "emit_event",
+        index: EMIT_EVENT,
+        params: &[I32, I32, I32, I32],
+        result: None,
+    },
+    HostFunction {
+        name: "block_producer",
+        index: BLOCK_PRODUCER,
+        params: &[],
+        result: Some(I32),
+    },
+    HostFunction {
+        name: "read_buffer",
+        index: READ_BUFFER,
+        params: &[I32],
+        result: None,
+    },
+    HostFunction {
+        name: "set_output",
+        index: SET_OUTPUT,
+        params: &[I32, I32],
+        result: None,
+    },
+    HostFunction {
+        name: GAS_FUNCTION,
+        index: GAS,
+        params: &[I32],
+        result: None,
+    },
+];
+
+/// HostImports resolves the imports of a wasm app, anything but the host functions above
+/// with their exact signature is refused at instantiation
+pub struct HostImports;
+
+impl ModuleImportResolver for HostImports {
+    fn resolve_func(&self, field_name: &str, signature: &Signature) -> Result<FuncRef, Error> {
+        let function: &HostFunction = match HOST_FUNCTIONS
+            .iter()
+            .find(|function| function.name == field_name)
+        {
+            Some(function) => function,
+            None => {
+                return Err(Error::Instantiation(format!(
+                    "host function {} doesn't exist",
+                    field_name
+                )))
+            }
+        };
+        let expected = Signature::new(function.params, function.result);
+        if signature != &expected {
+            return Err(Error::Instantiation(format!(
+                "host function {} must be imported as {:?}",
+                field_name, expected
+            )));
+        }
+        Ok(FuncInstance::alloc_host(expected, function.index))
+    }
+}
+
+/// HostExternals serves host function calls of one wasm call, the call traps once it
+/// is charged more than its gas limit
+pub struct HostExternals<'a> {
+    access: StateAccess<'a>,
+    memory: Option<MemoryRef>,
+    buffer: Vec<u8>,
+    output: Option<Vec<u8>>,
+    gas_left: u64,
+}
+
+impl<'a> HostExternals<'a> {
+    pub fn new(access: StateAccess<'a>, gas_limit: u64) -> HostExternals<'a> {
+        HostExternals {
+            access,
+            memory: None,
+            buffer: Vec::new(),
+            output: None,
+            gas_left: gas_limit,
+        }
+    }
+
+    /// memory is exported by the instance, it is known only once instantiated
+    pub fn set_memory(&mut self, memory: MemoryRef) {
+        self.memory = Some(memory);
+    }
+
+    /// data the app handed over through set_output, if any
+    pub fn take_output(&mut self) -> Option<Vec<u8>> {
+        self.output.take()
+    }
+
+    fn memory(&self) -> Result<&MemoryRef, Trap> {
+        match &self.memory {
+            Some(memory) => Ok(memory),
+            None => Err(trap(String::from(
+                "host functions can't be called from start",
+            ))),
+        }
+    }
+
+    /// bytes of the (ptr, len) pair starting at argument position
+    fn read_bytes(&self, args: &RuntimeArgs, position: usize) -> Result<Vec<u8>, Trap> {
+        let ptr: u32 = args.nth_checked(position)?;
+        let len: u32 = args.nth_checked(position + 1)?;
+        if len > MAX_HOST_DATA {
+            return Err(trap(format!("{} bytes exceed host data limit", len)));
+        }
+        self.memory()?
+            .get(ptr, len as usize)
+            .map_err(|error| trap(format!("out of bounds memory access: {}", error)))
+    }
+
+    fn read_string(&self, args: &RuntimeArgs, position: usize) -> Result<String, Trap> {
+        String::from_utf8(self.read_bytes(args, position)?)
+            .map_err(|_| trap(String::from("keys must be utf-8")))
+    }
+
+    fn state_context(&mut self) -> Result<&mut dyn StateContext, Trap> {
+        match &mut self.access {
+            StateAccess::Write(state_context) => Ok(&mut **state_context),
+            _ => Err(trap(String::from("state is read-only in this call"))),
+        }
+    }
+
+    fn get(&self, key: &String) -> Result<Option<State>, Trap> {
+        match &self.access {
+            StateAccess::Write(state_context) => Ok(state_context.get(key)),
+            StateAccess::Read(state_context) => Ok(state_context.get(key)),
+            StateAccess::Empty => Err(trap(String::from("state isn't accessible in this call"))),
+        }
+    }
+
+    fn get_storage(&self, account: &String, key: &String) -> Result<Option<Vec<u8>>, Trap> {
+        match &self.access {
+            StateAccess::Write(state_context) => Ok(state_context.get_storage(account, key)),
+            StateAccess::Read(state_context) => Ok(state_context.get_storage(account, key)),
+            StateAccess::Empty => Err(trap(String::from("state isn't accessible in this call"))),
+        }
+    }
+
+    /// place data in host buffer, the returned length is handed to the app
+    fn buffer(&mut self, data: Option<Vec<u8>>) -> Option<RuntimeValue> {
+        match data {
+            Some(data) => {
+                let len = data.len() as i32;
+                self.buffer = data;
+                Some(RuntimeValue::I32(len))
+            }
+            None => {
+                self.buffer.clear();
+                Some(RuntimeValue::I32(-1))
+            }
+        }
+    }
+}
+
+impl<'a> Externals for HostExternals<'a> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        match index {
+            STATE_GET => {
+                let key: String = self.read_string(&args, 0)?;
+                let data: Option<Vec<u8>> = self.get(&key)?.map(|state| state.get_data().clone());
+                Ok(self.buffer(data))
+            }
+            STATE_PUT => {
+                let key: String = self.read_string(&args, 0)?;
+                let data: Vec<u8> = self.read_bytes(&args, 2)?;
+                let state_context = self.state_context()?;
+                let mut state: State = state_context.get(&key).unwrap_or_else(State::new);
+                state.set_data(&data);
+                state_context.put(&key, state);
+                Ok(None)
+            }
+            STATE_DELETE => {
+                let key: String = self.read_string(&args, 0)?;
+                self.state_context()?.delete(&key);
+                Ok(None)
+            }
+            STATE_CONTAINS => {
+                let key: String = self.read_string(&args, 0)?;
+                let contains: bool = self.get(&key)?.is_some();
+                Ok(Some(RuntimeValue::I32(contains as i32)))
+            }
+            STORAGE_GET => {
+                let account: String = self.read_string(&args, 0)?;
+                let key: String = self.read_string(&args, 2)?;
+                let value: Option<Vec<u8>> = self.get_storage(&account, &key)?;
+                Ok(self.buffer(value))
+            }
+            STORAGE_PUT => {
+                let account: String = self.read_string(&args, 0)?;
+                let key: String = self.read_string(&args, 2)?;
+                let value: Vec<u8> = self.read_bytes(&args, 4)?;
+                self.state_context()?.put_storage(&account, &key, value);
+                Ok(None)
+            }
+            STORAGE_DELETE => {
+                let account: String = self.read_string(&args, 0)?;
+                let key: String = self.read_string(&args, 2)?;
+                self.state_context()?.delete_storage(&account, &key);
+                Ok(None)
+            }
+            EMIT_EVENT => {
+                let topic: String = self.read_string(&args, 0)?;
+                let data: Vec<u8> = self.read_bytes(&args, 2)?;
+                self.state_context()?.emit_event(&topic, data);
+                Ok(None)
+            }
+            BLOCK_PRODUCER => {
+                let producer: Option<String> = self.state_context()?.get_block_producer();
+                Ok(self.buffer(producer.map(String::into_bytes)))
+            }
+            READ_BUFFER => {
+                let ptr: u32 = args.nth_checked(0)?;
+                self.memory()?
+                    .set(ptr, &self.buffer)
+                    .map_err(|error| trap(format!("out of bounds memory access: {}", error)))?;
+                Ok(None)
+            }
+            SET_OUTPUT => {
+                self.output = Some(self.read_bytes(&args, 0)?);
+                Ok(None)
+            }
+            GAS => {
+                let gas: u32 = args.nth_checked(0)?;
+                if gas as u64 > self.gas_left {
+                    self.gas_left = 0;
+                    return Err(trap(String::from("out of gas")));
+                }
+                self.gas_left -= gas as u64;
+                Ok(None)
+            }
+            _ => Err(trap(format!("unknown host function index {}", index))),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! wasm_runtime runs apps compiled to WebAssembly as an alternative to native app
+//! libraries. Apps are interpreted by wasmi, so a third-party app can only reach state
+//! through the host functions in `host` and executes deterministically on every node.
+//! Every app is metered by `gas`, a call running past its gas limit traps.
+
+pub mod gas;
+pub mod host;
+pub mod wasm_app;
@@ -0,0 +1,376 @@
+use super::gas::inject_gas;
+use super::host::{HostExternals, HostImports, StateAccess, HOST_MODULE};
+use exonum_crypto::{hash, Hash};
+use parity_wasm::elements;
+use sdk::signed_transaction::SignedTransaction;
+use sdk::traits::{AppHandler, ReadStateContext, StateContext};
+use std::collections::HashMap;
+use std::fs;
+use utils::serializer::serialize;
+use wasmi::{ExternVal, ImportsBuilder, Module, ModuleInstance, ModuleRef, RuntimeValue};
+
+/// upper bound of linear memory a wasm app may declare, in 64KiB pages
+pub const MAX_MEMORY_PAGES: usize = 256;
+
+/// gas every call of an app export is given, one unit is charged per executed wasm
+/// instruction. A call running out of gas traps, so a looping app can't stall the node.
+pub const CALL_GAS_LIMIT: u64 = 10_000_000;
+
+/// status returned by app exports on success, any other status is a failure whose
+/// reason is the output of the call
+pub const STATUS_OK: i32 = 0;
+
+/// WasmApp runs an app compiled to WebAssembly inside the wasmi interpreter. Every call gets
+/// a fresh instance, so no state survives between calls except through StateContext.
+///
+/// App exports: `memory`, `alloc(len) -> ptr`, `name() -> status`,
+/// `check_transaction(ptr, len) -> status`, `execute(ptr, len) -> status` and
+/// `query(ptr, len) -> status`. Txns are passed in their canonical encoding, queries as
+/// cbor of (path, params sorted by name).
+pub struct WasmApp {
+    name: String,
+    code_hash: Hash,
+    module: Module,
+}
+
+impl WasmApp {
+    /// this function will read & validate the wasm module at given path
+    pub fn load(app_path: &str) -> Result<WasmApp, String> {
+        match fs::read(app_path) {
+            Ok(code) => WasmApp::from_code(&code),
+            Err(error) => Err(format!("wasm app {} couldn't load: {}", app_path, error)),
+        }
+    }
+
+    /// modules using floating point are refused as it isn't deterministic across nodes,
+    /// imports & memory limits are checked by instantiating it for its name. Gas metering
+    /// is injected before the module is validated, code hash is of the given code.
+    pub fn from_code(code: &[u8]) -> Result<WasmApp, String> {
+        let module: elements::Module = match parity_wasm::deserialize_buffer(code) {
+            Ok(module) => module,
+            Err(error) => return Err(format!("wasm module is invalid: {}", error)),
+        };
+        let module: Module = match Module::from_parity_wasm_module(inject_gas(module)?) {
+            Ok(module) => module,
+            Err(error) => return Err(format!("wasm module is invalid: {}", error)),
+        };
+        if module.deny_floating_point().is_err() {
+            return Err(String::from("wasm module uses floating point"));
+        }
+        let mut app = WasmApp {
+            name: String::new(),
+            code_hash: hash(code),
+            module,
+        };
+        let output: Vec<u8> = app.call("name", None, StateAccess::Empty)?;
+        app.name = match String::from_utf8(output) {
+            Ok(name) if !name.is_empty() => name,
+            _ => return Err(String::from("wasm app name must be non empty utf-8")),
+        };
+        Ok(app)
+    }
+
+    /// hash of the module bytes, identifies the exact code an app runs
+    pub fn code_hash(&self) -> Hash {
+        self.code_hash
+    }
+
+    fn instantiate(&self, externals: &mut HostExternals) -> Result<ModuleRef, String> {
+        let imports = ImportsBuilder::new().with_resolver(HOST_MODULE, &HostImports);
+        let instance: ModuleRef = match ModuleInstance::new(&self.module, &imports) {
+            Ok(not_started) => match not_started.run_start(externals) {
+                Ok(instance) => instance,
+                Err(error) => return Err(format!("wasm app start failed: {}", error)),
+            },
+            Err(error) => return Err(format!("wasm app instantiation failed: {}", error)),
+        };
+        let memory = match instance.export_by_name("memory") {
+            Some(ExternVal::Memory(memory)) => memory,
+            _ => return Err(String::from("wasm app must export its memory")),
+        };
+        match memory.maximum() {
+            Some(maximum) if maximum.0 <= MAX_MEMORY_PAGES => {}
+            _ => {
+                return Err(format!(
+                    "wasm app memory must declare a maximum of at most {} pages",
+                    MAX_MEMORY_PAGES
+                ))
+            }
+        }
+        externals.set_memory(memory);
+        Ok(instance)
+    }
+
+    /// this function will call given export with input copied into app memory, Ok carries
+    /// the output of a successful call and Err the failure reason
+    fn call(
+        &self,
+        export: &str,
+        input: Option<&[u8]>,
+        access: StateAccess,
+    ) -> Result<Vec<u8>, String> {
+        let mut externals = HostExternals::new(access, CALL_GAS_LIMIT);
+        let instance: ModuleRef = self.instantiate(&mut externals)?;
+        let args: Vec<RuntimeValue> = match input {
+            Some(input) => {
+                let len = RuntimeValue::I32(input.len() as i32);
+                let ptr: i32 = match instance.invoke_export("alloc", &[len], &mut externals) {
+                    Ok(Some(RuntimeValue::I32(ptr))) => ptr,
+                    Ok(_) => return Err(String::from("wasm app alloc must return a pointer")),
+                    Err(error) => return Err(format!("wasm app alloc failed: {}", error)),
+                };
+                let memory = match instance.export_by_name("memory") {
+                    Some(ExternVal::Memory(memory)) => memory,
+                    _ => return Err(String::from("wasm app must export its memory")),
+                };
+                if let Err(error) = memory.set(ptr as u32, input) {
+                    return Err(format!("wasm app input doesn't fit: {}", error));
+                }
+                vec![RuntimeValue::I32(ptr), len]
+            }
+            None => Vec::new(),
+        };
+        let status: i32 = match instance.invoke_export(export, &args, &mut externals) {
+            Ok(Some(RuntimeValue::I32(status))) => status,
+            Ok(_) => return Err(format!("wasm app {} must return a status", export)),
+            Err(error) => return Err(format!("wasm app {} failed: {}", export, error)),
+        };
+        let output: Vec<u8> = externals.take_output().unwrap_or_default();
+        if status == STATUS_OK {
+            Ok(output)
+        } else if output.is_empty() {
+            Err(format!("wasm app {} returned status {}", export, status))
+        } else {
+            Err(String::from_utf8_lossy(&output).into_owned())
+        }
+    }
+}
+
+impl AppHandler for WasmApp {
+    fn execute(
+        &self,
+        txn: &SignedTransaction,
+        state_context: &mut dyn StateContext,
+    ) -> Result<(), String> {
+        let input: Vec<u8> = txn.canonical_bytes();
+        self.call(
+            "execute",
+            Some(&input),
+            StateAccess::Write(&mut *state_context),
+        )?;
+        Ok(())
+    }
+
+    fn check_transaction(&self, txn: &SignedTransaction) -> Result<(), String> {
+        if txn.app_name != self.name {
+            return Err(format!("txn is addressed to {}", txn.app_name));
+        }
+        let input: Vec<u8> = txn.canonical_bytes();
+        self.call("check_transaction", Some(&input), StateAccess::Empty)?;
+        Ok(())
+    }
+
+    /// wasm apps don't report senders, their pending txns are bounded by the per app
+    /// limit of txn pool
+    fn sender(&self, _txn: &SignedTransaction) -> Option<String> {
+        None
+    }
+
+    fn nonce(&self, _txn: &SignedTransaction) -> Option<u64> {
+        None
+    }
+
+    fn next_nonce(&self, _sender: &String, _state_context: &dyn StateContext) -> Option<u64> {
+        None
+    }
+
+    fn fee(&self, _txn: &SignedTransaction) -> u64 {
+        0
+    }
+
+    fn query(
+        &self,
+        path: &String,
+        params: &HashMap<String, String>,
+        state_context: &dyn ReadStateContext,
+    ) -> Result<Vec<u8>, String> {
+        let mut params: Vec<(&String, &String)> = params.iter().collect();
+        params.sort();
+        let input: Vec<u8> = serialize(&(path, params))?;
+        self.call("query", Some(&input), StateAccess::Read(state_context))
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod test_wasm_app {
+    use super::*;
+    use sdk::test_utils::MemoryStateContext;
+
+    /// counter app: execute stores its input under key "count" and emits it as event,
+    /// query returns the stored input
+    const COUNTER_APP: &str = r#"
+        (module
+            (import "env" "state_get" (func $state_get (param i32 i32) (result i32)))
+            (import "env" "state_put" (func $state_put (param i32 i32 i32 i32)))
+            (import "env" "emit_event" (func $emit_event (param i32 i32 i32 i32)))
+            (import "env" "read_buffer" (func $read_buffer (param i32)))
+            (import "env" "set_output" (func $set_output (param i32 i32)))
+            (memory (export "memory") 1 2)
+            (data (i32.const 0) "counter")
+            (data (i32.const 16) "count")
+            (data (i32.const 32) "count missing")
+            (global $heap (mut i32) (i32.const 1024))
+            (func (export "alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $heap))
+                (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                (local.get $ptr))
+            (func (export "name") (result i32)
+                (call $set_output (i32.const 0) (i32.const 7))
+                (i32.const 0))
+            (func (export "check_transaction") (param $ptr i32) (param $len i32) (result i32)
+                (i32.const 0))
+            (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                (call $state_put (i32.const 16) (i32.const 5) (local.get $ptr) (local.get $len))
+                (call $emit_event (i32.const 16) (i32.const 5) (local.get $ptr) (local.get $len))
+                (i32.const 0))
+            (func (export "query") (param $ptr i32) (param $len i32) (result i32)
+                (local $found i32)
+                (local.set $found (call $state_get (i32.const 16) (i32.const 5)))
+                (if (i32.lt_s (local.get $found) (i32.const 0))
+                    (then
+                        (call $set_output (i32.const 32) (i32.const 13))
+                        (return (i32.const 1))))
+                (call $read_buffer (i32.const 2048))
+                (call $set_output (i32.const 2048) (local.get $found))
+                (i32.const 0)))
+    "#;
+
+    fn counter_txn(app_name: &str) -> SignedTransaction {
+        SignedTransaction {
+            txn: vec![7, 7],
+            app_name: String::from(app_name),
+            header: HashMap::new(),
+            signature: vec![],
+        }
+    }
+
+    #[test]
+    fn test_wasm_app_execute_and_query() {
+        let code: Vec<u8> = wat::parse_str(COUNTER_APP).unwrap();
+        let app: WasmApp = WasmApp::from_code(&code).unwrap();
+        assert_eq!(app.name(), "counter");
+        assert_eq!(app.code_hash(), hash(&code));
+
+        let mut state_context = MemoryStateContext::new();
+        let path = String::from("count");
+        assert_eq!(
+            app.query(&path, &HashMap::new(), &state_context.read_only()),
+            Err(String::from("count missing"))
+        );
+
+        let txn: SignedTransaction = counter_txn("counter");
+        assert_eq!(app.check_transaction(&txn), Ok(()));
+        assert!(app.check_transaction(&counter_txn("other_app")).is_err());
+        assert_eq!(app.execute(&txn, &mut state_context), Ok(()));
+        assert_eq!(
+            state_context.events,
+            vec![(String::from("count"), txn.canonical_bytes())]
+        );
+        assert_eq!(
+            app.query(&path, &HashMap::new(), &state_context.read_only()),
+            Ok(txn.canonical_bytes())
+        );
+    }
+
+    #[test]
+    fn test_wasm_app_sandbox() {
+        let float_app = r#"
+            (module
+                (memory (export "memory") 1 1)
+                (func (export "name") (result i32)
+                    (drop (f32.add (f32.const 1) (f32.const 2)))
+                    (i32.const 0)))
+        "#;
+        let unbounded_memory = r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "name") (result i32) (i32.const 0)))
+        "#;
+        let foreign_import = r#"
+            (module
+                (import "env" "system" (func $system (param i32)))
+                (memory (export "memory") 1 1)
+                (func (export "name") (result i32) (i32.const 0)))
+        "#;
+        let gas_import = r#"
+            (module
+                (import "env" "gas" (func $gas (param i32)))
+                (memory (export "memory") 1 1)
+                (func (export "name") (result i32) (i32.const 0)))
+        "#;
+        for (module, reason) in [
+            (float_app, "floating point"),
+            (unbounded_memory, "maximum"),
+            (foreign_import, "doesn't exist"),
+            (gas_import, "must not import host function gas"),
+        ]
+        .iter()
+        {
+            let code: Vec<u8> = wat::parse_str(module).unwrap();
+            match WasmApp::from_code(&code) {
+                Ok(_) => panic!("module with {} is loaded", reason),
+                Err(error) => assert!(error.contains(reason), "{}", error),
+            }
+        }
+    }
+
+    #[test]
+    fn test_wasm_app_out_of_gas() {
+        // execute loops forever, check_transaction reaches the same loop through the table
+        let looping_app = r#"
+            (module
+                (import "env" "set_output" (func $set_output (param i32 i32)))
+                (memory (export "memory") 1 1)
+                (data (i32.const 0) "looping")
+                (table 1 funcref)
+                (elem (i32.const 0) $spin)
+                (type $spin_type (func (param i32)))
+                (func $spin (param $depth i32)
+                    (loop $forever
+                        (local.set $depth (i32.add (local.get $depth) (i32.const 1)))
+                        (br $forever)))
+                (func (export "alloc") (param $len i32) (result i32) (i32.const 1024))
+                (func (export "name") (result i32)
+                    (call $set_output (i32.const 0) (i32.const 7))
+                    (i32.const 0))
+                (func (export "check_transaction") (param $ptr i32) (param $len i32) (result i32)
+                    (call_indirect (type $spin_type) (i32.const 0) (i32.const 0))
+                    (i32.const 0))
+                (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                    (call $spin (i32.const 0))
+                    (i32.const 0)))
+        "#;
+        let code: Vec<u8> = wat::parse_str(looping_app).unwrap();
+        let app: WasmApp = WasmApp::from_code(&code).unwrap();
+        assert_eq!(app.name(), "looping");
+        let txn: SignedTransaction = counter_txn("looping");
+        let mut state_context = MemoryStateContext::new();
+        for result in [
+            app.execute(&txn, &mut state_context),
+            app.check_transaction(&txn),
+        ]
+        .iter()
+        {
+            assert!(
+                result.as_ref().unwrap_err().contains("out of gas"),
+                "{:?}",
+                result
+            );
+        }
+    }
+}